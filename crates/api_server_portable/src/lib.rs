@@ -0,0 +1,69 @@
+use actix_web::{error, get, web, Result};
+use dt_core::{
+    portable::Portable,
+    search::SearchResult,
+    tracker::shared::{SharedTracker, DEFAULT_CACHE_CAPACITY},
+};
+use serde::{Deserialize, Serialize};
+use std::sync::RwLock;
+
+// The traced graphs are cached for the portable, a new state starts with an empty cache.
+pub struct AppState {
+    pub portable: Portable,
+    pub tracker: SharedTracker,
+}
+
+impl AppState {
+    // `cache_capacity` in bytes
+    pub fn new(portable: Portable, cache_capacity: usize) -> Self {
+        Self {
+            portable,
+            tracker: SharedTracker::new(cache_capacity),
+        }
+    }
+}
+
+impl From<Portable> for AppState {
+    fn from(portable: Portable) -> Self {
+        Self::new(portable, DEFAULT_CACHE_CAPACITY)
+    }
+}
+
+// The state is shared by all workers, so it can be replaced when the project is
+// analyzed again, see the `watch` command of `cli`. The cache of the old graph goes
+// with it.
+pub type SharedAppState = RwLock<AppState>;
+
+#[derive(Serialize)]
+struct SearchResponse {
+    project_root: String,
+    trace_result: SearchResult,
+}
+
+#[derive(Deserialize)]
+struct Info {
+    q: String,
+    exact_match: bool,
+}
+
+// The search runs over the portable, see `dt_core::search`.
+#[get("/search")]
+pub async fn search(
+    data: web::Data<SharedAppState>,
+    info: web::Query<Info>,
+) -> Result<web::Json<SearchResponse>> {
+    let data = data
+        .read()
+        .map_err(|_| error::ErrorInternalServerError("app state is poisoned"))?;
+    let trace_result =
+        dt_core::search::search(&data.portable, &data.tracker, &info.q, info.exact_match)
+            .map_err(error::ErrorInternalServerError)?;
+    if trace_result.is_empty() {
+        return Err(error::ErrorNotFound(format!("No result for {}", info.q)));
+    }
+
+    Ok(web::Json(SearchResponse {
+        project_root: data.portable.project_root.to_owned(),
+        trace_result,
+    }))
+}
@@ -1,28 +1,42 @@
-use anyhow::Context;
-use clap::{Parser, Subcommand};
+mod watch;
+
+use anyhow::{bail, Context};
+use clap::{Args, Parser, Subcommand, ValueEnum};
 use dt_core::{
+    analyzer::{Analysis, Analyzer, AnalyzerConfig, AnalyzerEvent},
+    boundary::{check_rules, Rules},
+    cache::ParsedModule,
     database::{models, Database, SqliteDb},
-    graph::{depend_on_graph::DependOnGraph, used_by_graph::UsedByGraph},
-    i18n::{collect_translation, I18nToSymbol},
+    dead_code::find_dead_code,
+    diff::{git, parse_unified_diff},
+    graph::projection::{Projection, Weight},
+    impact::{analyze_impact, OldSide},
+    metrics::{compute_metrics, RankBy},
     parser::{
         anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT,
-        collect_symbol_dependency,
         types::{FromOtherModule, FromType, ModuleExport, ModuleScopedVariable, SymbolDependency},
-        Input,
     },
-    path_resolver::{PathResolver, ToCanonicalString},
-    portable::Portable,
-    route::{collect_route_dependency, Route, SymbolToRoutes},
-    scheduler::ParserCandidateScheduler,
+    path_resolver::{to_relative_path, PathResolver},
+    portable::{Format, Portable},
+    route::Route,
+    scheduler::DiscoveryOptions,
+    snapshot_diff::Snapshot,
+    tracker::{DependOnTracker, DependencyTracker, ModuleSymbol, TraceLimits, TraceTarget},
+    visualize::{self, Granularity, Graph, TraceDirection},
 };
 use indicatif::{ProgressBar, ProgressStyle};
+use serde::Serialize;
 use std::{
-    collections::{HashMap, HashSet},
-    fs::File,
-    io::{BufReader, Write},
-    path::PathBuf,
+    collections::{BTreeMap, HashMap, HashSet},
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::Duration,
 };
 
+// The edges from the clean modules to the symbols of a cleared module.
+type UsedByEdges = Vec<(models::Module, Vec<(models::Symbol, models::Symbol)>)>;
+
 #[derive(Parser)]
 #[command(version, about = "Parse a project and serialize its output", long_about = None)]
 struct Cli {
@@ -34,184 +48,1061 @@ struct Cli {
 enum Command {
     /// Parse and export the project in portable format
     Portable {
-        /// Input path
-        #[arg(short)]
-        input: String,
-
-        /// translation.json path
-        #[arg(short)]
-        translation_path: String,
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
 
         /// Output path
         #[arg(short)]
         output: String,
+
+        /// Format of the portable, json is easier to debug while binary is smaller and
+        /// faster to load
+        #[arg(long, value_enum, default_value_t = PortableFormat::Json)]
+        format: PortableFormat,
+    },
+
+    /// Parse the project, serve the search API and keep it updated when files change
+    Watch {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        /// Port of the search API
+        #[arg(long, default_value_t = 8080)]
+        port: u16,
+
+        /// The memory for caching the traced graphs, in MiB
+        #[arg(long, default_value_t = 256)]
+        cache_size: usize,
     },
 
     /// Parse and export the project in database format
     Database {
-        /// Input path
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        /// Output path
         #[arg(short)]
-        input: String,
+        output: String,
+    },
+
+    /// Map a diff to the changed symbols and report the routes, i18n keys and entry
+    /// points affected by them
+    Impact {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        /// Unified diff file, use `-` to read from stdin
+        #[arg(long, conflicts_with = "from", required_unless_present = "from")]
+        diff: Option<String>,
 
-        /// translation.json path
+        /// Diff the repository from this revision
+        #[arg(long)]
+        from: Option<String>,
+
+        /// Diff the repository to this revision [default: the working tree]
+        #[arg(long, requires = "from")]
+        to: Option<String>,
+
+        /// Root of the repository, the paths in the diff are relative to it
+        /// [default: the git top level of the input path]
+        #[arg(long)]
+        repo: Option<String>,
+
+        /// Output path, the report is printed if it's not given
         #[arg(short)]
-        translation_path: String,
+        output: Option<String>,
+    },
 
-        /// Output path
+    /// Report the unreachable modules, unused exports and unused local variables from
+    /// the entry points
+    DeadCode {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        /// Globs of the entry modules relative to the input path, e.g. route files,
+        /// main.tsx or the modules exported by the package
+        #[arg(long = "entry", required = true)]
+        entry_points: Vec<String>,
+
+        /// Output path, the report is printed if it's not given
         #[arg(short)]
-        output: String,
+        output: Option<String>,
+    },
+
+    /// Export the whole graph, or the subgraph traced from a symbol, for visualization
+    Graph {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        #[arg(long, value_enum, default_value_t = GraphFormat::Dot)]
+        format: GraphFormat,
+
+        /// Draw the symbols grouped by modules, or only the modules, directories or
+        /// packages
+        #[arg(long, value_enum, default_value_t = GraphGranularity::Symbol)]
+        granularity: GraphGranularity,
+
+        /// Number of directories to group the modules by, for the directory granularity
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+
+        #[command(flatten)]
+        trace: TraceArgs,
+
+        /// Output path, the graph is printed if it's not given
+        #[arg(short)]
+        output: Option<String>,
+    },
+
+    /// Query the module, directory or package graph, e.g. the dependencies of a module
+    /// or the circular dependencies between packages
+    Deps {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        #[arg(long, value_enum, default_value_t = ProjectionLevel::Module)]
+        level: ProjectionLevel,
+
+        /// Number of directories to group the modules by, for the directory level
+        #[arg(long, default_value_t = 1)]
+        depth: usize,
+
+        #[command(flatten)]
+        query: DepsQuery,
+
+        /// Output path, the result is printed if it's not given
+        #[arg(short)]
+        output: Option<String>,
+    },
+
+    /// Rank the symbols and modules by fan-in, fan-out, transitive dependents, routes
+    /// reached or bridge scores
+    Hotspots {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        /// Globs of the entry modules relative to the input path, the depths are
+        /// counted from them
+        #[arg(long = "entry")]
+        entry_points: Vec<String>,
+
+        #[arg(long, value_enum, default_value_t = HotspotRank::TransitiveDependents)]
+        rank_by: HotspotRank,
+
+        /// Number of symbols and modules to report
+        #[arg(long, default_value_t = 50)]
+        limit: usize,
+
+        /// Only rank the named and default exports
+        #[arg(long)]
+        exports_only: bool,
+
+        /// Compute the bridge scores from this many symbols only, for large projects
+        #[arg(long)]
+        bridge_samples: Option<usize>,
+
+        /// Output path, the report is printed if it's not given
+        #[arg(short)]
+        output: Option<String>,
+    },
+
+    /// Check the dependencies against the boundary rules, exit with code 1 if any of
+    /// them is violated
+    Check {
+        #[command(flatten)]
+        analyzer: AnalyzerArgs,
+
+        /// Path of the rules file in JSON
+        #[arg(long)]
+        rules: String,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Output path, the violations are printed if it's not given
+        #[arg(short)]
+        output: Option<String>,
+    },
+
+    /// Compare two snapshots of the project, e.g. two releases, and report the added and
+    /// removed modules, symbols, dependencies, routes and i18n keys
+    Compare {
+        /// Path of the old portable or database
+        #[arg(long)]
+        old: String,
+
+        /// Path of the new portable or database
+        #[arg(long)]
+        new: String,
+
+        /// Project name in the databases
+        #[arg(long, default_value = "default_project")]
+        project: String,
+
+        #[arg(long, value_enum, default_value_t = ReportFormat::Text)]
+        format: ReportFormat,
+
+        /// Output path, the report is printed if it's not given
+        #[arg(short)]
+        output: Option<String>,
     },
 }
 
+#[derive(Args)]
+#[group(required = true, multiple = false)]
+struct DepsQuery {
+    /// The nodes this one depends on, with the number of symbol edges to each of them
+    #[arg(long)]
+    dependencies: Option<String>,
+
+    /// The nodes depending on this one, with the number of symbol edges from each of
+    /// them
+    #[arg(long)]
+    dependents: Option<String>,
+
+    /// The nodes this one depends on directly or indirectly
+    #[arg(long)]
+    transitive_dependencies: Option<String>,
+
+    /// The nodes depending on this one directly or indirectly
+    #[arg(long)]
+    transitive_dependents: Option<String>,
+
+    /// One of the shortest dependency chains between two nodes
+    #[arg(long, num_args = 2, value_names = ["FROM", "TO"])]
+    path: Option<Vec<String>>,
+
+    /// The groups of nodes depending on each other
+    #[arg(long)]
+    cycles: bool,
+
+    /// The nodes neither depending on nor used by other nodes
+    #[arg(long)]
+    orphans: bool,
+}
+
+#[derive(Args)]
+struct TraceArgs {
+    /// Only export the paths traced from a symbol of this module, the path is relative
+    /// to the input path
+    #[arg(long, requires = "symbol")]
+    trace: Option<String>,
+
+    /// The traced local variable
+    #[arg(long, group = "symbol", requires = "trace")]
+    local: Option<String>,
+
+    /// The traced named export
+    #[arg(long, group = "symbol", requires = "trace")]
+    named_export: Option<String>,
+
+    /// Trace the default export
+    #[arg(long, group = "symbol", requires = "trace")]
+    default_export: bool,
+
+    /// Trace the symbols the traced one depends on, instead of the ones using it
+    #[arg(long, requires = "trace")]
+    depend_on: bool,
+
+    /// Stop tracing at the symbols this many steps away
+    #[arg(long, requires = "trace")]
+    max_depth: Option<usize>,
+
+    /// Globs of the modules to stop tracing at, e.g. the app shell, relative to the input
+    /// path
+    #[arg(long = "stop-at", requires = "trace")]
+    stop_modules: Vec<String>,
+
+    /// Stop tracing after this many seconds
+    #[arg(long, requires = "trace")]
+    time_budget: Option<f64>,
+}
+
+impl TraceArgs {
+    fn to_module_symbol(&self) -> Option<ModuleSymbol> {
+        let module_path = self.trace.clone()?;
+        let trace_target = match (&self.local, &self.named_export) {
+            (Some(name), _) => TraceTarget::LocalVar(name.clone()),
+            (_, Some(name)) => TraceTarget::NamedExport(name.clone()),
+            _ => TraceTarget::DefaultExport,
+        };
+        Some((module_path, trace_target))
+    }
+
+    fn to_limits(&self) -> TraceLimits {
+        TraceLimits {
+            max_depth: self.max_depth,
+            time_budget: self.time_budget.map(Duration::from_secs_f64),
+            stop_modules: self.stop_modules.clone(),
+            ..TraceLimits::default()
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphFormat {
+    Dot,
+    Graphml,
+    Mermaid,
+    Cytoscape,
+}
+
+impl From<GraphFormat> for visualize::Format {
+    fn from(format: GraphFormat) -> Self {
+        match format {
+            GraphFormat::Dot => visualize::Format::Dot,
+            GraphFormat::Graphml => visualize::Format::GraphMl,
+            GraphFormat::Mermaid => visualize::Format::Mermaid,
+            GraphFormat::Cytoscape => visualize::Format::Cytoscape,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum GraphGranularity {
+    Symbol,
+    Module,
+    Directory,
+    Package,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum HotspotRank {
+    FanIn,
+    FanOut,
+    TransitiveDependents,
+    Routes,
+    BridgeScore,
+}
+
+impl From<HotspotRank> for RankBy {
+    fn from(rank: HotspotRank) -> Self {
+        match rank {
+            HotspotRank::FanIn => RankBy::FanIn,
+            HotspotRank::FanOut => RankBy::FanOut,
+            HotspotRank::TransitiveDependents => RankBy::TransitiveDependents,
+            HotspotRank::Routes => RankBy::Routes,
+            HotspotRank::BridgeScore => RankBy::BridgeScore,
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ReportFormat {
+    Text,
+    Json,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ProjectionLevel {
+    Module,
+    Directory,
+    Package,
+}
+
+impl ProjectionLevel {
+    fn as_str(&self) -> &'static str {
+        match self {
+            ProjectionLevel::Module => "module",
+            ProjectionLevel::Directory => "directory",
+            ProjectionLevel::Package => "package",
+        }
+    }
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum PortableFormat {
+    Json,
+    Binary,
+}
+
+impl From<PortableFormat> for Format {
+    fn from(format: PortableFormat) -> Self {
+        match format {
+            PortableFormat::Json => Format::Json,
+            PortableFormat::Binary => Format::Binary,
+        }
+    }
+}
+
+#[derive(Args)]
+struct AnalyzerArgs {
+    /// Input path
+    #[arg(short)]
+    input: String,
+
+    /// translation.json path
+    #[arg(short)]
+    translation_path: String,
+
+    #[command(flatten)]
+    discovery: DiscoveryArgs,
+
+    /// Keep parsing when modules do wildcard exports or namespace imports from each
+    /// other, the wildcard exports in the cycle are expanded partially
+    #[arg(long)]
+    break_cycles: bool,
+
+    /// Reuse the parsed modules whose content is not changed since the last run
+    #[arg(long)]
+    cache_dir: Option<String>,
+}
+
+impl AnalyzerArgs {
+    fn to_config(&self) -> AnalyzerConfig {
+        AnalyzerConfig {
+            project_root: self.input.clone(),
+            translation_file_path: Some(self.translation_path.clone()),
+            discovery_options: self.discovery.to_options(),
+            break_cycles: self.break_cycles,
+            cache_dir: self.cache_dir.clone(),
+        }
+    }
+}
+
+#[derive(Args)]
+struct DiscoveryArgs {
+    /// Only parse the files matching these globs, relative to the input path
+    /// [default: **/*.js, **/*.jsx, **/*.ts, **/*.tsx]
+    #[arg(long)]
+    include: Vec<String>,
+
+    /// Skip the files and directories matching these globs, relative to the input path.
+    /// Tests, node_modules, dist and coverage are always skipped
+    #[arg(long)]
+    exclude: Vec<String>,
+
+    /// Don't respect .gitignore and .ignore files
+    #[arg(long)]
+    no_ignore: bool,
+
+    /// Walk into hidden directories
+    #[arg(long)]
+    hidden: bool,
+}
+
+impl DiscoveryArgs {
+    fn to_options(&self) -> DiscoveryOptions {
+        let mut options = DiscoveryOptions::default();
+        if !self.include.is_empty() {
+            options.include = self.include.clone();
+        }
+        options.exclude.extend(self.exclude.iter().cloned());
+        options.respect_ignore_files = !self.no_ignore;
+        options.skip_hidden = !self.hidden;
+        options
+    }
+}
+
 fn main() -> anyhow::Result<()> {
     match Cli::parse().command {
         Command::Portable {
-            input,
-            translation_path,
+            analyzer,
             output,
+            format,
         } => {
-            parse_and_export_project_to_portable(&input, &output, &translation_path)
+            parse_and_export_project_to_portable(analyzer.to_config(), &output, format.into())
                 .context("parse and export project to portable")?;
         }
-        Command::Database {
-            input,
-            translation_path,
-            output,
+        Command::Watch {
+            analyzer,
+            port,
+            cache_size,
         } => {
-            parse_and_export_project_to_database(&input, &output, &translation_path)
+            watch::watch_project(analyzer.to_config(), port, cache_size)
+                .context("watch project")?;
+        }
+        Command::Database { analyzer, output } => {
+            parse_and_export_project_to_database(analyzer.to_config(), &output)
                 .context("parse and export project to database")?;
         }
+        Command::Impact {
+            analyzer,
+            diff,
+            from,
+            to,
+            repo,
+            output,
+        } => {
+            let repo_root = match repo {
+                Some(repo) => repo,
+                None => git::get_toplevel(&analyzer.input)
+                    .context("find the repository of the input path, try `--repo`")?,
+            };
+            match (diff, from) {
+                (Some(diff_path), _) => report_impact(
+                    analyzer.to_config(),
+                    &repo_root,
+                    &read_diff(&diff_path)?,
+                    output.as_deref(),
+                )
+                .context("report the impact of the diff")?,
+                (None, Some(from)) => report_impact_of_revisions(
+                    analyzer.to_config(),
+                    &repo_root,
+                    &from,
+                    to.as_deref(),
+                    output.as_deref(),
+                )
+                .context("report the impact between the revisions")?,
+                (None, None) => unreachable!("either `--diff` or `--from` is required"),
+            }
+        }
+        Command::DeadCode {
+            analyzer,
+            entry_points,
+            output,
+        } => {
+            report_dead_code(analyzer.to_config(), &entry_points, output.as_deref())
+                .context("report the dead code")?;
+        }
+        Command::Graph {
+            analyzer,
+            format,
+            granularity,
+            depth,
+            trace,
+            output,
+        } => {
+            export_graph(
+                analyzer.to_config(),
+                &trace,
+                format.into(),
+                granularity,
+                depth,
+                output.as_deref(),
+            )
+            .context("export the graph")?;
+        }
+        Command::Deps {
+            analyzer,
+            level,
+            depth,
+            query,
+            output,
+        } => {
+            query_deps(
+                analyzer.to_config(),
+                level,
+                depth,
+                &query,
+                output.as_deref(),
+            )
+            .context("query the dependencies")?;
+        }
+        Command::Hotspots {
+            analyzer,
+            entry_points,
+            rank_by,
+            limit,
+            exports_only,
+            bridge_samples,
+            output,
+        } => {
+            report_hotspots(
+                analyzer.to_config(),
+                &entry_points,
+                rank_by.into(),
+                limit,
+                exports_only,
+                bridge_samples,
+                output.as_deref(),
+            )
+            .context("report the hotspots")?;
+        }
+        Command::Check {
+            analyzer,
+            rules,
+            format,
+            output,
+        } => {
+            let violated =
+                check_boundaries(analyzer.to_config(), &rules, format, output.as_deref())
+                    .context("check the boundary rules")?;
+            if violated {
+                std::process::exit(1);
+            }
+        }
+        Command::Compare {
+            old,
+            new,
+            project,
+            format,
+            output,
+        } => {
+            compare_snapshots(&old, &new, &project, format, output.as_deref())
+                .context("compare the snapshots")?;
+        }
     }
     Ok(())
 }
 
-fn parse_and_export_project_to_portable(
-    project_root: &str,
-    output_portable_path: &str,
-    translation_file_path: &str,
-) -> anyhow::Result<()> {
-    let project_root = PathBuf::from(project_root).to_canonical_string()?;
-    let translation_json = File::open(&translation_file_path)?;
-    let translation_json_reader = BufReader::new(translation_json);
-
-    let mut scheduler = ParserCandidateScheduler::new(&project_root);
-    let mut depend_on_graph = DependOnGraph::new(&project_root);
-    let mut symbol_to_route = SymbolToRoutes::new();
-    let mut i18n_to_symbol = I18nToSymbol::new();
-    loop {
-        match scheduler.get_one_candidate() {
-            Some(c) => {
-                let module_src = c.to_str().context(format!("to_str() failed: {:?}", c))?;
-                let module_ast = Input::Path(module_src).get_module_ast()?;
-                let symbol_dependency = collect_symbol_dependency(&module_ast, module_src)?;
-                i18n_to_symbol.collect_i18n_usage(module_src, &module_ast)?;
-                symbol_to_route.collect_route_dependency(&module_ast, &symbol_dependency)?;
-
-                depend_on_graph.add_symbol_dependency(symbol_dependency)?;
-                scheduler.mark_candidate_as_parsed(c);
-            }
-            None => break,
+// Print the diagnostics, the progress is only shown for the database format since it's
+// much slower.
+fn print_event(event: AnalyzerEvent) {
+    match event {
+        AnalyzerEvent::CycleBroken(cycle) => eprintln!("break the cycle {}", cycle),
+        AnalyzerEvent::AmbiguousExport(ambiguous_export) => {
+            eprintln!("skip the export {}", ambiguous_export)
         }
+        AnalyzerEvent::Finished {
+            from_cache_count, ..
+        } if from_cache_count > 0 => {
+            eprintln!("{} modules are reused from the cache", from_cache_count)
+        }
+        _ => (),
     }
+}
 
-    let portable = Portable::new(
-        project_root.to_owned(),
-        serde_json::from_reader(translation_json_reader)?,
-        i18n_to_symbol.table,
-        symbol_to_route.table,
-        UsedByGraph::from(&depend_on_graph),
-    );
+fn parse_and_export_project_to_portable(
+    config: AnalyzerConfig,
+    output_portable_path: &str,
+    format: Format,
+) -> anyhow::Result<()> {
+    let mut analyzer = Analyzer::new(config)?;
+    let portable = analyzer.run(print_event)?.into_portable();
 
-    let serialized = portable.export()?;
-    let mut file = File::create(&output_portable_path)?;
-    file.write_all(serialized.as_bytes())?;
+    let serialized = portable.export_as(format)?;
+    let mut file = File::create(output_portable_path)?;
+    file.write_all(&serialized)?;
 
     Ok(())
 }
 
 fn parse_and_export_project_to_database(
-    project_root: &str,
+    config: AnalyzerConfig,
     output_database_path: &str,
-    translation_file_path: &str,
 ) -> anyhow::Result<()> {
-    let project_root = PathBuf::from(project_root).to_canonical_string()?;
+    let mut analyzer = Analyzer::new(config)?;
+    let project_root = analyzer.get_project_root().to_owned();
     // project name "default_project" can be different in feature "cross-project tracing"
     let project =
-        Project::new("default_project", &project_root, output_database_path).context(format!(
+        Project::open("default_project", &project_root, output_database_path).context(format!(
             "ready to a emit the project to database, project: {}, database: {}",
             project_root, output_database_path
         ))?;
 
-    let translation_file = File::open(translation_file_path).context(format!(
-        "open translation file, path: {}",
-        translation_file_path
-    ))?;
-    let translation_json_reader = BufReader::new(translation_file);
-    let translation_json: HashMap<String, String> =
-        serde_json::from_reader(translation_json_reader).context(format!(
-            "deserialize translation file, path: {}",
-            translation_file_path
+    let progress_style = ProgressStyle::with_template(
+        "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
+    )?
+    .progress_chars("##-");
+    let bar = ProgressBar::new(0);
+    bar.set_style(progress_style.clone());
+    let mut modules = vec![];
+    analyzer.for_each_module(
+        |event| match event {
+            AnalyzerEvent::Started { module_count } => bar.set_length(module_count as u64),
+            AnalyzerEvent::ModuleAnalyzed { .. } => bar.inc(1),
+            AnalyzerEvent::CycleBroken(cycle) => bar.println(format!("break the cycle {}", cycle)),
+            // the database expands the wildcard exports by itself
+            AnalyzerEvent::AmbiguousExport(_) | AnalyzerEvent::Finished { .. } => (),
+        },
+        |parsed_module, content_hash| {
+            modules.push((parsed_module, content_hash.to_owned()));
+            Ok(())
+        },
+    )?;
+    bar.finish_with_message("all modules parsed 🌲");
+
+    // Only the changed modules are emitted again if the project is in the database
+    // already, in one transaction so the database is never half updated.
+    let transaction = project.db.conn.unchecked_transaction()?;
+    let translation_json = analyzer.read_translation_json()?;
+    let added_keys = project
+        .update_translation(&translation_json)
+        .context("update translation of project")?;
+    let dirty = project.get_dirty_modules(&modules, &added_keys)?;
+    let used_by_edges = project.clear_modules(&dirty)?;
+
+    let bar = ProgressBar::new(modules.len() as u64);
+    bar.set_style(progress_style);
+    for (
+        ParsedModule {
+            symbol_dependency,
+            i18n_usage,
+            route_usage,
+        },
+        content_hash,
+    ) in modules.iter()
+    {
+        bar.inc(1);
+        if !dirty.contains(&project.remove_prefix(&symbol_dependency.canonical_path)) {
+            continue;
+        }
+        let module = project.add_module(symbol_dependency).context(format!(
+            "add symbol dependency of module {} to project",
+            symbol_dependency.canonical_path
         ))?;
-    project
-        .add_translation(&translation_json)
-        .context("add translation to project")?;
-
-    let mut scheduler = ParserCandidateScheduler::new(&project_root);
-    let bar = ProgressBar::new(scheduler.get_total_remaining_candidate_count() as u64);
-    bar.set_style(
-        ProgressStyle::with_template(
-            "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
-        )?
-        .progress_chars("##-"),
-    );
-    loop {
-        match scheduler.get_one_candidate() {
-            Some(c) => {
-                let module_src = c
-                    .to_str()
-                    .context(format!("get module_src, path_buf: {:?}", c))?;
-                let module_ast = Input::Path(module_src)
-                    .get_module_ast()
-                    .context(format!("get module ast, module_src: {}", module_src))?;
 
-                let symbol_dependency = collect_symbol_dependency(&module_ast, module_src)
-                    .context(format!(
-                        "collect symbol dependency for module: {}",
-                        &module_src
-                    ))?;
-                let module = project
-                    .add_module(&symbol_dependency)
-                    .context(format!(
-                        "add symbol dependency of module {} to project",
-                        symbol_dependency.canonical_path
-                    ))
-                    .context(format!("add module {} to project", module_src))?;
-
-                let i18n_usage = collect_translation(&module_ast)
-                    .context(format!("collect i18n usage for module: {}", &module_src))?;
-                project
-                    .add_i18n_usage(&module, &i18n_usage)
-                    .context(format!(
-                        "add i18n usage of module {} to project",
-                        module_src
-                    ))?;
+        project
+            .add_i18n_usage(&module, i18n_usage)
+            .context(format!(
+                "add i18n usage of module {} to project",
+                symbol_dependency.canonical_path
+            ))?;
 
-                let route_usage = collect_route_dependency(&module_ast, &symbol_dependency)
-                    .context(format!("collect route usage for module: {}", &module_src))?;
-                project
-                    .add_route_usage(&module, &route_usage)
-                    .context(format!(
-                        "add route usage of module {} to project",
-                        module_src
-                    ))?;
+        project
+            .add_route_usage(&module, route_usage)
+            .context(format!(
+                "add route usage of module {} to project",
+                symbol_dependency.canonical_path
+            ))?;
+        module.set_content_hash(&project.db.conn, content_hash)?;
+    }
+    project.restore_used_by_edges(used_by_edges)?;
+    project.project.delete_unreferenced(&project.db.conn)?;
+    transaction.commit()?;
+    bar.finish_with_message(format!("{} modules emitted 🌲", dirty.len()));
+    Ok(())
+}
+
+fn read_diff(diff_path: &str) -> anyhow::Result<String> {
+    match diff_path {
+        "-" => {
+            let mut diff = String::new();
+            io::stdin()
+                .read_to_string(&mut diff)
+                .context("read diff from stdin")?;
+            Ok(diff)
+        }
+        _ => fs::read_to_string(diff_path).context(format!("read diff from {}", diff_path)),
+    }
+}
+
+fn report_impact(
+    config: AnalyzerConfig,
+    repo_root: &str,
+    diff: &str,
+    output_report_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let file_diffs = parse_unified_diff(diff).context("parse the diff")?;
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event)?;
+    let report = analyze_impact(&analysis, repo_root, &file_diffs, None)?;
+    for skipped_file in report.skipped_files.iter() {
+        eprintln!("skip {}, it's not a module of the project", skipped_file);
+    }
+
+    write_report(&report, output_report_path)
+}
+
+// Both sides are analyzed from the revisions checked out to temporary worktrees, or the
+// new side from the working tree if `to` is not given. The symbols deleted by the diff
+// are traced at the old side.
+fn report_impact_of_revisions(
+    config: AnalyzerConfig,
+    repo_root: &str,
+    from: &str,
+    to: Option<&str>,
+    output_report_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let diff = git::diff_revisions(repo_root, from, to).context("diff the repository")?;
+    let file_diffs = parse_unified_diff(&diff).context("parse the diff")?;
+
+    let old_worktree = git::Worktree::add(repo_root, from)?;
+    let old_repo_root = path_to_string(old_worktree.path())?;
+    let mut analyzer = Analyzer::new(config_in_worktree(&config, repo_root, &old_repo_root)?)?;
+    let old_analysis = analyzer
+        .run(print_event)
+        .context(format!("analyze the project at {}", from))?;
+
+    let new_worktree = to.map(|to| git::Worktree::add(repo_root, to)).transpose()?;
+    let (config, new_repo_root) = match new_worktree.as_ref() {
+        Some(new_worktree) => {
+            let new_repo_root = path_to_string(new_worktree.path())?;
+            (
+                config_in_worktree(&config, repo_root, &new_repo_root)?,
+                new_repo_root,
+            )
+        }
+        None => (config, repo_root.to_owned()),
+    };
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event).context(format!(
+        "analyze the project at {}",
+        to.unwrap_or("the working tree")
+    ))?;
+
+    let old_side = OldSide {
+        analysis: &old_analysis,
+        repo_root: &old_repo_root,
+    };
+    let report = analyze_impact(&analysis, &new_repo_root, &file_diffs, Some(old_side))?;
+    for skipped_file in report.skipped_files.iter() {
+        eprintln!("skip {}, it's not a module of the project", skipped_file);
+    }
+
+    write_report(&report, output_report_path)
+}
 
-                scheduler.mark_candidate_as_parsed(c);
-                bar.inc(1);
+fn path_to_string(path: &Path) -> anyhow::Result<String> {
+    Ok(path
+        .to_str()
+        .context(format!("get path string, path_buf: {:?}", path))?
+        .to_owned())
+}
+
+// The same project in a worktree of the repository. The translation file is read from
+// the worktree as well if it's in the repository. The parse cache belongs to the working
+// tree, so it's not used.
+fn config_in_worktree(
+    config: &AnalyzerConfig,
+    repo_root: &str,
+    worktree_root: &str,
+) -> anyhow::Result<AnalyzerConfig> {
+    let repo_root = PathBuf::from(repo_root)
+        .canonicalize()
+        .context(format!("canonicalize repository root {}", repo_root))?;
+    let in_worktree = |path: &str| -> Option<String> {
+        let path = PathBuf::from(path).canonicalize().ok()?;
+        let relative_path = path.strip_prefix(&repo_root).ok()?;
+        Some(
+            Path::new(worktree_root)
+                .join(relative_path)
+                .to_string_lossy()
+                .to_string(),
+        )
+    };
+    let project_root = in_worktree(&config.project_root).context(format!(
+        "project {} is not in the repository {}",
+        config.project_root,
+        repo_root.display()
+    ))?;
+    let translation_file_path = config
+        .translation_file_path
+        .as_ref()
+        .map(|path| in_worktree(path).unwrap_or(path.to_owned()));
+    Ok(AnalyzerConfig {
+        project_root,
+        translation_file_path,
+        cache_dir: None,
+        ..config.clone()
+    })
+}
+
+fn report_dead_code(
+    config: AnalyzerConfig,
+    entry_points: &[String],
+    output_report_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event)?;
+    let report = find_dead_code(&analysis.used_by_graph, entry_points)?;
+    for entry_point in report.unmatched_entry_points.iter() {
+        eprintln!("entry point {} matches no module", entry_point);
+    }
+    write_report(&report, output_report_path)
+}
+
+fn export_graph(
+    config: AnalyzerConfig,
+    trace: &TraceArgs,
+    format: visualize::Format,
+    granularity: GraphGranularity,
+    depth: usize,
+    output_graph_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event)?;
+    let granularity = match granularity {
+        GraphGranularity::Symbol => Granularity::Symbol,
+        GraphGranularity::Module => Granularity::Module,
+        GraphGranularity::Directory | GraphGranularity::Package => {
+            if trace.trace.is_some() {
+                bail!("`--trace` only works with the symbol and module granularity");
             }
-            None => break,
+            let level = match granularity {
+                GraphGranularity::Directory => ProjectionLevel::Directory,
+                _ => ProjectionLevel::Package,
+            };
+            let graph = Graph::from_projection(&project(&analysis, level, depth));
+            return write_text(&graph.render(format), output_graph_path);
         }
+    };
+    let graph = match trace.to_module_symbol() {
+        None => Graph::from_used_by_graph(&analysis.used_by_graph, granularity),
+        Some(module_symbol) => {
+            let (trace_graph, direction) = match trace.depend_on {
+                true => {
+                    let mut tracker = DependOnTracker::new(&analysis.depend_on_graph, true);
+                    tracker.set_limits(trace.to_limits())?;
+                    (
+                        tracker.trace_graph(module_symbol)?,
+                        TraceDirection::DependOn,
+                    )
+                }
+                false => {
+                    let mut tracker = DependencyTracker::new(&analysis.used_by_graph, true);
+                    tracker.set_limits(trace.to_limits())?;
+                    (tracker.trace_graph(module_symbol)?, TraceDirection::UsedBy)
+                }
+            };
+            if trace_graph.truncation.is_truncated() {
+                eprintln!("the trace is {}", trace_graph.truncation);
+            }
+            Graph::from_trace_graph(&trace_graph, direction, granularity)
+        }
+    };
+    write_text(&graph.render(format), output_graph_path)
+}
+
+fn write_text(text: &str, output_path: Option<&str>) -> anyhow::Result<()> {
+    match output_path {
+        Some(output_path) => {
+            let mut file = File::create(output_path)?;
+            file.write_all(text.as_bytes())?;
+        }
+        None => print!("{}", text),
+    }
+    Ok(())
+}
+
+fn project(analysis: &Analysis, level: ProjectionLevel, depth: usize) -> Projection {
+    match level {
+        ProjectionLevel::Module => Projection::modules(&analysis.used_by_graph),
+        ProjectionLevel::Directory => Projection::directories(&analysis.used_by_graph, depth),
+        ProjectionLevel::Package => {
+            Projection::packages(&analysis.used_by_graph, &analysis.project_root)
+        }
+    }
+}
+
+fn query_deps(
+    config: AnalyzerConfig,
+    level: ProjectionLevel,
+    depth: usize,
+    query: &DepsQuery,
+    output_result_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event)?;
+    let projection = project(&analysis, level, depth);
+    let validate_node = |node: &str| -> anyhow::Result<()> {
+        if !projection.nodes.contains(node) {
+            bail!("{} is not a node of the {} graph", node, level.as_str());
+        }
+        Ok(())
+    };
+
+    let result = if let Some(node) = query.dependencies.as_deref() {
+        validate_node(node)?;
+        serde_json::to_value(
+            projection
+                .get_dependencies(node)
+                .into_iter()
+                .collect::<BTreeMap<&str, Weight>>(),
+        )?
+    } else if let Some(node) = query.dependents.as_deref() {
+        validate_node(node)?;
+        serde_json::to_value(
+            projection
+                .get_dependents(node)
+                .into_iter()
+                .collect::<BTreeMap<&str, Weight>>(),
+        )?
+    } else if let Some(node) = query.transitive_dependencies.as_deref() {
+        validate_node(node)?;
+        serde_json::to_value(projection.get_transitive_dependencies(node))?
+    } else if let Some(node) = query.transitive_dependents.as_deref() {
+        validate_node(node)?;
+        serde_json::to_value(projection.get_transitive_dependents(node))?
+    } else if let Some(path) = query.path.as_deref() {
+        validate_node(&path[0])?;
+        validate_node(&path[1])?;
+        serde_json::to_value(projection.find_path(&path[0], &path[1]))?
+    } else if query.cycles {
+        serde_json::to_value(projection.find_cycles())?
+    } else {
+        serde_json::to_value(projection.get_orphans())?
+    };
+    write_report(&result, output_result_path)
+}
+
+fn report_hotspots(
+    config: AnalyzerConfig,
+    entry_points: &[String],
+    rank_by: RankBy,
+    limit: usize,
+    exports_only: bool,
+    bridge_samples: Option<usize>,
+    output_report_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event)?;
+    let mut report = compute_metrics(&analysis, entry_points, bridge_samples)?;
+    for entry_point in report.unmatched_entry_points.iter() {
+        eprintln!("entry point {} matches no module", entry_point);
+    }
+    report.rank(rank_by);
+    if exports_only {
+        report.retain_exports();
+    }
+    report.truncate(limit);
+    write_report(&report, output_report_path)
+}
+
+// Return whether any rule is violated.
+fn check_boundaries(
+    config: AnalyzerConfig,
+    rules_path: &str,
+    format: ReportFormat,
+    output_report_path: Option<&str>,
+) -> anyhow::Result<bool> {
+    let rules = Rules::from_file(rules_path)?;
+    let mut analyzer = Analyzer::new(config)?;
+    let analysis = analyzer.run(print_event)?;
+    let violations = check_rules(&analysis.depend_on_graph, &rules)?;
+    match format {
+        ReportFormat::Text => {
+            let text: String = violations
+                .iter()
+                .map(|violation| format!("{}\n", violation))
+                .collect();
+            write_text(&text, output_report_path)?;
+            eprintln!("{} violations", violations.len());
+        }
+        ReportFormat::Json => write_report(&violations, output_report_path)?,
+    }
+    Ok(!violations.is_empty())
+}
+
+// The portables and databases are told apart by the header of the sqlite files.
+fn read_snapshot(path: &str, project_name: &str) -> anyhow::Result<Snapshot> {
+    let bytes = fs::read(path).context(format!("read snapshot from {}", path))?;
+    match bytes.starts_with(b"SQLite format 3\0") {
+        true => {
+            let db = SqliteDb::open(path)?;
+            Snapshot::from_database(&db, project_name)
+        }
+        false => Ok(Snapshot::from_portable(&Portable::import_bytes(&bytes)?)),
+    }
+}
+
+fn compare_snapshots(
+    old_path: &str,
+    new_path: &str,
+    project_name: &str,
+    format: ReportFormat,
+    output_report_path: Option<&str>,
+) -> anyhow::Result<()> {
+    let old = read_snapshot(old_path, project_name).context("read the old snapshot")?;
+    let new = read_snapshot(new_path, project_name).context("read the new snapshot")?;
+    let diff = old.diff(&new);
+    match format {
+        ReportFormat::Text => write_text(&diff.to_string(), output_report_path),
+        ReportFormat::Json => write_report(&diff, output_report_path),
+    }
+}
+
+fn write_report<T: Serialize>(report: &T, output_report_path: Option<&str>) -> anyhow::Result<()> {
+    let serialized = serde_json::to_string_pretty(report)?;
+    match output_report_path {
+        Some(output_report_path) => {
+            let mut file = File::create(output_report_path)?;
+            file.write_all(serialized.as_bytes())?;
+        }
+        None => println!("{}", serialized),
     }
-    bar.finish_with_message("all modules parsed 🌲");
     Ok(())
 }
 
@@ -223,10 +1114,21 @@ struct Project {
 }
 
 impl Project {
-    pub fn new(project_name: &str, project_root: &str, db_path: &str) -> anyhow::Result<Self> {
+    // The project is updated if it's already in the database, so the same database can
+    // be emitted again and again. A project of the same name but another root is an
+    // error, just like creating it twice.
+    pub fn open(project_name: &str, project_root: &str, db_path: &str) -> anyhow::Result<Self> {
         let db = SqliteDb::open(db_path)?;
         db.create_tables()?;
-        let project = models::Project::create(&db.conn, project_root, project_name)?;
+        let project = match models::Project::retrieve_by_name(&db.conn, project_name) {
+            Ok(project) if project.path == project_root => project,
+            Ok(project) => bail!(
+                "project {} of another root {} is already in the database",
+                project_name,
+                project.path
+            ),
+            Err(_) => models::Project::create(&db.conn, project_root, project_name)?,
+        };
         Ok(Self {
             db,
             project_root: project_root.to_owned(),
@@ -235,11 +1137,156 @@ impl Project {
         })
     }
 
-    fn remove_prefix(&self, canonical_path: &str) -> String {
-        match canonical_path.starts_with(&self.project_root) {
-            true => canonical_path[self.project_root.len()..].to_string(),
-            false => canonical_path.to_string(),
+    // Make the translations the same as the translation.json, returns the added keys.
+    // The usages of the removed keys are deleted with them.
+    fn update_translation(
+        &self,
+        translation_json: &HashMap<String, String>,
+    ) -> anyhow::Result<HashSet<String>> {
+        let mut added_keys: HashSet<String> = translation_json.keys().cloned().collect();
+        for translation in self.project.get_translations(&self.db.conn)? {
+            match translation_json.get(&translation.key) {
+                Some(value) => {
+                    if value != &translation.value {
+                        translation.update_value(&self.db.conn, value)?;
+                    }
+                    added_keys.remove(&translation.key);
+                }
+                None => translation.delete(&self.db.conn)?,
+            }
+        }
+        for key in added_keys.iter() {
+            self.project
+                .add_translation(&self.db.conn, key, &translation_json[key])?;
+        }
+        Ok(added_keys)
+    }
+
+    // The modules imported by wildcard exports and namespace imports, the exports
+    // of them are expanded when the module is added.
+    fn get_expanded_imports(&self, symbol_dependency: &SymbolDependency) -> Vec<String> {
+        let mut import_srcs: Vec<&String> = symbol_dependency
+            .re_export_star_from
+            .iter()
+            .flatten()
+            .collect();
+        for variable in symbol_dependency.local_variable_table.values() {
+            if let Some(FromOtherModule {
+                from,
+                from_type: FromType::Namespace,
+            }) = variable.import_from.as_ref()
+            {
+                import_srcs.push(from);
+            }
+        }
+        for module_export in symbol_dependency.named_export_table.values() {
+            if let ModuleExport::ReExportFrom(FromOtherModule {
+                from,
+                from_type: FromType::Namespace,
+            }) = module_export
+            {
+                import_srcs.push(from);
+            }
+        }
+        import_srcs
+            .into_iter()
+            .filter_map(|import_src| {
+                self.resolve_path(&symbol_dependency.canonical_path, import_src)
+                    .ok()
+            })
+            .collect()
+    }
+
+    // The modules to emit again, or to remove if they're not parsed any more: the
+    // changed ones by the content hash, the ones using the added translation keys, and
+    // the ones expanding the exports of them.
+    fn get_dirty_modules(
+        &self,
+        modules: &[(ParsedModule, String)],
+        added_keys: &HashSet<String>,
+    ) -> anyhow::Result<HashSet<String>> {
+        let mut module_hashes = self.project.get_module_hashes(&self.db.conn)?;
+        let mut dirty = HashSet::new();
+        let mut clean = vec![];
+        for (parsed_module, content_hash) in modules.iter() {
+            let module_path = self.remove_prefix(&parsed_module.symbol_dependency.canonical_path);
+            let is_changed = module_hashes.remove(&module_path).as_ref() != Some(content_hash)
+                || parsed_module
+                    .i18n_usage
+                    .values()
+                    .flatten()
+                    .any(|key| added_keys.contains(key));
+            match is_changed {
+                true => {
+                    dirty.insert(module_path);
+                }
+                false => clean.push((
+                    module_path,
+                    self.get_expanded_imports(&parsed_module.symbol_dependency),
+                )),
+            }
+        }
+        // the ones left are removed
+        dirty.extend(module_hashes.into_keys());
+
+        // The modules are in the scheduled order, a module comes after the ones it expands,
+        // but not for the modules in the broken cycles.
+        loop {
+            let count = dirty.len();
+            clean.retain(|(module_path, expanded_imports)| {
+                match expanded_imports.iter().any(|from| dirty.contains(from)) {
+                    true => {
+                        dirty.insert(module_path.to_owned());
+                        false
+                    }
+                    false => true,
+                }
+            });
+            if dirty.len() == count {
+                break;
+            }
+        }
+        Ok(dirty)
+    }
+
+    // Clear the dirty modules and delete the symbols of the ones to emit again, the
+    // edges to them from the clean modules are returned to restore after that.
+    fn clear_modules(&self, dirty: &HashSet<String>) -> anyhow::Result<UsedByEdges> {
+        let mut modules = vec![];
+        for module_path in dirty.iter() {
+            if let Ok(module) = self.project.get_module(&self.db.conn, module_path) {
+                module.clear(&self.db.conn)?;
+                modules.push(module);
+            }
+        }
+        // the edges between the dirty modules are gone after clearing them
+        let mut used_by_edges = vec![];
+        for module in modules {
+            let edges = module.get_used_by_edges(&self.db.conn)?;
+            module.delete_symbols(&self.db.conn)?;
+            used_by_edges.push((module, edges));
         }
+        Ok(used_by_edges)
+    }
+
+    // The symbols are created again if the module doesn't have them any more, just like
+    // importing from a module not parsed.
+    fn restore_used_by_edges(&self, used_by_edges: UsedByEdges) -> anyhow::Result<()> {
+        for (module, edges) in used_by_edges {
+            for (symbol, depend_on) in edges {
+                let depend_on = module.get_or_create_symbol(
+                    &self.db.conn,
+                    depend_on.variant,
+                    &depend_on.name,
+                )?;
+                models::SymbolDependency::create(&self.db.conn, &symbol, &depend_on)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn remove_prefix(&self, canonical_path: &str) -> String {
+        to_relative_path(&self.project_root, canonical_path)
     }
 
     fn resolve_path(&self, current_path: &str, import_src: &str) -> anyhow::Result<String> {
@@ -493,7 +1540,10 @@ impl Project {
                 if let Ok(from) = self.resolve_path(&symbol_dependency.canonical_path, &from) {
                     // When A module do wildcard export from B module, B module is guranteed to be
                     // parsed before A module. So we can query all named exports from B module.
-                    let import_from_module = self.project.get_module(&self.db.conn, &from)?;
+                    // The imported module may not exist if it's in a cycle which is broken by the
+                    // scheduler, then the wildcard export is expanded partially.
+                    let import_from_module =
+                        self.project.get_or_create_module(&self.db.conn, &from)?;
                     let named_export_symbols =
                         import_from_module.get_named_export_symbols(&self.db.conn)?;
                     for depend_on_symbol in named_export_symbols.iter() {
@@ -535,16 +1585,6 @@ impl Project {
         Ok(module)
     }
 
-    pub fn add_translation(
-        &self,
-        translation_json: &HashMap<String, String>,
-    ) -> anyhow::Result<()> {
-        for (key, value) in translation_json.iter() {
-            self.project.add_translation(&self.db.conn, key, value)?;
-        }
-        Ok(())
-    }
-
     pub fn add_i18n_usage(
         &self,
         module: &models::Module,
@@ -607,3 +1647,204 @@ impl Project {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::BTreeSet;
+
+    // Everything of the project by the module paths and symbol names, the ids differ
+    // between the databases.
+    fn dump(db_path: &str) -> BTreeSet<String> {
+        let db = SqliteDb::open(db_path).unwrap();
+        let conn = &db.conn;
+        let project = models::Project::retrieve_by_name(conn, "default_project").unwrap();
+        let module_paths: HashMap<usize, String> = project
+            .get_modules(conn)
+            .unwrap()
+            .into_iter()
+            .map(|module| (module.id, module.path))
+            .collect();
+        let symbols: HashMap<usize, String> = project
+            .get_symbols(conn)
+            .unwrap()
+            .into_iter()
+            .map(|symbol| {
+                let name = format!(
+                    "{} {:?} {}",
+                    module_paths[&symbol.module_id], symbol.variant, symbol.name
+                );
+                (symbol.id, name)
+            })
+            .collect();
+        let translations: HashMap<usize, String> = project
+            .get_translations(conn)
+            .unwrap()
+            .into_iter()
+            .map(|translation| (translation.id, translation.key))
+            .collect();
+
+        let mut dump: BTreeSet<String> = symbols.values().cloned().collect();
+        for edge in project.get_symbol_dependencies(conn).unwrap() {
+            dump.insert(format!(
+                "{} -> {}",
+                symbols[&edge.symbol_id], symbols[&edge.depend_on_symbol_id]
+            ));
+        }
+        for translation in project.get_translations(conn).unwrap() {
+            dump.insert(format!("{} = {}", translation.key, translation.value));
+        }
+        for usage in project.get_translation_usages(conn).unwrap() {
+            dump.insert(format!(
+                "{} uses {}",
+                symbols[&usage.symbol_id], translations[&usage.translation_id]
+            ));
+        }
+        dump
+    }
+
+    #[test]
+    fn emit_changed_modules_to_database() {
+        let project_root = std::env::temp_dir().join("cli_emit_changed_modules_to_database");
+        let _ = fs::remove_dir_all(&project_root);
+        fs::create_dir_all(&project_root).unwrap();
+        let write =
+            |name: &str, content: &str| fs::write(project_root.join(name), content).unwrap();
+        write("a.js", "export const A = 'A';");
+        write("b.js", "export * from './a';");
+        write(
+            "c.jsx",
+            "import { A } from './b';
+            const LABELS = translate({ c: 'i18n.c', cc: 'i18n.cc' });
+            export const C = () => <div>{LABELS.c}</div>;
+            export const CC = () => <div>{LABELS.cc}</div>;
+            const D = () => A;",
+        );
+        write(
+            "d.js",
+            "import * as a from './a'; export const D = () => a;",
+        );
+        write("f.js", "export const F = 'F';");
+        write("translation.json", r#"{ "i18n.c": "c" }"#);
+        let root = project_root.to_str().unwrap();
+        let config = AnalyzerConfig {
+            translation_file_path: Some(
+                project_root
+                    .join("translation.json")
+                    .to_str()
+                    .unwrap()
+                    .to_owned(),
+            ),
+            ..AnalyzerConfig::new(root)
+        };
+        let db_path = project_root.join("db.sqlite3");
+        let db_path = db_path.to_str().unwrap();
+        parse_and_export_project_to_database(config.clone(), db_path).unwrap();
+        assert!(dump(db_path).contains("d.js LocalVariable a -> a.js NamedExport A"));
+        let symbol_id_of_f = |db_path: &str| {
+            let project = Project::open("default_project", root, db_path).unwrap();
+            let module = project
+                .project
+                .get_module(&project.db.conn, "f.js")
+                .unwrap();
+            module
+                .get_symbol(&project.db.conn, models::SymbolVariant::NamedExport, "F")
+                .unwrap()
+                .id
+        };
+        let f = symbol_id_of_f(db_path);
+
+        // a.js is changed, b.js and d.js expand its exports, c.jsx uses the added key
+        write("a.js", "export const A = 'A'; export const AA = 'AA';");
+        fs::remove_file(project_root.join("d.js")).unwrap();
+        write("e.js", "import { AA } from './b'; export const E = AA;");
+        write("translation.json", r#"{ "i18n.c": "c!", "i18n.cc": "cc" }"#);
+        parse_and_export_project_to_database(config.clone(), db_path).unwrap();
+        let updated = dump(db_path);
+        assert!(updated.contains("b.js NamedExport AA -> a.js NamedExport AA"));
+        assert!(updated.contains("c.jsx LocalVariable CC uses i18n.cc"));
+        assert!(!updated.iter().any(|line| line.starts_with("d.js")));
+        // f.js is not emitted again
+        assert_eq!(symbol_id_of_f(db_path), f);
+
+        // the same as emitting it at once
+        let fresh_db_path = project_root.join("fresh.sqlite3");
+        let fresh_db_path = fresh_db_path.to_str().unwrap();
+        parse_and_export_project_to_database(config, fresh_db_path).unwrap();
+        assert_eq!(updated, dump(fresh_db_path));
+
+        assert!(Project::open("default_project", "/another/root", db_path).is_err());
+    }
+
+    fn git(repo_root: &Path, args: &[&str]) -> String {
+        let output = std::process::Command::new("git")
+            .arg("-C")
+            .arg(repo_root)
+            .args(["-c", "user.name=dt", "-c", "user.email=dt@example.com"])
+            .args(args)
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        String::from_utf8(output.stdout).unwrap()
+    }
+
+    #[test]
+    fn report_impact_between_revisions() {
+        let repo_root = std::env::temp_dir().join("cli_report_impact_between_revisions");
+        let _ = fs::remove_dir_all(&repo_root);
+        fs::create_dir_all(repo_root.join("app")).unwrap();
+        let write = |name: &str, content: &str| {
+            fs::write(repo_root.join("app").join(name), content).unwrap()
+        };
+        git(&repo_root, &["init", "--quiet"]);
+        write(
+            "a.js",
+            "export const A = 'A';\nexport const Gone = 'Gone';\n",
+        );
+        write(
+            "b.js",
+            "import { Gone } from './a';\nexport const B = () => Gone;\n",
+        );
+        git(&repo_root, &["add", "."]);
+        git(&repo_root, &["commit", "--quiet", "-m", "init"]);
+        write("a.js", "export const A = 'A';\n");
+        write("b.js", "export const B = () => 'B';\n");
+        git(&repo_root, &["commit", "--quiet", "-am", "remove Gone"]);
+        // the working tree is not the new side
+        fs::remove_file(repo_root.join("app").join("a.js")).unwrap();
+
+        let repo = repo_root.to_str().unwrap();
+        let config = AnalyzerConfig::new(repo_root.join("app").to_str().unwrap());
+        let output_path = std::env::temp_dir().join("cli_report_impact_between_revisions.json");
+        let output_path = output_path.to_str().unwrap();
+        report_impact_of_revisions(config, repo, "HEAD~1", Some("HEAD"), Some(output_path))
+            .unwrap();
+
+        let report: serde_json::Value =
+            serde_json::from_str(&fs::read_to_string(output_path).unwrap()).unwrap();
+        let changed_symbols: BTreeSet<String> = report["changed_symbols"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|symbol_impact| {
+                format!(
+                    "{} {} deleted: {}",
+                    symbol_impact["module_path"].as_str().unwrap(),
+                    symbol_impact["symbol"],
+                    symbol_impact["deleted"]
+                )
+            })
+            .collect();
+        assert_eq!(
+            changed_symbols,
+            BTreeSet::from([
+                String::from(r#"a.js "Gone" deleted: true"#),
+                String::from(r#"b.js "B" deleted: false"#),
+                // the import is deleted as well
+                String::from(r#"b.js "Gone" deleted: true"#),
+            ])
+        );
+        // the worktrees are removed
+        assert_eq!(git(&repo_root, &["worktree", "list"]).lines().count(), 1);
+    }
+}
@@ -0,0 +1,565 @@
+use crate::print_event;
+use actix_cors::Cors;
+use actix_web::{web, App, HttpServer};
+use anyhow::{bail, Context};
+use api_server_portable::{search, AppState, SharedAppState};
+use dt_core::{
+    analyzer::{Analysis, Analyzer, AnalyzerConfig},
+    cache::ParsedModule,
+    graph::depend_on_graph::DependOnGraph,
+    i18n::I18nToSymbol,
+    parser::types::SymbolDependency,
+    path_resolver::{to_absolute_path, to_relative_path},
+    portable::Portable,
+    route::SymbolToRoutes,
+    scheduler::ModuleMatcher,
+};
+use notify::{Event, EventKind, RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    fs,
+    panic::{self, AssertUnwindSafe},
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver, RecvTimeoutError},
+    thread,
+    time::{Duration, Instant},
+};
+
+// Saving a file or checking out a branch emits a burst of events, wait until it
+// settles down before updating the graph.
+const DEBOUNCE: Duration = Duration::from_millis(200);
+
+// `cache_size` in MiB
+pub fn watch_project(config: AnalyzerConfig, port: u16, cache_size: usize) -> anyhow::Result<()> {
+    let translation_path = match config.translation_file_path.as_ref() {
+        Some(translation_file_path) => Some(
+            PathBuf::from(translation_file_path)
+                .canonicalize()
+                .context(format!(
+                    "canonicalize translation file, path: {}",
+                    translation_file_path
+                ))?,
+        ),
+        None => None,
+    };
+    let (mut updater, portable) = Updater::new(config.clone(), translation_path.clone())?;
+    let project_root = updater.analyzer.get_project_root().to_owned();
+    let app_state = web::Data::new(SharedAppState::new(AppState::new(
+        portable,
+        cache_size << 20,
+    )));
+
+    let (tx, rx) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(tx)?;
+    watcher.watch(Path::new(&project_root), RecursiveMode::Recursive)?;
+    if let Some(translation_path) = translation_path.as_ref() {
+        if !translation_path.starts_with(&project_root) {
+            watcher.watch(translation_path, RecursiveMode::NonRecursive)?;
+        }
+    }
+
+    let module_matcher = ModuleMatcher::new(&project_root, &config.discovery_options)?;
+    let updater_app_state = app_state.clone();
+    thread::spawn(move || {
+        // the watcher stops when it's dropped
+        let _watcher = watcher;
+        // a directory is not a module, but the modules under it are added or removed
+        // with it when it's moved
+        let is_relevant = |path: &Path| {
+            translation_path.as_deref() == Some(path)
+                || module_matcher.is_match(path)
+                || (module_matcher.is_walked(path) && !path.is_file())
+        };
+        while let Some(changed_paths) = wait_for_changes(&rx, is_relevant) {
+            updater.update(&updater_app_state, &changed_paths);
+        }
+    });
+
+    eprintln!("serve the search API at http://127.0.0.1:{}", port);
+    actix_web::rt::System::new().block_on(async move {
+        HttpServer::new(move || {
+            App::new()
+                .wrap(Cors::default().allow_any_method().allow_any_origin())
+                .app_data(app_state.clone())
+                .service(search)
+        })
+        .bind(("127.0.0.1", port))?
+        .run()
+        .await
+    })?;
+    Ok(())
+}
+
+// Block until some relevant paths change and the events settle down. Returns `None`
+// if the watcher is gone.
+fn wait_for_changes(
+    rx: &Receiver<notify::Result<Event>>,
+    is_relevant: impl Fn(&Path) -> bool,
+) -> Option<Vec<PathBuf>> {
+    let mut changed_paths: Vec<PathBuf> = vec![];
+    loop {
+        let event = match changed_paths.is_empty() {
+            true => rx.recv().ok()?,
+            false => match rx.recv_timeout(DEBOUNCE) {
+                Ok(event) => event,
+                Err(RecvTimeoutError::Timeout) => return Some(changed_paths),
+                Err(RecvTimeoutError::Disconnected) => return None,
+            },
+        };
+        let event = match event {
+            Ok(event) => event,
+            Err(e) => {
+                eprintln!("watch error: {}", e);
+                continue;
+            }
+        };
+        if let EventKind::Access(_) = event.kind {
+            continue;
+        }
+        for path in event.paths {
+            if is_relevant(&path) && !changed_paths.contains(&path) {
+                changed_paths.push(path);
+            }
+        }
+    }
+}
+
+// Keeps the depend-on graph of the served portable, so the graphs are patched for the
+// changed modules instead of analyzing the whole project again.
+struct Updater {
+    analyzer: Analyzer,
+    module_matcher: ModuleMatcher,
+    depend_on_graph: DependOnGraph,
+    translation_path: Option<PathBuf>,
+    // the changed paths failed to update, they're updated again with the next changes
+    pending_paths: Vec<PathBuf>,
+    // the graphs are left half patched, analyze the whole project with the next changes
+    needs_rebuild: bool,
+}
+
+fn split_analysis(analysis: Analysis) -> (DependOnGraph, Portable) {
+    let Analysis {
+        project_root,
+        translation_json,
+        i18n_to_symbol,
+        symbol_to_route,
+        depend_on_graph,
+        used_by_graph,
+    } = analysis;
+    let portable = Portable::new(
+        project_root,
+        translation_json,
+        i18n_to_symbol,
+        symbol_to_route,
+        used_by_graph,
+    );
+    (depend_on_graph, portable)
+}
+
+impl Updater {
+    fn new(
+        config: AnalyzerConfig,
+        translation_path: Option<PathBuf>,
+    ) -> anyhow::Result<(Self, Portable)> {
+        let discovery_options = config.discovery_options.clone();
+        let mut analyzer = Analyzer::new(config)?;
+        let module_matcher = ModuleMatcher::new(analyzer.get_project_root(), &discovery_options)?;
+        let (depend_on_graph, portable) = split_analysis(analyzer.run(print_event)?);
+        let updater = Self {
+            analyzer,
+            module_matcher,
+            depend_on_graph,
+            translation_path,
+            pending_paths: vec![],
+            needs_rebuild: false,
+        };
+        Ok((updater, portable))
+    }
+
+    // Only the changed modules are parsed and patched in the graphs, the cached graphs
+    // having their symbols are dropped. The old graph keeps being served if a module can't
+    // be parsed, e.g. it's saved in the middle of editing.
+    fn update(&mut self, app_state: &SharedAppState, changed_paths: &[PathBuf]) {
+        for path in changed_paths.iter() {
+            eprintln!("changed: {}", path.display());
+            if !self.pending_paths.contains(path) {
+                self.pending_paths.push(path.to_owned());
+            }
+        }
+        let start = Instant::now();
+        let updated = panic::catch_unwind(AssertUnwindSafe(|| match self.needs_rebuild {
+            true => self.rebuild(app_state),
+            false => self.patch(app_state),
+        }));
+        match updated {
+            Ok(Ok(())) => {
+                self.pending_paths.clear();
+                eprintln!("updated in {:?}", start.elapsed());
+            }
+            Ok(Err(e)) => eprintln!("failed to update, keep the old graph: {:?}", e),
+            Err(_) => {
+                self.needs_rebuild = true;
+                eprintln!("failed to update, keep the old graph");
+            }
+        }
+    }
+
+    fn rebuild(&mut self, app_state: &SharedAppState) -> anyhow::Result<()> {
+        self.needs_rebuild = true;
+        let (depend_on_graph, portable) = split_analysis(self.analyzer.run(print_event)?);
+        self.depend_on_graph = depend_on_graph;
+        // the state is whole again after it's replaced
+        let mut state = app_state
+            .write()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        state.portable = portable;
+        state.tracker.clear();
+        app_state.clear_poison();
+        self.needs_rebuild = false;
+        Ok(())
+    }
+
+    fn patch(&mut self, app_state: &SharedAppState) -> anyhow::Result<()> {
+        let project_root = self.analyzer.get_project_root().to_owned();
+        let mut translation_json = None;
+        let mut replaced = vec![];
+        let mut removed = vec![];
+        // parse everything before changing the graphs
+        for path in self.pending_paths.iter() {
+            if self.translation_path.as_ref() == Some(path) {
+                translation_json = Some(self.analyzer.read_translation_json()?);
+                continue;
+            }
+            let module_src = path
+                .to_str()
+                .context(format!("get module_src, path_buf: {:?}", path))?;
+            let module_path = to_relative_path(&project_root, module_src);
+            let mut module_srcs = vec![];
+            if path.is_file() {
+                module_srcs.push(path.to_owned());
+            } else if path.is_dir() {
+                // a directory moved into the project, its modules are added
+                find_modules(&self.module_matcher, path, &mut module_srcs)?;
+            } else if self.depend_on_graph.table.contains_key(&module_path) {
+                removed.push(module_path);
+            } else {
+                // a directory deleted or moved away, its modules are removed
+                let prefix = format!("{}/", module_path);
+                removed.extend(
+                    self.depend_on_graph
+                        .table
+                        .keys()
+                        .filter(|module_path| module_path.starts_with(&prefix))
+                        .cloned(),
+                );
+            }
+            for module_src in module_srcs.iter() {
+                let module_src = module_src
+                    .to_str()
+                    .context(format!("get module_src, path_buf: {:?}", module_src))?;
+                replaced.push((
+                    to_relative_path(&project_root, module_src),
+                    self.analyzer.parse_module(module_src)?,
+                ));
+            }
+        }
+        // the modules of a directory are reported with it as well
+        let mut seen = HashSet::new();
+        replaced.retain(|(module_path, _)| seen.insert(module_path.to_owned()));
+        let mut seen = HashSet::new();
+        removed.retain(|module_path| seen.insert(module_path.to_owned()));
+
+        let mut affected = HashSet::new();
+        for module_path in replaced.iter().map(|(module_path, _)| module_path) {
+            self.collect_affected_modules(module_path, &mut affected);
+        }
+        for module_path in removed.iter() {
+            self.collect_affected_modules(module_path, &mut affected);
+        }
+        let relinked = match self.patch_depend_on_graph(&replaced, &removed) {
+            Ok(relinked) => relinked,
+            Err(e) => {
+                eprintln!(
+                    "failed to patch the graph, analyze the project again: {:?}",
+                    e
+                );
+                return self.rebuild(app_state);
+            }
+        };
+
+        let mut state = match app_state.write() {
+            Ok(state) => state,
+            Err(_) => bail!("the app state is poisoned"),
+        };
+        let portable = &mut state.portable;
+        for module_path in removed.iter() {
+            remove_usages(portable, module_path);
+            portable
+                .used_by_graph
+                .remove_module(&self.depend_on_graph, module_path);
+        }
+        for (module_path, parsed_module) in replaced.iter() {
+            remove_usages(portable, module_path);
+            add_usages(portable, module_path, parsed_module);
+            portable
+                .used_by_graph
+                .replace_module(&self.depend_on_graph, module_path);
+        }
+        for module_path in relinked.iter() {
+            portable
+                .used_by_graph
+                .replace_module(&self.depend_on_graph, module_path);
+        }
+        if let Some(translation_json) = translation_json {
+            portable.translation_json = translation_json;
+        }
+
+        for module_path in replaced
+            .iter()
+            .map(|(module_path, _)| module_path)
+            .chain(relinked.iter())
+        {
+            self.collect_affected_modules(module_path, &mut affected);
+        }
+        state.tracker.evict_modules(&affected);
+        Ok(())
+    }
+
+    // Returns the modules replaced again to resolve their imports from the added modules.
+    fn patch_depend_on_graph(
+        &mut self,
+        replaced: &[(String, ParsedModule)],
+        removed: &[String],
+    ) -> anyhow::Result<Vec<String>> {
+        for module_path in removed.iter() {
+            self.depend_on_graph.remove_module(module_path)?;
+        }
+        let added: Vec<&String> = replaced
+            .iter()
+            .map(|(module_path, _)| module_path)
+            .filter(|module_path| !self.depend_on_graph.table.contains_key(*module_path))
+            .collect();
+        self.replace_modules(
+            replaced
+                .iter()
+                .map(|(_, parsed_module)| parsed_module.symbol_dependency.clone())
+                .collect(),
+        )?;
+
+        let mut relinked = HashSet::new();
+        for module_path in added {
+            relinked.extend(self.depend_on_graph.get_unresolved_importers(module_path));
+        }
+        let mut symbol_dependencies = vec![];
+        for module_path in relinked.iter() {
+            let module_src = to_absolute_path(self.analyzer.get_project_root(), module_path);
+            symbol_dependencies.push(self.analyzer.parse_module(&module_src)?.symbol_dependency);
+        }
+        self.replace_modules(symbol_dependencies)?;
+        Ok(relinked.into_iter().collect())
+    }
+
+    // A module doing wildcard exports from another module fails to be replaced before
+    // that module is added, so the failed ones are tried again after the others.
+    fn replace_modules(
+        &mut self,
+        mut symbol_dependencies: Vec<SymbolDependency>,
+    ) -> anyhow::Result<()> {
+        while !symbol_dependencies.is_empty() {
+            let count = symbol_dependencies.len();
+            let mut error = None;
+            symbol_dependencies.retain(|symbol_dependency| {
+                match self
+                    .depend_on_graph
+                    .replace_module(symbol_dependency.clone())
+                {
+                    Ok(()) => false,
+                    Err(e) => {
+                        error = Some(e);
+                        true
+                    }
+                }
+            });
+            if let Some(e) = error.filter(|_| symbol_dependencies.len() == count) {
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
+
+    // The modules whose symbols or used-by edges change with the module: itself, the ones
+    // doing wildcard exports from it, and the ones they import.
+    fn collect_affected_modules(&self, module_path: &str, affected: &mut HashSet<String>) {
+        let mut modules = vec![module_path.to_owned()];
+        modules.extend(self.depend_on_graph.get_wildcard_dependents(module_path));
+        for module_path in modules {
+            affected.extend(self.depend_on_graph.get_imported_modules(&module_path));
+            affected.insert(module_path);
+        }
+    }
+}
+
+// The modules under a directory, the symlinks are not followed.
+fn find_modules(
+    module_matcher: &ModuleMatcher,
+    dir: &Path,
+    module_srcs: &mut Vec<PathBuf>,
+) -> anyhow::Result<()> {
+    for entry in fs::read_dir(dir).context(format!("read dir {:?}", dir))? {
+        let entry = entry?;
+        let path = entry.path();
+        let file_type = entry.file_type()?;
+        if file_type.is_dir() && module_matcher.is_walked(&path) {
+            find_modules(module_matcher, &path, module_srcs)?;
+        } else if file_type.is_file() && module_matcher.is_match(&path) {
+            module_srcs.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn remove_usages(portable: &mut Portable, module_path: &str) {
+    portable.i18n_to_symbol.retain(|_, module_to_symbols| {
+        module_to_symbols.remove(module_path);
+        !module_to_symbols.is_empty()
+    });
+    portable.symbol_to_route.remove(module_path);
+}
+
+fn add_usages(portable: &mut Portable, module_path: &str, parsed_module: &ParsedModule) {
+    let mut i18n_to_symbol = I18nToSymbol::new();
+    i18n_to_symbol.add_i18n_usage(module_path, &parsed_module.i18n_usage);
+    for (i18n_key, module_to_symbols) in i18n_to_symbol.table {
+        portable
+            .i18n_to_symbol
+            .entry(i18n_key)
+            .or_default()
+            .extend(module_to_symbols);
+    }
+    let mut symbol_to_route = SymbolToRoutes::new();
+    symbol_to_route.add_route_usage(module_path, &parsed_module.route_usage);
+    portable.symbol_to_route.extend(symbol_to_route.table);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dt_core::tracker::{shared::DEFAULT_CACHE_CAPACITY, TraceGraph, TraceTarget};
+    use std::{collections::BTreeSet, sync::Arc};
+
+    fn modules_of(graph: &TraceGraph) -> BTreeSet<&str> {
+        graph
+            .nodes
+            .iter()
+            .map(|(module_path, _)| module_path.as_str())
+            .collect()
+    }
+
+    #[test]
+    fn update_changed_modules() {
+        let project_root = std::env::temp_dir().join("cli_watch_update_changed_modules");
+        let _ = fs::remove_dir_all(&project_root);
+        fs::create_dir_all(&project_root).unwrap();
+        let project_root = project_root.canonicalize().unwrap();
+        let path = |name: &str| project_root.join(name);
+        let write = |name: &str, content: &str| fs::write(path(name), content).unwrap();
+        write("a.js", "export const A = 'A';");
+        write("b.js", "import { A } from './a'; export const B = () => A;");
+        write("c.js", "import { D } from './d'; export const C = () => D;");
+        write("x.js", "export const X = 'X';");
+        write("y.js", "import { X } from './x'; export const Y = () => X;");
+
+        let config = AnalyzerConfig::new(project_root.to_str().unwrap());
+        let (mut updater, portable) = Updater::new(config, None).unwrap();
+        let app_state = SharedAppState::new(AppState::new(portable, DEFAULT_CACHE_CAPACITY));
+        let trace = |module_path: &str, name: &str| -> Arc<TraceGraph> {
+            let state = app_state.read().unwrap();
+            let module_symbol = (
+                module_path.to_owned(),
+                TraceTarget::NamedExport(name.to_owned()),
+            );
+            state
+                .tracker
+                .trace_graph(&state.portable, module_symbol)
+                .unwrap()
+        };
+        assert_eq!(
+            modules_of(&trace("a.js", "A")),
+            BTreeSet::from(["a.js", "b.js"])
+        );
+        let graph_of_x = trace("x.js", "X");
+        assert_eq!(modules_of(&graph_of_x), BTreeSet::from(["x.js", "y.js"]));
+
+        // write
+        write("d.js", "export const D = 'D';");
+        write("e.js", "import { A } from './a'; export const E = () => A;");
+        updater.update(&app_state, &[path("d.js"), path("e.js")]);
+        assert_eq!(
+            modules_of(&trace("a.js", "A")),
+            BTreeSet::from(["a.js", "b.js", "e.js"])
+        );
+        assert_eq!(
+            modules_of(&trace("d.js", "D")),
+            BTreeSet::from(["c.js", "d.js"])
+        );
+        // the graphs of the other modules are kept in the cache
+        assert!(Arc::ptr_eq(&trace("x.js", "X"), &graph_of_x));
+
+        // edit
+        write("b.js", "export const B = () => 'B';");
+        updater.update(&app_state, &[path("b.js")]);
+        assert_eq!(
+            modules_of(&trace("a.js", "A")),
+            BTreeSet::from(["a.js", "e.js"])
+        );
+
+        // delete
+        fs::remove_file(path("e.js")).unwrap();
+        updater.update(&app_state, &[path("e.js")]);
+        assert_eq!(modules_of(&trace("a.js", "A")), BTreeSet::from(["a.js"]));
+        assert!(!app_state
+            .read()
+            .unwrap()
+            .portable
+            .used_by_graph
+            .modules
+            .contains_key("e.js"));
+
+        // move a directory in, rename it and delete it
+        let dir = std::env::temp_dir().join("cli_watch_update_changed_modules_dir");
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("f.js"),
+            "import { A } from '../a'; export const F = () => A;",
+        )
+        .unwrap();
+        fs::rename(&dir, path("f")).unwrap();
+        updater.update(&app_state, &[path("f")]);
+        assert_eq!(
+            modules_of(&trace("a.js", "A")),
+            BTreeSet::from(["a.js", "f/f.js"])
+        );
+        fs::rename(path("f"), path("g")).unwrap();
+        updater.update(&app_state, &[path("f"), path("g")]);
+        assert_eq!(
+            modules_of(&trace("a.js", "A")),
+            BTreeSet::from(["a.js", "g/f.js"])
+        );
+        fs::remove_dir_all(path("g")).unwrap();
+        updater.update(&app_state, &[path("g")]);
+        assert_eq!(modules_of(&trace("a.js", "A")), BTreeSet::from(["a.js"]));
+
+        // a module can't be parsed, it's updated with the next changes
+        write("b.js", "import { A } from './a'; export const B = () => A");
+        write("x.js", "export const X = ");
+        updater.update(&app_state, &[path("b.js"), path("x.js")]);
+        assert_eq!(modules_of(&trace("a.js", "A")), BTreeSet::from(["a.js"]));
+        write("x.js", "export const X = 'X';");
+        updater.update(&app_state, &[path("x.js")]);
+        assert_eq!(
+            modules_of(&trace("a.js", "A")),
+            BTreeSet::from(["a.js", "b.js"])
+        );
+    }
+}
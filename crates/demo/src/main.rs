@@ -1,13 +1,10 @@
-use anyhow::Context;
 use clap::Parser;
 use console::style;
 use demo::spreadsheet::write_to_spreadsheet;
 use dialoguer::{theme::ColorfulTheme, BasicHistory, Confirm, Input, Select};
 use dt_core::{
-    graph::{depend_on_graph::DependOnGraph, used_by_graph::UsedByGraph},
-    parser::{collect_symbol_dependency, Input as ModuleInput},
-    path_resolver::{PathResolver, ToCanonicalString},
-    scheduler::ParserCandidateScheduler,
+    analyzer::{Analyzer, AnalyzerConfig, AnalyzerEvent},
+    path_resolver::{to_relative_path, PathResolver, ToCanonicalString},
     tracker::{DependencyTracker, TraceTarget},
 };
 use indicatif::{ProgressBar, ProgressStyle};
@@ -33,46 +30,44 @@ fn main() -> anyhow::Result<()> {
 
     let root = args.src;
 
-    let mut scheduler = ParserCandidateScheduler::new(&root);
-    let mut depend_on_graph = DependOnGraph::new(&root);
-
-    let bar = ProgressBar::new(scheduler.get_total_remaining_candidate_count() as u64);
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new(&root))?;
+    let bar = ProgressBar::new(0);
     bar.set_style(
         ProgressStyle::with_template(
             "[{elapsed_precise}] {bar:40.cyan/blue} {pos:>7}/{len:7} {msg}",
         )?
         .progress_chars("##-"),
     );
-    loop {
-        match scheduler.get_one_candidate() {
-            Some(c) => {
-                let module_src = c.to_str().context(format!("to_str() failed: {:?}", c))?;
-                let module_ast = ModuleInput::Path(module_src).get_module_ast()?;
-                let symbol_dependency = collect_symbol_dependency(&module_ast, module_src)?;
-                depend_on_graph.add_symbol_dependency(symbol_dependency)?;
-                scheduler.mark_candidate_as_parsed(c);
-                bar.inc(1);
-            }
-            None => break,
-        }
-    }
+    let analysis = analyzer.run(|event| match event {
+        AnalyzerEvent::Started { module_count } => bar.set_length(module_count as u64),
+        AnalyzerEvent::ModuleAnalyzed { .. } => bar.inc(1),
+        _ => (),
+    })?;
     bar.finish_with_message("all modules parsed 🌲");
 
-    let used_by_graph = UsedByGraph::from(&depend_on_graph);
+    let used_by_graph = analysis.used_by_graph;
     let mut dependency_tracker = DependencyTracker::new(&used_by_graph, false);
 
-    let path_resolver = PathResolver::new(&PathBuf::from(&root).to_canonical_string()?);
+    let project_root = PathBuf::from(&root).to_canonical_string()?;
+    let path_resolver = PathResolver::new(&project_root);
+    // the module paths in the graph are relative to the project root
+    let resolve_path = |input: &str| -> anyhow::Result<String> {
+        Ok(to_relative_path(
+            &project_root,
+            &path_resolver.resolve_path("", input)?,
+        ))
+    };
     let mut target_path_history = BasicHistory::new().max_entries(8).no_duplicates(true);
     loop {
         let target_path = Input::with_theme(&ColorfulTheme::default())
             .with_prompt("Your module path")
             .history_with(&mut target_path_history)
             .validate_with(|input: &String| -> anyhow::Result<()> {
-                let input = path_resolver.resolve_path("", input)?;
+                let input = resolve_path(input)?;
                 dependency_tracker.validate_module_path(&input)
             })
             .interact_text()?;
-        let resolved_target_path = path_resolver.resolve_path("", &target_path)?;
+        let resolved_target_path = resolve_path(&target_path)?;
 
         let symbol_type_selection = Select::with_theme(&ColorfulTheme::default())
             .with_prompt("Pick the symbol type")
@@ -122,7 +117,14 @@ fn main() -> anyhow::Result<()> {
             target_symbol.to_string(),
             rand_string
         );
-        write_to_spreadsheet(&output_path, &track_result);
+        write_to_spreadsheet(&output_path, &track_result.paths);
+        for cycle in track_result.cycles.iter() {
+            let steps: Vec<String> = cycle
+                .iter()
+                .map(|(module_path, symbol)| format!("{} ({})", symbol.to_string(), module_path))
+                .collect();
+            println!("{} {}", style("cycle:").yellow(), steps.join(" -> "));
+        }
 
         println!(
             "Track result has been saved to {}",
@@ -0,0 +1,180 @@
+use anyhow::Context;
+use dt_i18n::collect_translation;
+use dt_parser::{collect_symbol_dependency, types::SymbolDependency, Input};
+use dt_route::{collect_route_dependency, Route};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::{self, File},
+    io::{BufReader, BufWriter},
+    path::Path,
+};
+
+// Bump it when the format of the cache file changes, the old cache will be dropped.
+const CACHE_VERSION: usize = 2;
+const CACHE_FILE_NAME: &str = "parse_cache.json";
+
+// Everything we need from the module AST. The symbol dependency is the one before
+// adding into the `DependOnGraph`, so the wildcard exports and namespace imports are
+// not expanded yet. That's why a module doesn't need to be parsed again when the
+// modules it does wildcard exports or namespace imports from are changed, they are
+// expanded again when the graph is built.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParsedModule {
+    pub symbol_dependency: SymbolDependency,
+    pub i18n_usage: HashMap<String, HashSet<String>>,
+    pub route_usage: Vec<Route>,
+}
+
+impl ParsedModule {
+    pub fn parse(module_src: &str) -> anyhow::Result<Self> {
+        let module_ast = Input::Path(module_src)
+            .get_module_ast()
+            .context(format!("get module ast, module_src: {}", module_src))?;
+        let symbol_dependency = collect_symbol_dependency(&module_ast, module_src).context(
+            format!("collect symbol dependency for module: {}", module_src),
+        )?;
+        let i18n_usage = collect_translation(&module_ast)
+            .context(format!("collect i18n usage for module: {}", module_src))?;
+        let route_usage = collect_route_dependency(&module_ast, &symbol_dependency)
+            .context(format!("collect route usage for module: {}", module_src))?;
+        Ok(Self {
+            symbol_dependency,
+            i18n_usage,
+            route_usage,
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct CacheEntry {
+    content_hash: String,
+    parsed_module: ParsedModule,
+}
+
+#[derive(Deserialize)]
+struct CacheFile {
+    version: usize,
+    project_root: String,
+    entries: HashMap<String, CacheEntry>,
+}
+
+#[derive(Serialize)]
+struct CacheFileRef<'a> {
+    version: usize,
+    project_root: &'a str,
+    entries: &'a HashMap<String, CacheEntry>,
+}
+
+#[derive(Debug)]
+pub struct ParseCache {
+    project_root: String,
+    entries: HashMap<String, CacheEntry>,
+
+    // modules got in this run, the others are deleted from the project
+    visited: HashSet<String>,
+
+    hit_count: usize,
+}
+
+impl ParseCache {
+    pub fn new(project_root: &str) -> Self {
+        Self {
+            project_root: project_root.to_owned(),
+            entries: HashMap::new(),
+            visited: HashSet::new(),
+            hit_count: 0,
+        }
+    }
+
+    /// Start with an empty cache if the cache directory doesn't have a compatible
+    /// cache for the project.
+    pub fn load(cache_dir: &str, project_root: &str) -> anyhow::Result<Self> {
+        let mut cache = Self::new(project_root);
+        let cache_path = Path::new(cache_dir).join(CACHE_FILE_NAME);
+        if !cache_path.is_file() {
+            return Ok(cache);
+        }
+        let file = File::open(&cache_path).context(format!("open cache {:?}", cache_path))?;
+        if let Ok(cache_file) = serde_json::from_reader::<_, CacheFile>(BufReader::new(file)) {
+            if cache_file.version == CACHE_VERSION && cache_file.project_root == project_root {
+                cache.entries = cache_file.entries;
+            }
+        }
+        Ok(cache)
+    }
+
+    /// Start another run with the same cache, e.g. when watching a project, only the
+    /// modules got after it are saved.
+    pub fn start_run(&mut self) {
+        self.visited.clear();
+    }
+
+    /// Only the modules got in this run are saved.
+    pub fn save(&mut self, cache_dir: &str) -> anyhow::Result<()> {
+        self.entries
+            .retain(|module_src, _| self.visited.contains(module_src));
+
+        fs::create_dir_all(cache_dir).context(format!("create cache dir {}", cache_dir))?;
+        let cache_path = Path::new(cache_dir).join(CACHE_FILE_NAME);
+        let tmp_path = cache_path.with_extension("json.tmp");
+        let file = File::create(&tmp_path).context(format!("create cache {:?}", tmp_path))?;
+        serde_json::to_writer(
+            BufWriter::new(file),
+            &CacheFileRef {
+                version: CACHE_VERSION,
+                project_root: &self.project_root,
+                entries: &self.entries,
+            },
+        )?;
+        // a broken cache won't be left if we failed to write it
+        fs::rename(&tmp_path, &cache_path)
+            .context(format!("rename {:?} to {:?}", tmp_path, cache_path))?;
+        Ok(())
+    }
+
+    /// Parse the module only if its content is changed since the cache was written.
+    pub fn get_or_parse(&mut self, module_src: &str) -> anyhow::Result<ParsedModule> {
+        let content = fs::read(module_src).context(format!("read module {}", module_src))?;
+        let content_hash = hash_content(&content);
+        self.visited.insert(module_src.to_owned());
+
+        if let Some(entry) = self.entries.get(module_src) {
+            if entry.content_hash == content_hash {
+                self.hit_count += 1;
+                return Ok(entry.parsed_module.clone());
+            }
+        }
+
+        let parsed_module = ParsedModule::parse(module_src)?;
+        self.entries.insert(
+            module_src.to_owned(),
+            CacheEntry {
+                content_hash,
+                parsed_module: parsed_module.clone(),
+            },
+        );
+        Ok(parsed_module)
+    }
+
+    pub fn get_hit_count(&self) -> usize {
+        self.hit_count
+    }
+
+    /// The hash of the module content got by `get_or_parse()`, it's stable across runs
+    /// and toolchains, so it can be stored elsewhere to find the changed modules, e.g.
+    /// in the database.
+    pub fn get_content_hash(&self, module_src: &str) -> Option<&str> {
+        self.entries
+            .get(module_src)
+            .map(|entry| entry.content_hash.as_str())
+    }
+}
+
+fn hash_content(content: &[u8]) -> String {
+    Sha256::digest(content)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
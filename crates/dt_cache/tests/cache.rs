@@ -0,0 +1,89 @@
+use dt_cache::ParseCache;
+use std::{fs, path::PathBuf};
+
+fn prepare_project(name: &str) -> PathBuf {
+    let project_root = std::env::temp_dir().join(name);
+    let _ = fs::remove_dir_all(&project_root);
+    fs::create_dir_all(&project_root).unwrap();
+    fs::write(project_root.join("a.js"), "export const A = 'A';").unwrap();
+    fs::write(project_root.join("b.js"), "export * from './a';").unwrap();
+    project_root
+}
+
+#[test]
+fn reuse_unchanged_modules() {
+    let project_root = prepare_project("dt_cache_reuse_unchanged_modules");
+    let cache_dir = project_root.join(".cache");
+    let cache_dir = cache_dir.to_str().unwrap();
+    let a = project_root.join("a.js");
+    let b = project_root.join("b.js");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+    let root = project_root.to_str().unwrap();
+
+    let mut cache = ParseCache::load(cache_dir, root).unwrap();
+    cache.get_or_parse(a).unwrap();
+    cache.get_or_parse(b).unwrap();
+    assert_eq!(cache.get_hit_count(), 0);
+    // sha-256, the same on every run
+    assert_eq!(
+        cache.get_content_hash(a),
+        Some("43845207b4a6de4f4596a7ff51ef8f6ca6d8e6faa3ef6bbfe8ff3343c0a30dd1")
+    );
+    cache.save(cache_dir).unwrap();
+
+    fs::write(a, "export const A = 'A'; export const AA = 'AA';").unwrap();
+    let mut cache = ParseCache::load(cache_dir, root).unwrap();
+    let parsed_a = cache.get_or_parse(a).unwrap();
+    let parsed_b = cache.get_or_parse(b).unwrap();
+    assert_eq!(cache.get_hit_count(), 1);
+    assert!(parsed_a
+        .symbol_dependency
+        .named_export_table
+        .contains_key("AA"));
+    // wildcard exports are expanded when building the graph
+    assert_eq!(
+        parsed_b.symbol_dependency.re_export_star_from,
+        Some(vec![String::from("./a")])
+    );
+    cache.save(cache_dir).unwrap();
+
+    // cache of another project is not used
+    let mut cache = ParseCache::load(cache_dir, "/another/project").unwrap();
+    cache.get_or_parse(a).unwrap();
+    assert_eq!(cache.get_hit_count(), 0);
+}
+
+#[test]
+fn drop_deleted_modules() {
+    let project_root = prepare_project("dt_cache_drop_deleted_modules");
+    let cache_dir = project_root.join(".cache");
+    let cache_dir = cache_dir.to_str().unwrap();
+    let a = project_root.join("a.js");
+    let b = project_root.join("b.js");
+    let (a, b) = (a.to_str().unwrap(), b.to_str().unwrap());
+    let root = project_root.to_str().unwrap();
+
+    let mut cache = ParseCache::load(cache_dir, root).unwrap();
+    cache.get_or_parse(a).unwrap();
+    cache.get_or_parse(b).unwrap();
+    cache.save(cache_dir).unwrap();
+
+    // b.js is not parsed in this run
+    let mut cache = ParseCache::load(cache_dir, root).unwrap();
+    cache.get_or_parse(a).unwrap();
+    cache.save(cache_dir).unwrap();
+
+    let mut cache = ParseCache::load(cache_dir, root).unwrap();
+    cache.get_or_parse(b).unwrap();
+    assert_eq!(cache.get_hit_count(), 0);
+    cache.get_or_parse(a).unwrap();
+    cache.save(cache_dir).unwrap();
+
+    // the same cache running again without b.js
+    cache.start_run();
+    cache.get_or_parse(a).unwrap();
+    cache.save(cache_dir).unwrap();
+    let mut cache = ParseCache::load(cache_dir, root).unwrap();
+    cache.get_or_parse(b).unwrap();
+    assert_eq!(cache.get_hit_count(), 0);
+}
@@ -0,0 +1,262 @@
+use crate::{
+    cache::{ParseCache, ParsedModule},
+    graph::{
+        depend_on_graph::{AmbiguousExport, DependOnGraph},
+        used_by_graph::UsedByGraph,
+    },
+    i18n::I18nToSymbol,
+    path_resolver::{to_relative_path, ToCanonicalString},
+    portable::Portable,
+    route::SymbolToRoutes,
+    scheduler::{BlockedCycle, DiscoveryOptions, ParserCandidateScheduler},
+};
+use anyhow::{bail, Context};
+use std::{
+    collections::{HashMap, HashSet},
+    fs::File,
+    io::BufReader,
+    path::PathBuf,
+};
+
+#[derive(Debug, Clone)]
+pub struct AnalyzerConfig {
+    pub project_root: String,
+
+    // translation.json, the translation is empty if it's not given
+    pub translation_file_path: Option<String>,
+
+    pub discovery_options: DiscoveryOptions,
+
+    // keep analyzing when modules do wildcard exports or namespace imports from each
+    // other, the wildcard exports in the cycle are expanded partially
+    pub break_cycles: bool,
+
+    // reuse the parsed modules whose content is not changed since the last run
+    pub cache_dir: Option<String>,
+}
+
+impl AnalyzerConfig {
+    pub fn new(project_root: &str) -> Self {
+        Self {
+            project_root: project_root.to_owned(),
+            translation_file_path: None,
+            discovery_options: DiscoveryOptions::default(),
+            break_cycles: false,
+            cache_dir: None,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AnalyzerEvent<'a> {
+    Started {
+        module_count: usize,
+    },
+    ModuleAnalyzed {
+        module_path: &'a str,
+        from_cache: bool,
+    },
+    CycleBroken(&'a BlockedCycle),
+    // reported after all the modules are analyzed, the name is excluded from the named
+    // exports of the module
+    AmbiguousExport(&'a AmbiguousExport),
+    Finished {
+        module_count: usize,
+        from_cache_count: usize,
+    },
+}
+
+// The module paths of the analysis are relative to `project_root`.
+#[derive(Debug)]
+pub struct Analysis {
+    pub project_root: String,
+    pub translation_json: HashMap<String, String>,
+    pub i18n_to_symbol: HashMap<String, HashMap<String, HashSet<String>>>,
+    pub symbol_to_route: HashMap<String, HashMap<String, Vec<String>>>,
+    pub depend_on_graph: DependOnGraph,
+    pub used_by_graph: UsedByGraph,
+}
+
+impl Analysis {
+    pub fn into_portable(self) -> Portable {
+        Portable::new(
+            self.project_root,
+            self.translation_json,
+            self.i18n_to_symbol,
+            self.symbol_to_route,
+            self.used_by_graph,
+        )
+    }
+}
+
+// Runs the whole pipeline: discover the modules, parse them in the order given by the
+// scheduler (or get them from the parse cache), then build the graphs.
+//
+// ```ignore
+// let mut analyzer = Analyzer::new(AnalyzerConfig::new("path/to/project"))?;
+// let analysis = analyzer.run(|event| println!("{:?}", event))?;
+// ```
+#[derive(Debug)]
+pub struct Analyzer {
+    config: AnalyzerConfig,
+    project_root: String,
+    parse_cache: ParseCache,
+}
+
+impl Analyzer {
+    pub fn new(config: AnalyzerConfig) -> anyhow::Result<Self> {
+        let project_root = PathBuf::from(&config.project_root)
+            .to_canonical_string()
+            .context(format!("canonicalize project root {}", config.project_root))?;
+        let parse_cache = match config.cache_dir.as_ref() {
+            Some(cache_dir) => ParseCache::load(cache_dir, &project_root)
+                .context(format!("load parse cache from {}", cache_dir))?,
+            None => ParseCache::new(&project_root),
+        };
+        Ok(Self {
+            config,
+            project_root,
+            parse_cache,
+        })
+    }
+
+    pub fn get_project_root(&self) -> &str {
+        &self.project_root
+    }
+
+    /// Run it again to get the fresh analysis after files change, only the changed
+    /// modules are parsed again.
+    pub fn run(&mut self, mut on_event: impl FnMut(AnalyzerEvent)) -> anyhow::Result<Analysis> {
+        let translation_json = self.read_translation_json()?;
+        let mut depend_on_graph = DependOnGraph::new(&self.project_root);
+        depend_on_graph.set_allow_partial_wildcard_expansion(self.config.break_cycles);
+        let mut i18n_to_symbol = I18nToSymbol::new();
+        let mut symbol_to_route = SymbolToRoutes::new();
+
+        let project_root = self.project_root.clone();
+        self.for_each_module(&mut on_event, |parsed_module, _| {
+            // the same as the module paths in the graphs
+            let module_path = to_relative_path(
+                &project_root,
+                &parsed_module.symbol_dependency.canonical_path,
+            );
+            i18n_to_symbol.add_i18n_usage(&module_path, &parsed_module.i18n_usage);
+            symbol_to_route.add_route_usage(&module_path, &parsed_module.route_usage);
+            depend_on_graph.add_symbol_dependency(parsed_module.symbol_dependency)
+        })?;
+        for ambiguous_export in depend_on_graph.get_ambiguous_exports().iter() {
+            on_event(AnalyzerEvent::AmbiguousExport(ambiguous_export));
+        }
+
+        let used_by_graph = UsedByGraph::from(&depend_on_graph);
+        Ok(Analysis {
+            project_root: self.project_root.clone(),
+            translation_json,
+            i18n_to_symbol: i18n_to_symbol.table,
+            symbol_to_route: symbol_to_route.table,
+            depend_on_graph,
+            used_by_graph,
+        })
+    }
+
+    /// Visit the parsed modules with the hash of their content in the scheduled order, a
+    /// module is visited after the modules it does wildcard exports or namespace imports
+    /// from. Use it to emit the modules somewhere else, e.g. a database.
+    pub fn for_each_module(
+        &mut self,
+        mut on_event: impl FnMut(AnalyzerEvent),
+        mut visit: impl FnMut(ParsedModule, &str) -> anyhow::Result<()>,
+    ) -> anyhow::Result<()> {
+        let mut scheduler = ParserCandidateScheduler::with_options(
+            &self.project_root,
+            &self.config.discovery_options,
+        )?;
+        let module_count = scheduler.get_total_remaining_candidate_count();
+        let start_hit_count = self.parse_cache.get_hit_count();
+        self.parse_cache.start_run();
+        on_event(AnalyzerEvent::Started { module_count });
+
+        loop {
+            match scheduler.get_one_candidate() {
+                Some(c) => {
+                    let module_src = c
+                        .to_str()
+                        .context(format!("get module_src, path_buf: {:?}", c))?;
+                    let hit_count = self.parse_cache.get_hit_count();
+                    let parsed_module = self
+                        .parse_cache
+                        .get_or_parse(module_src)
+                        .context(format!("parse module {}", module_src))?;
+                    let content_hash = self
+                        .parse_cache
+                        .get_content_hash(module_src)
+                        .context(format!("get content hash of module {}", module_src))?;
+                    visit(parsed_module, content_hash)
+                        .context(format!("analyze module {}", module_src))?;
+                    on_event(AnalyzerEvent::ModuleAnalyzed {
+                        module_path: module_src,
+                        from_cache: self.parse_cache.get_hit_count() > hit_count,
+                    });
+                    scheduler.mark_candidate_as_parsed(c);
+                }
+                None => match self.config.break_cycles {
+                    true => match scheduler.break_cycle() {
+                        Some(cycle) => on_event(AnalyzerEvent::CycleBroken(&cycle)),
+                        None => break,
+                    },
+                    false => break,
+                },
+            }
+        }
+        ensure_no_blocked_cycles(&scheduler)?;
+
+        if let Some(cache_dir) = self.config.cache_dir.as_ref() {
+            self.parse_cache
+                .save(cache_dir)
+                .context(format!("save parse cache to {}", cache_dir))?;
+        }
+        on_event(AnalyzerEvent::Finished {
+            module_count,
+            from_cache_count: self.parse_cache.get_hit_count() - start_hit_count,
+        });
+        Ok(())
+    }
+
+    /// Parse a module of the project, or get it from the parse cache if its content is not
+    /// changed. Use it to update the graphs for some changed modules without running again.
+    pub fn parse_module(&mut self, module_src: &str) -> anyhow::Result<ParsedModule> {
+        self.parse_cache
+            .get_or_parse(module_src)
+            .context(format!("parse module {}", module_src))
+    }
+
+    pub fn read_translation_json(&self) -> anyhow::Result<HashMap<String, String>> {
+        match self.config.translation_file_path.as_ref() {
+            Some(translation_file_path) => {
+                let translation_file = File::open(translation_file_path).context(format!(
+                    "open translation file, path: {}",
+                    translation_file_path
+                ))?;
+                serde_json::from_reader(BufReader::new(translation_file)).context(format!(
+                    "deserialize translation file, path: {}",
+                    translation_file_path
+                ))
+            }
+            None => Ok(HashMap::new()),
+        }
+    }
+}
+
+fn ensure_no_blocked_cycles(scheduler: &ParserCandidateScheduler) -> anyhow::Result<()> {
+    let cycles = scheduler.get_blocked_cycles();
+    if !cycles.is_empty() {
+        let chains: Vec<String> = cycles.iter().map(|cycle| format!("  {}", cycle)).collect();
+        bail!(
+            "{} modules are blocked by circular wildcard exports or namespace imports, \
+            enable `break_cycles` to analyze them anyway:\n{}",
+            scheduler.get_total_remaining_candidate_count(),
+            chains.join("\n")
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,336 @@
+use crate::{
+    graph::depend_on_graph::DependOnGraph,
+    parser::{
+        anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT,
+        types::{FromOtherModule, FromType, ModuleExport},
+    },
+    tracker::{ModuleSymbol, TraceTarget},
+};
+use anyhow::Context;
+use globset::{GlobBuilder, GlobMatcher, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::{HashMap, HashSet},
+    fmt, fs,
+};
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleKind {
+    // the modules matching `from` must not depend on the modules matching `to`
+    #[default]
+    Forbidden,
+    // the modules matching `from` may only depend on the modules matching `to`
+    Allowed,
+}
+
+// A boundary rule of the rules file, the paths are globs relative to the project root and
+// `*` doesn't match `/`.
+//
+// ```json
+// {
+//   "name": "features don't use the internals of other features",
+//   "from": ["features/**"],
+//   "to": ["features/*/internal/**"],
+//   "group": "features/*"
+// }
+// ```
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rule {
+    pub name: String,
+    #[serde(default)]
+    pub kind: RuleKind,
+    pub from: Vec<String>,
+    pub to: Vec<String>,
+    // the dependencies not checked by the rule
+    #[serde(default)]
+    pub except: Vec<String>,
+    // globs of the depended symbol names, "default" for the default exports, all the
+    // symbols are checked if it's empty
+    #[serde(default)]
+    pub symbols: Vec<String>,
+    // the dependencies between the modules of the same group are not checked, e.g.
+    // "features/*" groups the modules by feature, it can't contain `**`
+    pub group: Option<String>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Rules {
+    pub rules: Vec<Rule>,
+}
+
+impl Rules {
+    pub fn from_json(json: &str) -> anyhow::Result<Self> {
+        serde_json::from_str(json).context("parse rules")
+    }
+
+    pub fn from_file(path: &str) -> anyhow::Result<Self> {
+        let json = fs::read_to_string(path).context(format!("read rules from {}", path))?;
+        Self::from_json(&json)
+    }
+}
+
+// A symbol depending on a symbol it's not supposed to, `path` goes from the depending
+// symbol through the re-exports to the depended one.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Violation {
+    pub rule: String,
+    pub path: Vec<ModuleSymbol>,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path: Vec<String> = self
+            .path
+            .iter()
+            .map(|(module_path, symbol)| match symbol {
+                TraceTarget::LocalVar(name) => format!("{} {}", module_path, name),
+                TraceTarget::NamedExport(name) => format!("{} export {}", module_path, name),
+                TraceTarget::DefaultExport => format!("{} export default", module_path),
+            })
+            .collect();
+        write!(f, "[{}] {}", self.rule, path.join(" -> "))
+    }
+}
+
+struct CompiledRule<'rule> {
+    rule: &'rule Rule,
+    from: GlobSet,
+    to: GlobSet,
+    except: GlobSet,
+    symbols: GlobSet,
+    // the group glob and the number of directories it matches
+    group: Option<(GlobMatcher, usize)>,
+}
+
+impl<'rule> CompiledRule<'rule> {
+    fn new(rule: &'rule Rule) -> anyhow::Result<Self> {
+        let group = match rule.group.as_ref() {
+            Some(group) => {
+                if group.contains("**") {
+                    anyhow::bail!("group {} can't contain **", group);
+                }
+                let depth = group.trim_end_matches('/').split('/').count();
+                Some((glob(group)?.compile_matcher(), depth))
+            }
+            None => None,
+        };
+        Ok(Self {
+            rule,
+            from: glob_set(&rule.from)?,
+            to: glob_set(&rule.to)?,
+            except: glob_set(&rule.except)?,
+            symbols: glob_set(&rule.symbols)?,
+            group,
+        })
+    }
+
+    fn get_group<'a>(&self, module_path: &'a str) -> Option<&'a str> {
+        let (matcher, depth) = self.group.as_ref()?;
+        let end = module_path
+            .match_indices('/')
+            .nth(depth - 1)
+            .map(|(i, _)| i)?;
+        let group = &module_path[..end];
+        matcher.is_match(group).then_some(group)
+    }
+
+    // Whether `from_module` depending on the symbol of `to_module` breaks the rule.
+    fn is_violated(&self, from_module: &str, (to_module, symbol): &ModuleSymbol) -> bool {
+        if from_module == to_module || self.except.is_match(to_module) {
+            return false;
+        }
+        if !self.rule.symbols.is_empty() && !self.symbols.is_match(symbol.to_string()) {
+            return false;
+        }
+        if let Some(group) = self.get_group(from_module) {
+            if self.get_group(to_module) == Some(group) {
+                return false;
+            }
+        }
+        match self.rule.kind {
+            RuleKind::Forbidden => self.to.is_match(to_module),
+            RuleKind::Allowed => !self.to.is_match(to_module),
+        }
+    }
+}
+
+fn glob(pattern: &str) -> anyhow::Result<globset::Glob> {
+    GlobBuilder::new(pattern)
+        .literal_separator(true)
+        .build()
+        .context(format!("invalid glob {}", pattern))
+}
+
+fn glob_set(patterns: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for pattern in patterns.iter() {
+        builder.add(glob(pattern)?);
+    }
+    Ok(builder.build()?)
+}
+
+// Check the imports and re-exports of every module against the rules. The re-exports
+// of the imported symbols are followed, so importing the internals of another module
+// through its barrel file is caught as well. The modules outside of the graph, e.g.
+// packages, are not checked.
+pub fn check_rules(
+    depend_on_graph: &DependOnGraph,
+    rules: &Rules,
+) -> anyhow::Result<Vec<Violation>> {
+    let compiled_rules = rules
+        .rules
+        .iter()
+        .map(|rule| CompiledRule::new(rule).context(format!("compile rule {}", rule.name)))
+        .collect::<anyhow::Result<Vec<CompiledRule>>>()?;
+
+    let mut module_paths: Vec<&String> = depend_on_graph.table.keys().collect();
+    module_paths.sort();
+    let mut violations = vec![];
+    // the re-exports walked from each depended symbol, they're the same for every rule
+    let mut walked: HashMap<ModuleSymbol, ReExports> = HashMap::new();
+    for rule in compiled_rules.iter() {
+        for module_path in module_paths.iter() {
+            if !rule.from.is_match(module_path) {
+                continue;
+            }
+            let module = &depend_on_graph.table[*module_path];
+            let mut dependencies: Vec<(ModuleSymbol, &FromOtherModule)> = vec![];
+            for (name, variable) in module.local_variable_table.iter() {
+                if let Some(import_from) = variable.import_from.as_ref() {
+                    let symbol = TraceTarget::LocalVar(name.to_owned());
+                    dependencies.push(((module_path.to_string(), symbol), import_from));
+                }
+            }
+            for (name, module_export) in module.named_export_table.iter() {
+                if let ModuleExport::ReExportFrom(from_other_module) = module_export {
+                    let symbol = TraceTarget::NamedExport(name.to_owned());
+                    dependencies.push(((module_path.to_string(), symbol), from_other_module));
+                }
+            }
+            if let Some(ModuleExport::ReExportFrom(from_other_module)) = &module.default_export {
+                let symbol = TraceTarget::DefaultExport;
+                dependencies.push(((module_path.to_string(), symbol), from_other_module));
+            }
+
+            let mut module_violations = vec![];
+            for (symbol, from_other_module) in dependencies {
+                for depended in get_exports(depend_on_graph, from_other_module) {
+                    let re_exports = walked
+                        .entry(depended.clone())
+                        .or_insert_with(|| walk_re_exports(depend_on_graph, depended));
+                    if let Some(path) = find_violation(rule, &symbol, re_exports) {
+                        module_violations.push(Violation {
+                            rule: rule.rule.name.to_owned(),
+                            path,
+                        });
+                    }
+                }
+            }
+            module_violations.sort_by(|a, b| {
+                let key = |violation: &Violation| -> Vec<(String, String)> {
+                    violation
+                        .path
+                        .iter()
+                        .map(|(module_path, symbol)| (module_path.to_owned(), symbol.to_string()))
+                        .collect()
+                };
+                key(a).cmp(&key(b))
+            });
+            violations.extend(module_violations);
+        }
+    }
+    Ok(violations)
+}
+
+// The exports depended on by an import or a re-export, a namespace depends on all the
+// named exports of the module.
+fn get_exports(
+    depend_on_graph: &DependOnGraph,
+    FromOtherModule { from, from_type }: &FromOtherModule,
+) -> Vec<ModuleSymbol> {
+    match from_type {
+        FromType::Named(name) => vec![(from.to_owned(), TraceTarget::NamedExport(name.to_owned()))],
+        FromType::Default => vec![(from.to_owned(), TraceTarget::DefaultExport)],
+        FromType::Namespace => match depend_on_graph.table.get(from) {
+            Some(module) => {
+                let mut names: Vec<&String> = module
+                    .named_export_table
+                    .keys()
+                    .filter(|name| *name != SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT)
+                    .collect();
+                names.sort();
+                names
+                    .into_iter()
+                    .map(|name| (from.to_owned(), TraceTarget::NamedExport(name.to_owned())))
+                    .collect()
+            }
+            None => vec![],
+        },
+    }
+}
+
+// The symbols reached through the re-exports from a symbol, in the order they're walked
+// depth-first, with the index of the symbol re-exporting each of them.
+type ReExports = Vec<(ModuleSymbol, Option<usize>)>;
+
+// Every symbol is walked once, so the re-exports going back to a walked symbol, or
+// reaching it through another namespace re-export, are not followed again.
+fn walk_re_exports(depend_on_graph: &DependOnGraph, symbol: ModuleSymbol) -> ReExports {
+    let mut re_exports: ReExports = vec![];
+    let mut visited: HashSet<ModuleSymbol> = HashSet::new();
+    let mut stack = vec![(symbol, None)];
+    while let Some((current, re_exported_by)) = stack.pop() {
+        if !visited.insert(current.clone()) {
+            continue;
+        }
+        let module = match depend_on_graph.table.get(&current.0) {
+            Some(module) => module,
+            None => continue,
+        };
+        let module_export = match &current.1 {
+            TraceTarget::NamedExport(name) => module.named_export_table.get(name),
+            TraceTarget::DefaultExport => module.default_export.as_ref(),
+            TraceTarget::LocalVar(_) => None,
+        };
+        let index = re_exports.len();
+        re_exports.push((current, re_exported_by));
+        if let Some(ModuleExport::ReExportFrom(from_other_module)) = module_export {
+            // a namespace re-export, e.g. `export * as name from`, depends on many
+            // symbols, they're walked in order
+            let next = get_exports(depend_on_graph, from_other_module);
+            stack.extend(
+                next.into_iter()
+                    .rev()
+                    .map(|depended| (depended, Some(index))),
+            );
+        }
+    }
+    re_exports
+}
+
+// Find the first re-exported symbol breaking the rule for the depending symbol, the path
+// from the depending symbol to it is returned.
+fn find_violation(
+    rule: &CompiledRule,
+    symbol: &ModuleSymbol,
+    re_exports: &ReExports,
+) -> Option<Vec<ModuleSymbol>> {
+    let mut index = re_exports
+        .iter()
+        .position(|(depended, _)| rule.is_violated(&symbol.0, depended))?;
+    let mut path = vec![];
+    loop {
+        let (depended, re_exported_by) = &re_exports[index];
+        path.push(depended.clone());
+        match re_exported_by {
+            Some(re_exported_by) => index = *re_exported_by,
+            None => break,
+        }
+    }
+    path.push(symbol.clone());
+    path.reverse();
+    Some(path)
+}
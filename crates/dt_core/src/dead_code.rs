@@ -0,0 +1,154 @@
+use crate::{
+    graph::used_by_graph::UsedByGraph,
+    symbol_graph::{match_entry_points, SymbolGraph},
+    tracker::{ModuleSymbol, TraceTarget},
+};
+use serde::Serialize;
+use std::collections::HashSet;
+
+#[derive(Debug, Serialize)]
+pub struct UnusedExport {
+    pub module_path: String,
+    pub symbol: TraceTarget,
+    // the exports of barrel modules passing this one through, e.g.
+    // `export { name } from './this-module'` in index.ts, they are unused as well
+    pub re_exported_by: Vec<ModuleSymbol>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeadCodeReport {
+    pub entry_modules: Vec<String>,
+    // modules whose symbols are not used by the entry modules at all
+    pub unreachable_modules: Vec<String>,
+    // named and default exports of the reachable modules
+    pub unused_exports: Vec<UnusedExport>,
+    // local variables of the reachable modules, the ones only used by unused exports
+    // are left out since they go away with the exports
+    pub unused_local_variables: Vec<ModuleSymbol>,
+    // entry point globs matching no module
+    pub unmatched_entry_points: Vec<String>,
+}
+
+// Find the symbols not used by the entry modules, e.g. route files, main.tsx or the
+// modules exported by the package. Every symbol of the entry modules is used, the
+// symbols they depend on transitively are used as well.
+//
+// The entry points are globs relative to the project root. Imports for side effects
+// and top-level statements are not in the graph, so modules only imported for side
+// effects should be given as entry points too.
+pub fn find_dead_code(
+    used_by_graph: &UsedByGraph,
+    entry_points: &[String],
+) -> anyhow::Result<DeadCodeReport> {
+    let (entry_modules, unmatched_entry_points) = match_entry_points(used_by_graph, entry_points)?;
+
+    let graph = SymbolGraph::new(used_by_graph);
+    let live = graph.walk(
+        graph
+            .symbols
+            .iter()
+            .filter(|(module_path, _)| entry_modules.contains(module_path))
+            .cloned(),
+        |_| true,
+        |graph, symbol| &graph.depend_on[symbol],
+    );
+
+    let reachable_modules: HashSet<&str> = entry_modules
+        .iter()
+        .map(|module_path| module_path.as_str())
+        .chain(live.iter().map(|(module_path, _)| module_path.as_str()))
+        .collect();
+    let mut unreachable_modules: Vec<String> = used_by_graph
+        .modules
+        .keys()
+        .filter(|module_path| !reachable_modules.contains(module_path.as_str()))
+        .cloned()
+        .collect();
+    unreachable_modules.sort();
+
+    let mut dead: Vec<&ModuleSymbol> = graph
+        .symbols
+        .iter()
+        .filter(|symbol| reachable_modules.contains(symbol.0.as_str()) && !live.contains(*symbol))
+        .collect();
+    dead.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    let is_dead_export = |symbol: &ModuleSymbol| is_export(symbol) && !live.contains(symbol);
+
+    // An unused export passing through another unused export of a reachable module is
+    // reported along with that one, the head of such re-export chains goes first. The
+    // exports left after the heads are re-exporting each other in cycles.
+    let is_head = |symbol: &ModuleSymbol| {
+        !graph.depend_on[symbol].iter().any(|dependency| {
+            dependency.0 != symbol.0
+                && reachable_modules.contains(dependency.0.as_str())
+                && is_dead_export(dependency)
+        })
+    };
+    let dead_exports: Vec<&ModuleSymbol> = dead
+        .iter()
+        .filter(|symbol| is_export(symbol))
+        .cloned()
+        .collect();
+    let mut unused_exports = vec![];
+    let mut reported = HashSet::new();
+    for symbol in dead_exports
+        .iter()
+        .filter(|symbol| is_head(symbol))
+        .chain(dead_exports.iter().filter(|symbol| !is_head(symbol)))
+    {
+        if reported.contains(*symbol) {
+            continue;
+        }
+        let re_exports = graph.walk(
+            [(*symbol).clone()],
+            |(module_path, next)| {
+                // the module itself, e.g. `export { name as alias }`, is not a barrel
+                module_path != &next.0 && is_dead_export(next)
+            },
+            |graph, symbol| &graph.used_by[symbol],
+        );
+        let mut re_exported_by: Vec<ModuleSymbol> = re_exports
+            .iter()
+            .filter(|re_export| re_export != symbol)
+            .cloned()
+            .collect();
+        re_exported_by.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+        reported.extend(re_exports);
+        unused_exports.push(UnusedExport {
+            module_path: symbol.0.clone(),
+            symbol: symbol.1.clone(),
+            re_exported_by,
+        });
+    }
+
+    let only_used_by_unused_exports = graph.walk(
+        reported.iter().cloned(),
+        |(_, next)| !live.contains(next),
+        |graph, symbol| &graph.depend_on[symbol],
+    );
+    let unused_local_variables = dead
+        .into_iter()
+        .filter(|symbol| !is_export(symbol) && !only_used_by_unused_exports.contains(*symbol))
+        .cloned()
+        .collect();
+
+    Ok(DeadCodeReport {
+        entry_modules,
+        unreachable_modules,
+        unused_exports,
+        unused_local_variables,
+        unmatched_entry_points,
+    })
+}
+
+fn is_export((_, symbol): &ModuleSymbol) -> bool {
+    !matches!(symbol, TraceTarget::LocalVar(_))
+}
+
+fn sort_key((module_path, symbol): &ModuleSymbol) -> (&str, u8, &str) {
+    match symbol {
+        TraceTarget::NamedExport(name) => (module_path, 0, name),
+        TraceTarget::DefaultExport => (module_path, 1, ""),
+        TraceTarget::LocalVar(name) => (module_path, 2, name),
+    }
+}
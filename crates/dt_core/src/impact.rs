@@ -0,0 +1,267 @@
+use crate::{
+    analyzer::Analysis,
+    diff::FileDiff,
+    parser::{collect_symbol_spans, types::SpannedSymbol, Input},
+    path_resolver::{to_absolute_path, to_relative_path, ToCanonicalString},
+    tracker::{DependencyTracker, ModuleSymbol, TraceTarget},
+};
+use anyhow::Context;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap, HashSet},
+    path::PathBuf,
+};
+
+#[derive(Debug, Serialize)]
+pub struct SymbolImpact {
+    pub module_path: String,
+    pub symbol: TraceTarget,
+    // the symbol is gone at the new side, it's traced at the old side
+    pub deleted: bool,
+    pub routes: BTreeSet<String>,
+    pub i18n_keys: BTreeSet<String>,
+    // the last symbols of the dependency paths, nothing depends on them
+    pub entry_points: Vec<ModuleSymbol>,
+    // the symbols using each other on the way, see `TraceResult`
+    pub cycles: Vec<Vec<ModuleSymbol>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ImpactReport {
+    pub changed_symbols: Vec<SymbolImpact>,
+    pub routes: BTreeSet<String>,
+    pub i18n_keys: BTreeSet<String>,
+    // changed files which are not modules of the analysis, e.g. styles or files outside
+    // the project root, or deleted files without the old side
+    pub skipped_files: Vec<String>,
+}
+
+// The project analyzed at the old side of the diff, e.g. checked out from the revision
+// the diff is from. The paths in the diff are relative to `repo_root`.
+pub struct OldSide<'a> {
+    pub analysis: &'a Analysis,
+    pub repo_root: &'a str,
+}
+
+// One side of the diff to look up and trace the symbols.
+struct Side<'a> {
+    analysis: &'a Analysis,
+    repo_root: &'a str,
+    symbol_to_i18n: HashMap<(&'a str, &'a str), Vec<&'a str>>,
+    dependency_tracker: DependencyTracker<'a>,
+}
+
+impl<'a> Side<'a> {
+    fn new(analysis: &'a Analysis, repo_root: &'a str) -> Self {
+        Self {
+            analysis,
+            repo_root,
+            symbol_to_i18n: invert_i18n_to_symbol(&analysis.i18n_to_symbol),
+            dependency_tracker: DependencyTracker::new(&analysis.used_by_graph, true),
+        }
+    }
+
+    // the module path of a path in the diff if it's a module of the analysis
+    fn get_module_path(&self, path: Option<&String>) -> Option<String> {
+        let module_path = PathBuf::from(self.repo_root)
+            .join(path?)
+            .to_canonical_string()
+            .ok()
+            .map(|module_path| to_relative_path(&self.analysis.project_root, &module_path))?;
+        match self
+            .analysis
+            .used_by_graph
+            .modules
+            .contains_key(&module_path)
+        {
+            true => Some(module_path),
+            false => None,
+        }
+    }
+
+    // Trace the symbols depending on the symbol, the routes and i18n keys used by any
+    // symbol on the dependency paths are affected.
+    fn trace(
+        &mut self,
+        module_path: &str,
+        symbol: TraceTarget,
+        deleted: bool,
+    ) -> anyhow::Result<SymbolImpact> {
+        let mut symbol_impact = SymbolImpact {
+            module_path: module_path.to_owned(),
+            symbol: symbol.clone(),
+            deleted,
+            routes: BTreeSet::new(),
+            i18n_keys: BTreeSet::new(),
+            entry_points: vec![],
+            cycles: vec![],
+        };
+        let mut entry_points = HashSet::new();
+        let trace_result = self
+            .dependency_tracker
+            .trace((module_path.to_owned(), symbol))
+            .context(format!("trace changed symbols of module {}", module_path))?;
+        for full_path in trace_result.paths.iter() {
+            if let Some(entry_point) = full_path.first() {
+                if entry_points.insert(entry_point.clone()) {
+                    symbol_impact.entry_points.push(entry_point.clone());
+                }
+            }
+            for (step_module_path, step_trace_target) in full_path.iter() {
+                // routes and i18n keys are only related to local variables
+                if let TraceTarget::LocalVar(step_symbol_name) = step_trace_target {
+                    if let Some(routes) = self
+                        .analysis
+                        .symbol_to_route
+                        .get(step_module_path)
+                        .and_then(|symbol_to_routes| symbol_to_routes.get(step_symbol_name))
+                    {
+                        symbol_impact.routes.extend(routes.iter().cloned());
+                    }
+                    if let Some(i18n_keys) = self
+                        .symbol_to_i18n
+                        .get(&(step_module_path.as_str(), step_symbol_name.as_str()))
+                    {
+                        symbol_impact
+                            .i18n_keys
+                            .extend(i18n_keys.iter().map(|key| key.to_string()));
+                    }
+                }
+            }
+        }
+        symbol_impact.cycles = trace_result.cycles;
+        Ok(symbol_impact)
+    }
+}
+
+// Map the changed lines of each file onto the module-scoped symbols, then trace the
+// symbols depending on them. The routes and i18n keys used by any symbol on the
+// dependency paths are affected.
+//
+// The paths in the diff are relative to `repo_root`. The spans are collected from the
+// files on disk, so the project should be analyzed at the new side of the diff. Give the
+// old side to trace the symbols deleted by the diff as well, including the ones of the
+// deleted files, they're looked up by the removed lines at the old side.
+pub fn analyze_impact(
+    analysis: &Analysis,
+    repo_root: &str,
+    file_diffs: &[FileDiff],
+    old_side: Option<OldSide>,
+) -> anyhow::Result<ImpactReport> {
+    let mut new_side = Side::new(analysis, repo_root);
+    let mut old_side = old_side.map(|old_side| Side::new(old_side.analysis, old_side.repo_root));
+    let mut report = ImpactReport {
+        changed_symbols: vec![],
+        routes: BTreeSet::new(),
+        i18n_keys: BTreeSet::new(),
+        skipped_files: vec![],
+    };
+
+    for file_diff in file_diffs.iter() {
+        let mut symbol_impacts = vec![];
+        let new_module_path = new_side.get_module_path(file_diff.new_path.as_ref());
+        if let Some(module_path) = new_module_path.as_ref() {
+            let changed_symbols = find_symbols(analysis, module_path, |start, end| {
+                file_diff.is_changed(start, end)
+            })?;
+            for symbol in changed_symbols {
+                symbol_impacts.push(new_side.trace(module_path, symbol, false)?);
+            }
+        }
+        let old_module_path = old_side
+            .as_ref()
+            .and_then(|old_side| old_side.get_module_path(file_diff.old_path.as_ref()));
+        if let (Some(old_side), Some(module_path)) = (old_side.as_mut(), old_module_path.as_ref()) {
+            let removed_symbols = find_symbols(old_side.analysis, module_path, |start, end| {
+                file_diff.is_removed(start, end)
+            })?;
+            // the symbols still at the new side are traced there if they're changed
+            let new_module = new_module_path
+                .as_ref()
+                .and_then(|module_path| analysis.depend_on_graph.table.get(module_path));
+            for symbol in removed_symbols {
+                let is_kept = new_module.is_some_and(|new_module| match &symbol {
+                    TraceTarget::LocalVar(name) => {
+                        new_module.local_variable_table.contains_key(name)
+                    }
+                    TraceTarget::NamedExport(name) => {
+                        new_module.named_export_table.contains_key(name)
+                    }
+                    TraceTarget::DefaultExport => new_module.default_export.is_some(),
+                });
+                if !is_kept {
+                    symbol_impacts.push(old_side.trace(module_path, symbol, true)?);
+                }
+            }
+        }
+        if new_module_path.is_none() && old_module_path.is_none() {
+            if let Some(path) = file_diff.new_path.as_ref().or(file_diff.old_path.as_ref()) {
+                report.skipped_files.push(path.to_owned());
+            }
+        }
+
+        for symbol_impact in symbol_impacts {
+            report.routes.extend(symbol_impact.routes.iter().cloned());
+            report
+                .i18n_keys
+                .extend(symbol_impact.i18n_keys.iter().cloned());
+            report.changed_symbols.push(symbol_impact);
+        }
+    }
+
+    Ok(report)
+}
+
+// The symbols whose spans are touched by the lines, symbols unknown to the graph are
+// ignored.
+fn find_symbols(
+    analysis: &Analysis,
+    module_path: &str,
+    is_touched: impl Fn(usize, usize) -> bool,
+) -> anyhow::Result<Vec<TraceTarget>> {
+    let module_src = to_absolute_path(&analysis.project_root, module_path);
+    let (module_ast, source_map) = Input::Path(&module_src)
+        .get_module_ast_and_source_map()
+        .context(format!("get module ast, module_src: {}", module_src))?;
+    let module = analysis.used_by_graph.modules.get(module_path).unwrap();
+
+    let mut symbols = vec![];
+    for symbol_span in collect_symbol_spans(&module_ast, &source_map) {
+        if !is_touched(symbol_span.start_line, symbol_span.end_line) {
+            continue;
+        }
+        let (target, is_traceable) = match symbol_span.symbol {
+            SpannedSymbol::LocalVariable(name) => {
+                let is_traceable = module.local_variable_table.contains_key(&name);
+                (TraceTarget::LocalVar(name), is_traceable)
+            }
+            SpannedSymbol::NamedExport(name) => {
+                let is_traceable = module.named_export_table.contains_key(&name);
+                (TraceTarget::NamedExport(name), is_traceable)
+            }
+            SpannedSymbol::DefaultExport => (TraceTarget::DefaultExport, true),
+        };
+        if is_traceable && !symbols.contains(&target) {
+            symbols.push(target);
+        }
+    }
+    Ok(symbols)
+}
+
+// "i18n key" => "module path" => ["symbol"] to ("module path", "symbol") => ["i18n key"]
+fn invert_i18n_to_symbol(
+    i18n_to_symbol: &HashMap<String, HashMap<String, HashSet<String>>>,
+) -> HashMap<(&str, &str), Vec<&str>> {
+    let mut symbol_to_i18n: HashMap<(&str, &str), Vec<&str>> = HashMap::new();
+    for (i18n_key, module_to_symbols) in i18n_to_symbol.iter() {
+        for (module_path, symbols) in module_to_symbols.iter() {
+            for symbol in symbols.iter() {
+                symbol_to_i18n
+                    .entry((module_path.as_str(), symbol.as_str()))
+                    .or_default()
+                    .push(i18n_key.as_str());
+            }
+        }
+    }
+    symbol_to_i18n
+}
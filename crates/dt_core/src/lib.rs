@@ -1,7 +1,24 @@
+pub mod analyzer;
+pub mod boundary;
+pub mod dead_code;
+pub mod impact;
+pub mod metrics;
+pub mod search;
+pub mod snapshot_diff;
+mod symbol_graph;
+
+pub mod cache {
+    pub use dt_cache::*;
+}
+
 pub mod database {
     pub use dt_database::*;
 }
 
+pub mod diff {
+    pub use dt_diff::*;
+}
+
 pub mod graph {
     pub use dt_graph::*;
 }
@@ -33,3 +50,7 @@ pub mod scheduler {
 pub mod tracker {
     pub use dt_tracker::*;
 }
+
+pub mod visualize {
+    pub use dt_visualize::*;
+}
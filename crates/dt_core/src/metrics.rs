@@ -0,0 +1,347 @@
+use crate::{
+    analyzer::Analysis,
+    graph::projection::Projection,
+    symbol_graph::{match_entry_points, SymbolGraph},
+    tracker::{ModuleSymbol, TraceTarget},
+};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Metrics {
+    // the number of symbols or modules using this one directly
+    pub fan_in: usize,
+    // the number of symbols or modules this one depends on directly
+    pub fan_out: usize,
+    // the number of symbols or modules using this one directly or indirectly
+    pub transitive_dependents: usize,
+    // the number of routes depending on this one
+    pub routes: usize,
+    // the length of the shortest dependency chain from the entry modules, None if it's
+    // not reachable from them
+    pub depth: Option<usize>,
+    // the number of shortest dependency chains between the other symbols or modules
+    // going through this one, the chains are split evenly when there are many of them
+    pub bridge_score: f64,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct SymbolMetrics {
+    pub module_path: String,
+    pub symbol: TraceTarget,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ModuleMetrics {
+    pub module_path: String,
+    #[serde(flatten)]
+    pub metrics: Metrics,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RankBy {
+    FanIn,
+    FanOut,
+    TransitiveDependents,
+    Routes,
+    BridgeScore,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetricsReport {
+    pub symbols: Vec<SymbolMetrics>,
+    pub modules: Vec<ModuleMetrics>,
+    // entry point globs matching no module
+    pub unmatched_entry_points: Vec<String>,
+}
+
+impl MetricsReport {
+    // Sort the symbols and modules by the given metric in descending order, the ties
+    // are broken by the transitive dependents and then the routes.
+    pub fn rank(&mut self, by: RankBy) {
+        let key = |metrics: &Metrics| {
+            let primary = match by {
+                RankBy::FanIn => metrics.fan_in as f64,
+                RankBy::FanOut => metrics.fan_out as f64,
+                RankBy::TransitiveDependents => metrics.transitive_dependents as f64,
+                RankBy::Routes => metrics.routes as f64,
+                RankBy::BridgeScore => metrics.bridge_score,
+            };
+            (primary, metrics.transitive_dependents, metrics.routes)
+        };
+        let compare = |a: &Metrics, b: &Metrics| key(b).partial_cmp(&key(a)).unwrap();
+        self.symbols.sort_by(|a, b| compare(&a.metrics, &b.metrics));
+        self.modules.sort_by(|a, b| compare(&a.metrics, &b.metrics));
+    }
+
+    // Keep the named and default exports only, the local variables have almost the same
+    // metrics as their exports.
+    pub fn retain_exports(&mut self) {
+        self.symbols
+            .retain(|symbol| !matches!(symbol.symbol, TraceTarget::LocalVar(_)));
+    }
+
+    pub fn truncate(&mut self, limit: usize) {
+        self.symbols.truncate(limit);
+        self.modules.truncate(limit);
+    }
+}
+
+// Compute the metrics of every symbol and module of the used-by graph, e.g. to find the
+// most impactful components before a redesign. The entry points are globs relative to
+// the project root like the ones of `find_dead_code()`, they are only used for the depth.
+//
+// The bridge scores take a BFS from every symbol, give `bridge_score_samples` to start
+// from that many symbols only for large projects, the scores are scaled up then.
+pub fn compute_metrics(
+    analysis: &Analysis,
+    entry_points: &[String],
+    bridge_score_samples: Option<usize>,
+) -> anyhow::Result<MetricsReport> {
+    let used_by_graph = &analysis.used_by_graph;
+    let (entry_modules, unmatched_entry_points) = match_entry_points(used_by_graph, entry_points)?;
+
+    let symbol_graph = SymbolGraph::new(used_by_graph);
+    let mut symbols: Vec<&ModuleSymbol> = symbol_graph.symbols.iter().collect();
+    symbols.sort_by(|a, b| sort_key(a).cmp(&sort_key(b)));
+    let symbol_ids: HashMap<&ModuleSymbol, usize> = symbols
+        .iter()
+        .enumerate()
+        .map(|(id, symbol)| (*symbol, id))
+        .collect();
+    let to_ids = |neighbors: &Vec<ModuleSymbol>| -> Vec<usize> {
+        let ids: HashSet<usize> = neighbors.iter().map(|symbol| symbol_ids[symbol]).collect();
+        let mut ids: Vec<usize> = ids.into_iter().collect();
+        ids.sort();
+        ids
+    };
+    let graph = IndexedGraph {
+        depend_on: symbols
+            .iter()
+            .map(|symbol| to_ids(&symbol_graph.depend_on[*symbol]))
+            .collect(),
+        used_by: symbols
+            .iter()
+            .map(|symbol| to_ids(&symbol_graph.used_by[*symbol]))
+            .collect(),
+    };
+
+    // the routes are attached to the local variables, they reach everything the local
+    // variables depend on
+    let mut route_ids: HashMap<&str, usize> = HashMap::new();
+    let mut routes: Vec<HashSet<usize>> = vec![HashSet::new(); symbols.len()];
+    for (module_path, symbol_to_routes) in analysis.symbol_to_route.iter() {
+        for (name, route_paths) in symbol_to_routes.iter() {
+            let symbol = (
+                module_path.to_owned(),
+                TraceTarget::LocalVar(name.to_owned()),
+            );
+            let id = match symbol_ids.get(&symbol) {
+                Some(id) => *id,
+                None => continue,
+            };
+            let attached: Vec<usize> = route_paths
+                .iter()
+                .map(|route_path| {
+                    let next_id = route_ids.len();
+                    *route_ids.entry(route_path).or_insert(next_id)
+                })
+                .collect();
+            for reached in reachable(&graph.depend_on, [id]) {
+                routes[reached].extend(attached.iter());
+            }
+        }
+    }
+
+    let entry_symbols = (0..symbols.len())
+        .filter(|id| entry_modules.binary_search(&symbols[*id].0).is_ok())
+        .collect::<Vec<usize>>();
+    let depths = graph.depths(&entry_symbols);
+    let bridge_scores = graph.bridge_scores(bridge_score_samples);
+    let symbol_metrics = symbols
+        .iter()
+        .enumerate()
+        .map(|(id, (module_path, symbol))| SymbolMetrics {
+            module_path: module_path.to_owned(),
+            symbol: symbol.to_owned(),
+            metrics: Metrics {
+                fan_in: graph.used_by[id].len(),
+                fan_out: graph.depend_on[id].len(),
+                transitive_dependents: reachable(&graph.used_by, [id]).len() - 1,
+                routes: routes[id].len(),
+                depth: depths[id],
+                bridge_score: bridge_scores[id],
+            },
+        })
+        .collect();
+
+    // the modules are measured on the projected graph, a module reaches the routes of
+    // its symbols
+    let projection = Projection::modules(used_by_graph);
+    let modules: Vec<&String> = projection.nodes.iter().collect();
+    let module_ids: HashMap<&str, usize> = modules
+        .iter()
+        .enumerate()
+        .map(|(id, module_path)| (module_path.as_str(), id))
+        .collect();
+    let to_ids = |neighbors: Vec<(&str, _)>| -> Vec<usize> {
+        neighbors
+            .into_iter()
+            .map(|(module_path, _)| module_ids[module_path])
+            .collect()
+    };
+    let module_graph = IndexedGraph {
+        depend_on: modules
+            .iter()
+            .map(|module_path| to_ids(projection.get_dependencies(module_path)))
+            .collect(),
+        used_by: modules
+            .iter()
+            .map(|module_path| to_ids(projection.get_dependents(module_path)))
+            .collect(),
+    };
+    let mut module_routes: Vec<HashSet<usize>> = vec![HashSet::new(); modules.len()];
+    for (id, (module_path, _)) in symbols.iter().enumerate() {
+        module_routes[module_ids[module_path.as_str()]].extend(routes[id].iter());
+    }
+    let entry_module_ids: Vec<usize> = entry_modules
+        .iter()
+        .map(|module_path| module_ids[module_path.as_str()])
+        .collect();
+    let module_depths = module_graph.depths(&entry_module_ids);
+    let module_bridge_scores = module_graph.bridge_scores(bridge_score_samples);
+    let module_metrics = modules
+        .iter()
+        .enumerate()
+        .map(|(id, module_path)| ModuleMetrics {
+            module_path: module_path.to_string(),
+            metrics: Metrics {
+                fan_in: module_graph.used_by[id].len(),
+                fan_out: module_graph.depend_on[id].len(),
+                transitive_dependents: reachable(&module_graph.used_by, [id]).len() - 1,
+                routes: module_routes[id].len(),
+                depth: module_depths[id],
+                bridge_score: module_bridge_scores[id],
+            },
+        })
+        .collect();
+
+    Ok(MetricsReport {
+        symbols: symbol_metrics,
+        modules: module_metrics,
+        unmatched_entry_points,
+    })
+}
+
+// The nodes reachable from the given ones over the edges, including themselves.
+fn reachable(edges: &[Vec<usize>], from: impl IntoIterator<Item = usize>) -> HashSet<usize> {
+    let mut visited: HashSet<usize> = HashSet::new();
+    let mut queue: VecDeque<usize> = VecDeque::new();
+    for id in from {
+        if visited.insert(id) {
+            queue.push_back(id);
+        }
+    }
+    while let Some(id) = queue.pop_front() {
+        for next in edges[id].iter() {
+            if visited.insert(*next) {
+                queue.push_back(*next);
+            }
+        }
+    }
+    visited
+}
+
+fn sort_key((module_path, symbol): &ModuleSymbol) -> (&str, u8, &str) {
+    match symbol {
+        TraceTarget::LocalVar(name) => (module_path, 0, name),
+        TraceTarget::NamedExport(name) => (module_path, 1, name),
+        TraceTarget::DefaultExport => (module_path, 2, ""),
+    }
+}
+
+// The symbols or modules as indexes, an edge from `A` to `B` in `depend_on` means `A`
+// depends on `B`.
+struct IndexedGraph {
+    depend_on: Vec<Vec<usize>>,
+    used_by: Vec<Vec<usize>>,
+}
+
+impl IndexedGraph {
+    // The length of the shortest path from the given nodes to each node.
+    fn depths(&self, from: &[usize]) -> Vec<Option<usize>> {
+        let mut depths = vec![None; self.depend_on.len()];
+        let mut queue: VecDeque<usize> = VecDeque::new();
+        for id in from.iter() {
+            depths[*id] = Some(0);
+            queue.push_back(*id);
+        }
+        while let Some(id) = queue.pop_front() {
+            let depth = depths[id].unwrap() + 1;
+            for next in self.depend_on[id].iter() {
+                if depths[*next].is_none() {
+                    depths[*next] = Some(depth);
+                    queue.push_back(*next);
+                }
+            }
+        }
+        depths
+    }
+
+    // Betweenness centrality with Brandes' algorithm, starting from evenly spaced
+    // samples of the nodes if `samples` is given.
+    fn bridge_scores(&self, samples: Option<usize>) -> Vec<f64> {
+        let n = self.depend_on.len();
+        let mut scores = vec![0.0; n];
+        let step = match samples {
+            Some(samples) if samples > 0 && samples < n => n.div_ceil(samples),
+            _ => 1,
+        };
+        let sources: Vec<usize> = (0..n).step_by(step).collect();
+
+        let mut distances: Vec<Option<usize>> = vec![None; n];
+        let mut path_counts = vec![0.0; n];
+        let mut predecessors: Vec<Vec<usize>> = vec![vec![]; n];
+        let mut dependencies = vec![0.0; n];
+        for source in sources.iter() {
+            let mut visited = vec![];
+            let mut queue: VecDeque<usize> = VecDeque::from([*source]);
+            distances[*source] = Some(0);
+            path_counts[*source] = 1.0;
+            while let Some(id) = queue.pop_front() {
+                visited.push(id);
+                let distance = distances[id].unwrap() + 1;
+                for next in self.depend_on[id].iter() {
+                    if distances[*next].is_none() {
+                        distances[*next] = Some(distance);
+                        queue.push_back(*next);
+                    }
+                    if distances[*next] == Some(distance) {
+                        path_counts[*next] += path_counts[id];
+                        predecessors[*next].push(id);
+                    }
+                }
+            }
+            for id in visited.iter().rev() {
+                for predecessor in predecessors[*id].iter() {
+                    dependencies[*predecessor] +=
+                        path_counts[*predecessor] / path_counts[*id] * (1.0 + dependencies[*id]);
+                }
+                if id != source {
+                    scores[*id] += dependencies[*id];
+                }
+            }
+            for id in visited {
+                distances[id] = None;
+                path_counts[id] = 0.0;
+                predecessors[id].clear();
+                dependencies[id] = 0.0;
+            }
+        }
+
+        let scale = n as f64 / sources.len().max(1) as f64;
+        scores.iter().map(|score| score * scale).collect()
+    }
+}
@@ -0,0 +1,76 @@
+use crate::tracker::{shared::SharedTracker, store::GraphStore, TraceGraph};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Serialize)]
+pub struct Step {
+    pub module_path: String,
+    pub symbol_name: String,
+}
+
+// The symbols from the one using the i18n key, which comes first, to the ones attached to
+// a route. `(a, b)` of the edges means `nodes[a]` is used by `nodes[b]`.
+#[derive(Debug, Serialize)]
+pub struct StepGraph {
+    pub nodes: Vec<Step>,
+    pub edges: Vec<(usize, usize)>,
+}
+
+impl From<&TraceGraph> for StepGraph {
+    fn from(trace_graph: &TraceGraph) -> Self {
+        Self {
+            nodes: trace_graph
+                .nodes
+                .iter()
+                .map(|(module_path, trace_target)| Step {
+                    module_path: module_path.clone(),
+                    symbol_name: trace_target.to_string(),
+                })
+                .collect(),
+            edges: trace_graph.edges.clone(),
+        }
+    }
+}
+
+// i18n key => route => symbol name => one graph per symbol with the name
+pub type SearchResult = HashMap<String, HashMap<String, HashMap<String, Vec<StepGraph>>>>;
+
+// Find the i18n keys by their translation, then trace the symbols using each key to the
+// routes, the search API of both servers. The graphs are cached by the tracker, so the
+// symbols found by the earlier searches are not traced again. The matched keys are in the
+// result even if no routes are reached, so it's empty only when nothing matches.
+pub fn search(
+    store: &impl GraphStore,
+    tracker: &SharedTracker,
+    search: &str,
+    exact_match: bool,
+) -> anyhow::Result<SearchResult> {
+    let mut search_result = HashMap::new();
+    for i18n_key in store.search_translations(search, exact_match)? {
+        let mut route_to_graphs: HashMap<String, HashMap<String, Vec<StepGraph>>> = HashMap::new();
+        let mut sources = store.translation_usages(&i18n_key)?;
+        // a symbol using the key more than once is traced once
+        let mut seen = HashSet::new();
+        sources.retain(|source| seen.insert(source.clone()));
+        let trace_graphs = tracker.trace_graphs(store, sources.clone())?;
+        for ((_, source_trace_target), trace_graph) in sources.iter().zip(trace_graphs) {
+            // find the symbols used in some routes
+            let mut route_to_nodes: HashMap<String, Vec<usize>> = HashMap::new();
+            for (i, step) in trace_graph.nodes.iter().enumerate() {
+                for route in store.routes(step)? {
+                    route_to_nodes.entry(route).or_default().push(i);
+                }
+            }
+            for (route, nodes) in route_to_nodes {
+                route_to_graphs
+                    .entry(route)
+                    .or_default()
+                    .entry(source_trace_target.to_string())
+                    .or_default()
+                    .push(StepGraph::from(&trace_graph.subgraph_to(&nodes)));
+            }
+        }
+        search_result.insert(i18n_key, route_to_graphs);
+    }
+    Ok(search_result)
+}
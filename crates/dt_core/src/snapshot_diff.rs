@@ -0,0 +1,361 @@
+use crate::{
+    database::{
+        models::{self, SymbolVariant},
+        SqliteDb,
+    },
+    graph::used_by_graph::{UsedBy, UsedByOther, UsedByType},
+    portable::Portable,
+};
+use anyhow::Context;
+use serde::Serialize;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SymbolKind {
+    LocalVariable,
+    NamedExport,
+    DefaultExport,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Symbol {
+    pub module_path: String,
+    pub kind: SymbolKind,
+    // "default" for the default export
+    pub name: String,
+}
+
+impl Symbol {
+    pub fn new(module_path: &str, kind: SymbolKind, name: &str) -> Self {
+        let name = match kind {
+            SymbolKind::DefaultExport => "default",
+            _ => name,
+        };
+        Self {
+            module_path: module_path.to_owned(),
+            kind,
+            name: name.to_owned(),
+        }
+    }
+
+    fn from_used_by_type(module_path: &str, used_by_type: &UsedByType) -> Self {
+        match used_by_type {
+            UsedByType::LocalVar(name) => Self::new(module_path, SymbolKind::LocalVariable, name),
+            UsedByType::NamedExport(name) => Self::new(module_path, SymbolKind::NamedExport, name),
+            UsedByType::DefaultExport => Self::new(module_path, SymbolKind::DefaultExport, ""),
+        }
+    }
+}
+
+impl fmt::Display for Symbol {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.kind {
+            SymbolKind::LocalVariable => write!(f, "{} {}", self.module_path, self.name),
+            SymbolKind::NamedExport => write!(f, "{} export {}", self.module_path, self.name),
+            SymbolKind::DefaultExport => write!(f, "{} export default", self.module_path),
+        }
+    }
+}
+
+// `from` depends on `to`, see the edge rules of the README.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct Dependency {
+    pub from: Symbol,
+    pub to: Symbol,
+}
+
+impl fmt::Display for Dependency {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} -> {}", self.from, self.to)
+    }
+}
+
+// The routes and i18n keys are attached to the local variables.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct RouteUsage {
+    pub route: String,
+    pub module_path: String,
+    pub symbol: String,
+}
+
+impl fmt::Display for RouteUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} {}", self.route, self.module_path, self.symbol)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+pub struct I18nUsage {
+    pub key: String,
+    pub module_path: String,
+    pub symbol: String,
+}
+
+impl fmt::Display for I18nUsage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {} {}", self.key, self.module_path, self.symbol)
+    }
+}
+
+// The structure of a project at some point, e.g. a release, loaded from a portable or a
+// project of the database. Compare the snapshots from the same source, the database
+// keeps the symbols imported from the modules outside of the project while the
+// portable doesn't.
+#[derive(Debug, Default, PartialEq)]
+pub struct Snapshot {
+    pub modules: BTreeSet<String>,
+    pub symbols: BTreeSet<Symbol>,
+    pub dependencies: BTreeSet<Dependency>,
+    pub route_usages: BTreeSet<RouteUsage>,
+    pub i18n_usages: BTreeSet<I18nUsage>,
+}
+
+impl Snapshot {
+    pub fn from_portable(portable: &Portable) -> Self {
+        let mut snapshot = Self::default();
+        for (module_path, module) in portable.used_by_graph.modules.iter() {
+            snapshot.modules.insert(module_path.to_owned());
+            let local_variables = module.local_variable_table.iter().map(|(name, used_by)| {
+                (
+                    Symbol::new(module_path, SymbolKind::LocalVariable, name),
+                    used_by,
+                )
+            });
+            let named_exports = module.named_export_table.iter().map(|(name, used_by)| {
+                (
+                    Symbol::new(module_path, SymbolKind::NamedExport, name),
+                    used_by,
+                )
+            });
+            // unused default exports are None as well, they are found from their users
+            // below, e.g. the local variables exported as default
+            let default_export = module.default_export.as_ref().map(|_| {
+                (
+                    Symbol::new(module_path, SymbolKind::DefaultExport, ""),
+                    &module.default_export,
+                )
+            });
+            for (symbol, used_by_list) in local_variables.chain(named_exports).chain(default_export)
+            {
+                for used_by in used_by_list.iter().flatten() {
+                    let user = match used_by {
+                        UsedBy::Itself(used_by_type) => {
+                            Symbol::from_used_by_type(module_path, used_by_type)
+                        }
+                        UsedBy::Other(UsedByOther { by, by_type }) => {
+                            Symbol::from_used_by_type(by, by_type)
+                        }
+                    };
+                    snapshot.dependencies.insert(Dependency {
+                        from: user,
+                        to: symbol.clone(),
+                    });
+                }
+                snapshot.symbols.insert(symbol);
+            }
+        }
+        // the users are symbols of the graph as well
+        for dependency in snapshot.dependencies.iter() {
+            snapshot.symbols.insert(dependency.from.clone());
+        }
+
+        for (module_path, symbol_to_routes) in portable.symbol_to_route.iter() {
+            for (symbol, routes) in symbol_to_routes.iter() {
+                for route in routes.iter() {
+                    snapshot.route_usages.insert(RouteUsage {
+                        route: route.to_owned(),
+                        module_path: module_path.to_owned(),
+                        symbol: symbol.to_owned(),
+                    });
+                }
+            }
+        }
+        for (key, module_to_symbols) in portable.i18n_to_symbol.iter() {
+            for (module_path, symbols) in module_to_symbols.iter() {
+                for symbol in symbols.iter() {
+                    snapshot.i18n_usages.insert(I18nUsage {
+                        key: key.to_owned(),
+                        module_path: module_path.to_owned(),
+                        symbol: symbol.to_owned(),
+                    });
+                }
+            }
+        }
+        snapshot
+    }
+
+    pub fn from_database(db: &SqliteDb, project_name: &str) -> anyhow::Result<Self> {
+        let conn = &db.conn;
+        let project = models::Project::retrieve_by_name(conn, project_name)
+            .context(format!("retrieve project {}", project_name))?;
+        let mut snapshot = Self::default();
+
+        let modules: HashMap<usize, String> = project
+            .get_modules(conn)?
+            .into_iter()
+            .map(|module| (module.id, module.path))
+            .collect();
+        snapshot.modules.extend(modules.values().cloned());
+        let symbols: HashMap<usize, Symbol> = project
+            .get_symbols(conn)?
+            .into_iter()
+            .map(|symbol| {
+                let kind = match symbol.variant {
+                    SymbolVariant::LocalVariable => SymbolKind::LocalVariable,
+                    SymbolVariant::NamedExport => SymbolKind::NamedExport,
+                    SymbolVariant::DefaultExport => SymbolKind::DefaultExport,
+                };
+                let module_path = &modules[&symbol.module_id];
+                (symbol.id, Symbol::new(module_path, kind, &symbol.name))
+            })
+            .collect();
+        snapshot.symbols.extend(symbols.values().cloned());
+        for symbol_dependency in project.get_symbol_dependencies(conn)? {
+            snapshot.dependencies.insert(Dependency {
+                from: symbols[&symbol_dependency.symbol_id].clone(),
+                to: symbols[&symbol_dependency.depend_on_symbol_id].clone(),
+            });
+        }
+
+        let routes: HashMap<usize, String> = project
+            .get_routes(conn)?
+            .into_iter()
+            .map(|route| (route.id, route.path))
+            .collect();
+        for route_usage in project.get_route_usages(conn)? {
+            let symbol = &symbols[&route_usage.symbol_id];
+            snapshot.route_usages.insert(RouteUsage {
+                route: routes[&route_usage.route_id].to_owned(),
+                module_path: symbol.module_path.to_owned(),
+                symbol: symbol.name.to_owned(),
+            });
+        }
+        let translations: HashMap<usize, String> = project
+            .get_translations(conn)?
+            .into_iter()
+            .map(|translation| (translation.id, translation.key))
+            .collect();
+        for translation_usage in project.get_translation_usages(conn)? {
+            let symbol = &symbols[&translation_usage.symbol_id];
+            snapshot.i18n_usages.insert(I18nUsage {
+                key: translations[&translation_usage.translation_id].to_owned(),
+                module_path: symbol.module_path.to_owned(),
+                symbol: symbol.name.to_owned(),
+            });
+        }
+        Ok(snapshot)
+    }
+
+    // What changed from `self` to `new`.
+    pub fn diff(&self, new: &Snapshot) -> SnapshotDiff {
+        let added_modules = difference(&new.modules, &self.modules);
+        let removed_modules = difference(&self.modules, &new.modules);
+        // the symbols of the added and removed modules come and go with them
+        let added_symbols = difference(&new.symbols, &self.symbols)
+            .into_iter()
+            .filter(|symbol| added_modules.binary_search(&symbol.module_path).is_err())
+            .collect();
+        let removed_symbols = difference(&self.symbols, &new.symbols)
+            .into_iter()
+            .filter(|symbol| removed_modules.binary_search(&symbol.module_path).is_err())
+            .collect();
+        SnapshotDiff {
+            added_symbols,
+            removed_symbols,
+            added_modules,
+            removed_modules,
+            added_dependencies: difference(&new.dependencies, &self.dependencies),
+            removed_dependencies: difference(&self.dependencies, &new.dependencies),
+            added_route_usages: difference(&new.route_usages, &self.route_usages),
+            removed_route_usages: difference(&self.route_usages, &new.route_usages),
+            added_i18n_usages: difference(&new.i18n_usages, &self.i18n_usages),
+            removed_i18n_usages: difference(&self.i18n_usages, &new.i18n_usages),
+        }
+    }
+}
+
+fn difference<T: Ord + Clone>(a: &BTreeSet<T>, b: &BTreeSet<T>) -> Vec<T> {
+    a.difference(b).cloned().collect()
+}
+
+// Everything is sorted, the JSON output is stable and the `Display` output is the
+// readable report.
+#[derive(Debug, Default, PartialEq, Serialize)]
+pub struct SnapshotDiff {
+    pub added_modules: Vec<String>,
+    pub removed_modules: Vec<String>,
+    // the symbols of the added and removed modules are not listed
+    pub added_symbols: Vec<Symbol>,
+    pub removed_symbols: Vec<Symbol>,
+    pub added_dependencies: Vec<Dependency>,
+    pub removed_dependencies: Vec<Dependency>,
+    pub added_route_usages: Vec<RouteUsage>,
+    pub removed_route_usages: Vec<RouteUsage>,
+    pub added_i18n_usages: Vec<I18nUsage>,
+    pub removed_i18n_usages: Vec<I18nUsage>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl fmt::Display for SnapshotDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.is_empty() {
+            return writeln!(f, "no changes");
+        }
+        let sections = [
+            (
+                "modules",
+                self.added_modules.clone(),
+                self.removed_modules.clone(),
+            ),
+            (
+                "symbols",
+                to_lines(&self.added_symbols),
+                to_lines(&self.removed_symbols),
+            ),
+            (
+                "dependencies",
+                to_lines(&self.added_dependencies),
+                to_lines(&self.removed_dependencies),
+            ),
+            (
+                "routes",
+                to_lines(&self.added_route_usages),
+                to_lines(&self.removed_route_usages),
+            ),
+            (
+                "i18n keys",
+                to_lines(&self.added_i18n_usages),
+                to_lines(&self.removed_i18n_usages),
+            ),
+        ];
+        let sections = sections
+            .iter()
+            .filter(|(_, added, removed)| !added.is_empty() || !removed.is_empty());
+        for (i, (title, added, removed)) in sections.enumerate() {
+            if i > 0 {
+                writeln!(f)?;
+            }
+            writeln!(f, "{} (+{} -{})", title, added.len(), removed.len())?;
+            for line in added.iter() {
+                writeln!(f, "  + {}", line)?;
+            }
+            for line in removed.iter() {
+                writeln!(f, "  - {}", line)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+fn to_lines<T: fmt::Display>(items: &[T]) -> Vec<String> {
+    items.iter().map(|item| item.to_string()).collect()
+}
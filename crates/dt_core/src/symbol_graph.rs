@@ -0,0 +1,129 @@
+use crate::{
+    graph::used_by_graph::{UsedBy, UsedByGraph, UsedByOther, UsedByType},
+    tracker::{ModuleSymbol, TraceTarget},
+};
+use anyhow::Context;
+use globset::{Glob, GlobSetBuilder};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+// Match the module paths with the entry point globs, which are relative to the project
+// root. Returns the sorted entry modules and the globs matching no module.
+pub(crate) fn match_entry_points(
+    used_by_graph: &UsedByGraph,
+    entry_points: &[String],
+) -> anyhow::Result<(Vec<String>, Vec<String>)> {
+    let mut builder = GlobSetBuilder::new();
+    for entry_point in entry_points.iter() {
+        builder.add(Glob::new(entry_point).context(format!("invalid glob {}", entry_point))?);
+    }
+    let glob_set = builder.build()?;
+
+    let mut entry_modules = vec![];
+    let mut matched_globs = HashSet::new();
+    for module_path in used_by_graph.modules.keys() {
+        let matches = glob_set.matches(module_path);
+        if !matches.is_empty() {
+            entry_modules.push(module_path.to_owned());
+            matched_globs.extend(matches);
+        }
+    }
+    entry_modules.sort();
+    let unmatched_entry_points = entry_points
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| !matched_globs.contains(i))
+        .map(|(_, entry_point)| entry_point.to_owned())
+        .collect();
+    Ok((entry_modules, unmatched_entry_points))
+}
+
+// The symbols of the used-by graph with the edges in both directions. The default
+// exports are collected from their used-by lists and the symbols using them, since
+// `Module::default_export` is None for both unused and missing default exports.
+pub(crate) struct SymbolGraph {
+    pub symbols: HashSet<ModuleSymbol>,
+    pub depend_on: HashMap<ModuleSymbol, Vec<ModuleSymbol>>,
+    pub used_by: HashMap<ModuleSymbol, Vec<ModuleSymbol>>,
+}
+
+impl SymbolGraph {
+    pub fn new(used_by_graph: &UsedByGraph) -> Self {
+        let mut graph = Self {
+            symbols: HashSet::new(),
+            depend_on: HashMap::new(),
+            used_by: HashMap::new(),
+        };
+        for (module_path, module) in used_by_graph.modules.iter() {
+            let local_variables = module
+                .local_variable_table
+                .iter()
+                .map(|(name, used_by)| (TraceTarget::LocalVar(name.to_owned()), used_by));
+            let named_exports = module
+                .named_export_table
+                .iter()
+                .map(|(name, used_by)| (TraceTarget::NamedExport(name.to_owned()), used_by));
+            let default_export = module
+                .default_export
+                .as_ref()
+                .map(|_| (TraceTarget::DefaultExport, &module.default_export));
+            for (symbol, used_by_list) in local_variables.chain(named_exports).chain(default_export)
+            {
+                let symbol = (module_path.to_owned(), symbol);
+                graph.add_symbol(&symbol);
+                for used_by in used_by_list.iter().flatten() {
+                    let (by, by_type) = match used_by {
+                        UsedBy::Itself(used_by_type) => (module_path, used_by_type),
+                        UsedBy::Other(UsedByOther { by, by_type }) => (by, by_type),
+                    };
+                    let user = (
+                        by.to_owned(),
+                        match by_type {
+                            UsedByType::NamedExport(name) => {
+                                TraceTarget::NamedExport(name.to_owned())
+                            }
+                            UsedByType::DefaultExport => TraceTarget::DefaultExport,
+                            UsedByType::LocalVar(name) => TraceTarget::LocalVar(name.to_owned()),
+                        },
+                    );
+                    graph.add_symbol(&user);
+                    graph.depend_on.get_mut(&user).unwrap().push(symbol.clone());
+                    graph.used_by.get_mut(&symbol).unwrap().push(user);
+                }
+            }
+        }
+        graph
+    }
+
+    fn add_symbol(&mut self, symbol: &ModuleSymbol) {
+        if self.symbols.insert(symbol.clone()) {
+            self.depend_on.insert(symbol.clone(), vec![]);
+            self.used_by.insert(symbol.clone(), vec![]);
+        }
+    }
+
+    // Visit the symbols from the given ones with BFS, `should_visit` is called with
+    // (current module path, next symbol) for each edge.
+    pub fn walk<'graph>(
+        &'graph self,
+        from: impl IntoIterator<Item = ModuleSymbol>,
+        should_visit: impl Fn((&String, &ModuleSymbol)) -> bool,
+        get_next: impl Fn(&'graph Self, &ModuleSymbol) -> &'graph Vec<ModuleSymbol>,
+    ) -> HashSet<ModuleSymbol> {
+        let mut visited: HashSet<ModuleSymbol> = HashSet::new();
+        let mut queue: VecDeque<ModuleSymbol> = VecDeque::new();
+        for symbol in from {
+            if visited.insert(symbol.clone()) {
+                queue.push_back(symbol);
+            }
+        }
+        while let Some(symbol) = queue.pop_front() {
+            for next in get_next(self, &symbol).iter() {
+                if !visited.contains(next) && should_visit((&symbol.0, next)) {
+                    visited.insert(next.clone());
+                    queue.push_back(next.clone());
+                }
+            }
+        }
+        visited
+    }
+}
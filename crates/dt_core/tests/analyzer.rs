@@ -0,0 +1,55 @@
+use dt_core::analyzer::{Analyzer, AnalyzerConfig, AnalyzerEvent};
+
+#[test]
+fn run() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture")).unwrap();
+    let mut events = vec![];
+    let analysis = analyzer
+        .run(|event| {
+            events.push(match event {
+                AnalyzerEvent::Started { module_count } => format!("started {}", module_count),
+                AnalyzerEvent::ModuleAnalyzed { .. } => String::from("analyzed"),
+                AnalyzerEvent::CycleBroken(cycle) => format!("cycle broken {}", cycle),
+                AnalyzerEvent::AmbiguousExport(ambiguous_export) => {
+                    format!("ambiguous export {}", ambiguous_export)
+                }
+                AnalyzerEvent::Finished {
+                    module_count,
+                    from_cache_count,
+                } => format!("finished {} {}", module_count, from_cache_count),
+            })
+        })
+        .unwrap();
+    assert_eq!(
+        events,
+        vec![
+            "started 3",
+            "analyzed",
+            "analyzed",
+            "analyzed",
+            "finished 3 0"
+        ]
+    );
+    assert_eq!(analysis.depend_on_graph.table.len(), 3);
+    assert!(analysis.translation_json.is_empty());
+}
+
+#[test]
+fn blocked_cycles() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-cycle")).unwrap();
+    assert!(analyzer.run(|_| ()).is_err());
+
+    let mut config = AnalyzerConfig::new("tests/fixture-cycle");
+    config.break_cycles = true;
+    let mut analyzer = Analyzer::new(config).unwrap();
+    let mut broken_cycles = 0;
+    let analysis = analyzer
+        .run(|event| {
+            if let AnalyzerEvent::CycleBroken(_) = event {
+                broken_cycles += 1;
+            }
+        })
+        .unwrap();
+    assert_eq!(broken_cycles, 1);
+    assert_eq!(analysis.depend_on_graph.table.len(), 2);
+}
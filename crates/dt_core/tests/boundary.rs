@@ -0,0 +1,96 @@
+use dt_core::{
+    analyzer::{Analyzer, AnalyzerConfig},
+    boundary::{check_rules, Rules},
+    graph::depend_on_graph::DependOnGraph,
+};
+
+const RULES: &str = r#"{
+  "rules": [
+    {
+      "name": "feature-internals",
+      "from": ["features/**"],
+      "to": ["features/*/internal/**"],
+      "group": "features/*"
+    },
+    {
+      "name": "ui-independent",
+      "from": ["packages/ui/**"],
+      "to": ["features/**"]
+    },
+    {
+      "name": "features-layer",
+      "kind": "allowed",
+      "from": ["features/**"],
+      "to": ["features/**", "packages/**"]
+    },
+    {
+      "name": "no-colors",
+      "from": ["features/**"],
+      "to": ["packages/ui/**"],
+      "symbols": ["col*"]
+    }
+  ]
+}"#;
+
+#[test]
+fn violations() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-boundary")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let rules = Rules::from_json(RULES).unwrap();
+    let violations = check_rules(&analysis.depend_on_graph, &rules).unwrap();
+
+    let violations: Vec<String> = violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect();
+    assert_eq!(
+        violations,
+        vec![
+            // the internal button is imported through the barrel module of cart, and the
+            // cart page using its own internals is fine
+            "[feature-internals] features/checkout/page.js CartButton -> features/cart/index.js export CartButton -> features/cart/internal/button.js export CartButton",
+            "[feature-internals] features/checkout/page.js price -> features/cart/internal/price.js export price",
+            "[ui-independent] packages/ui/button.js CartPage -> features/cart/page.js export CartPage",
+            "[features-layer] features/checkout/page.js config -> config.js export config",
+            "[no-colors] features/checkout/page.js color -> packages/ui/theme.js export color",
+        ]
+    );
+}
+
+#[test]
+fn invalid_rules() {
+    // unknown fields are rejected
+    assert!(Rules::from_json(r#"{ "rules": [{ "name": "a", "from": [], "too": [] }] }"#).is_err());
+
+    let rules = Rules::from_json(
+        r#"{ "rules": [{ "name": "a", "from": [], "to": [], "group": "features/**" }] }"#,
+    )
+    .unwrap();
+    assert!(check_rules(&DependOnGraph::new("tests/fixture-boundary"), &rules).is_err());
+}
+
+#[test]
+fn namespace_re_export_cycle() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-boundary-cycle")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let rules = Rules::from_json(
+        r#"{
+  "rules": [
+    { "name": "no-b", "from": ["c.js"], "to": ["b.js"] },
+    { "name": "no-d", "from": ["c.js"], "to": ["d.js"] }
+  ]
+}"#,
+    )
+    .unwrap();
+    let violations = check_rules(&analysis.depend_on_graph, &rules).unwrap();
+
+    // a.js and b.js re-export each other's namespace, each symbol is walked once
+    let violations: Vec<String> = violations
+        .iter()
+        .map(|violation| violation.to_string())
+        .collect();
+    assert_eq!(
+        violations,
+        vec!["[no-b] c.js ns -> a.js export ns -> b.js export ns"]
+    );
+}
@@ -0,0 +1,100 @@
+use dt_core::{
+    analyzer::{Analyzer, AnalyzerConfig},
+    dead_code::find_dead_code,
+    tracker::TraceTarget,
+};
+
+#[test]
+fn unused_symbols() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-dead-code")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let report = find_dead_code(
+        &analysis.used_by_graph,
+        &[String::from("main.js"), String::from("pages/**/*.js")],
+    )
+    .unwrap();
+
+    assert_eq!(report.entry_modules, vec![String::from("main.js")]);
+    // card.js is only re-exported by the barrel module
+    assert_eq!(
+        report.unreachable_modules,
+        vec![
+            String::from("components/card.js"),
+            String::from("orphan.js")
+        ]
+    );
+
+    let unused_exports: Vec<(&str, &TraceTarget)> = report
+        .unused_exports
+        .iter()
+        .map(|unused_export| (unused_export.module_path.as_str(), &unused_export.symbol))
+        .collect();
+    assert_eq!(
+        unused_exports,
+        vec![
+            (
+                "components/button.js",
+                &TraceTarget::NamedExport(String::from("Button"))
+            ),
+            (
+                "components/home.js",
+                &TraceTarget::NamedExport(String::from("unusedName"))
+            ),
+            (
+                "components/index.js",
+                &TraceTarget::NamedExport(String::from("Card"))
+            ),
+            ("title.js", &TraceTarget::DefaultExport),
+        ]
+    );
+    assert_eq!(
+        report.unused_exports[0].re_exported_by,
+        vec![(
+            String::from("components/index.js"),
+            TraceTarget::NamedExport(String::from("Button"))
+        )]
+    );
+    assert!(report.unused_exports[1].re_exported_by.is_empty());
+
+    // the local variable `style` goes away with the export `Button`
+    assert_eq!(
+        report.unused_local_variables,
+        vec![(
+            String::from("components/home.js"),
+            TraceTarget::LocalVar(String::from("unusedHelper"))
+        )]
+    );
+    assert_eq!(
+        report.unmatched_entry_points,
+        vec![String::from("pages/**/*.js")]
+    );
+}
+
+#[test]
+fn package_exports() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-dead-code")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let report = find_dead_code(
+        &analysis.used_by_graph,
+        &[String::from("main.js"), String::from("components/index.js")],
+    )
+    .unwrap();
+
+    assert_eq!(report.unreachable_modules, vec![String::from("orphan.js")]);
+    let unused_exports: Vec<(&str, &TraceTarget)> = report
+        .unused_exports
+        .iter()
+        .map(|unused_export| (unused_export.module_path.as_str(), &unused_export.symbol))
+        .collect();
+    assert_eq!(
+        unused_exports,
+        vec![
+            (
+                "components/home.js",
+                &TraceTarget::NamedExport(String::from("unusedName"))
+            ),
+            ("title.js", &TraceTarget::DefaultExport),
+        ]
+    );
+    assert!(report.unmatched_entry_points.is_empty());
+}
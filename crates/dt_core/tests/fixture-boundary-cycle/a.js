@@ -0,0 +1,2 @@
+export * as ns from './b';
+export const x = 1;
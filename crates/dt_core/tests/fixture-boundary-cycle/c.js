@@ -0,0 +1,3 @@
+import { ns } from './a';
+
+export const c = () => ns;
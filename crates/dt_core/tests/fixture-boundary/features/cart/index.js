@@ -0,0 +1 @@
+export { CartButton } from './internal/button';
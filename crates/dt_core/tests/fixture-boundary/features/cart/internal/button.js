@@ -0,0 +1 @@
+export const CartButton = () => <button>cart</button>;
@@ -0,0 +1 @@
+export const price = (amount) => `$${amount}`;
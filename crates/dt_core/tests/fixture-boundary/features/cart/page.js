@@ -0,0 +1,3 @@
+import { price } from './internal/price';
+
+export const CartPage = () => <div>{price(1)}</div>;
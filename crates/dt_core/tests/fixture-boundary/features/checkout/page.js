@@ -0,0 +1,11 @@
+import { CartButton } from '../cart';
+import { price } from '../cart/internal/price';
+import { config } from '../../config';
+import { color } from '../../packages/ui/theme';
+
+export const CheckoutPage = () => (
+  <div style={{ color }}>
+    <CartButton />
+    {price(config.total)}
+  </div>
+);
@@ -0,0 +1,3 @@
+import { CartPage } from '../../features/cart/page';
+
+export const Button = () => <CartPage />;
@@ -0,0 +1,4 @@
+const style = { color: 'red' };
+
+export const Button = () => style;
+export const IconButton = () => null;
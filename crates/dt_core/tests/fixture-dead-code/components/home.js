@@ -0,0 +1,7 @@
+import { Title } from '../title';
+
+const unusedHelper = () => null;
+const helper = () => Title;
+
+export const Home = () => helper();
+export const unusedName = 'unused';
@@ -0,0 +1,3 @@
+export { Home } from './home';
+export { Button } from './button';
+export * from './card';
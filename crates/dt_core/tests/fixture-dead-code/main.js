@@ -0,0 +1,4 @@
+import { Home } from './components';
+import { IconButton } from './components/button';
+
+export const App = () => [Home, IconButton];
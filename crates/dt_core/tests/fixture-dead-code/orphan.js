@@ -0,0 +1 @@
+export const Orphan = () => null;
@@ -0,0 +1,2 @@
+export const Title = 'title';
+export default Title;
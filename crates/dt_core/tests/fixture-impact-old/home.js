@@ -0,0 +1,6 @@
+import { Title } from './title';
+import { Old } from './old';
+
+const Home = () => <Title>{Old}</Title>;
+
+export default Home;
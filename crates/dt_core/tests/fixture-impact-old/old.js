@@ -0,0 +1 @@
+export const Old = 1;
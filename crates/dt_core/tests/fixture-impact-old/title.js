@@ -0,0 +1,6 @@
+const LABELS = translate({
+  title: 'i18n.title',
+});
+
+export const Title = () => <h1>title</h1>;
+export const Gone = () => null;
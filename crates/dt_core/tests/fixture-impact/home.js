@@ -0,0 +1,5 @@
+import { Title } from './title';
+
+const Home = () => <Title />;
+
+export default Home;
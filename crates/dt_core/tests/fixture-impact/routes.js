@@ -0,0 +1,8 @@
+import Home from './home';
+
+export default {
+  'route.home': {
+    path: '/home',
+    page: Home,
+  },
+};
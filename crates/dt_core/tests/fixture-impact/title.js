@@ -0,0 +1,7 @@
+const LABELS = translate({
+  title: 'i18n.title',
+});
+
+export const Title = () => <h1>{LABELS.title}</h1>;
+
+export const Unused = () => null;
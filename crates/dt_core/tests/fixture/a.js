@@ -0,0 +1 @@
+export const A = 'A';
@@ -0,0 +1,2 @@
+import { A } from './b';
+export const C = () => A;
@@ -0,0 +1,153 @@
+use dt_core::{
+    analyzer::{Analyzer, AnalyzerConfig},
+    diff::parse_unified_diff,
+    impact::{analyze_impact, OldSide},
+    tracker::TraceTarget,
+};
+use std::collections::BTreeSet;
+
+const DIFF: &str = r#"diff --git a/title.js b/title.js
+--- a/title.js
++++ b/title.js
+@@ -5 +5 @@
+-export const Title = () => <h1>title</h1>;
++export const Title = () => <h1>{LABELS.title}</h1>;
+@@ -6,0 +7 @@
++export const Unused = () => null;
+diff --git a/old.js b/old.js
+deleted file mode 100644
+--- a/old.js
++++ /dev/null
+@@ -1 +0,0 @@
+-export const Old = 1;
+"#;
+
+#[test]
+fn changed_symbols() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-impact")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let file_diffs = parse_unified_diff(DIFF).unwrap();
+    let report = analyze_impact(&analysis, "tests/fixture-impact", &file_diffs, None).unwrap();
+
+    // the module paths are relative to the project root
+    let title = String::from("title.js");
+    let routes = String::from("routes.js");
+
+    assert_eq!(report.changed_symbols.len(), 2);
+    let changed_title = &report.changed_symbols[0];
+    assert_eq!(changed_title.module_path, title);
+    assert_eq!(
+        changed_title.symbol,
+        TraceTarget::LocalVar(String::from("Title"))
+    );
+    assert_eq!(
+        changed_title.routes,
+        BTreeSet::from([String::from("/home")])
+    );
+    assert_eq!(
+        changed_title.i18n_keys,
+        BTreeSet::from([String::from("i18n.title")])
+    );
+    assert_eq!(
+        changed_title.entry_points,
+        vec![(routes, TraceTarget::DefaultExport)]
+    );
+
+    let changed_unused = &report.changed_symbols[1];
+    assert_eq!(
+        changed_unused.symbol,
+        TraceTarget::LocalVar(String::from("Unused"))
+    );
+    assert!(changed_unused.routes.is_empty());
+    assert_eq!(
+        changed_unused.entry_points,
+        vec![(title, TraceTarget::NamedExport(String::from("Unused")))]
+    );
+
+    assert_eq!(report.routes, BTreeSet::from([String::from("/home")]));
+    assert_eq!(
+        report.i18n_keys,
+        BTreeSet::from([String::from("i18n.title")])
+    );
+    assert_eq!(report.skipped_files, vec![String::from("old.js")]);
+}
+
+// title.js and old.js of tests/fixture-impact-old are changed to the ones of
+// tests/fixture-impact, the other changes are not in the diff
+const DIFF_FROM_OLD: &str = r#"diff --git a/title.js b/title.js
+--- a/title.js
++++ b/title.js
+@@ -5,2 +5,3 @@
+-export const Title = () => <h1>title</h1>;
+-export const Gone = () => null;
++export const Title = () => <h1>{LABELS.title}</h1>;
++
++export const Unused = () => null;
+diff --git a/old.js b/old.js
+deleted file mode 100644
+--- a/old.js
++++ /dev/null
+@@ -1 +0,0 @@
+-export const Old = 1;
+"#;
+
+#[test]
+fn deleted_symbols() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-impact")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-impact-old")).unwrap();
+    let old_analysis = analyzer.run(|_| ()).unwrap();
+    let file_diffs = parse_unified_diff(DIFF_FROM_OLD).unwrap();
+    let old_side = OldSide {
+        analysis: &old_analysis,
+        repo_root: "tests/fixture-impact-old",
+    };
+    let report = analyze_impact(
+        &analysis,
+        "tests/fixture-impact",
+        &file_diffs,
+        Some(old_side),
+    )
+    .unwrap();
+
+    // Title is changed at the new side, not deleted
+    let changed_symbols: Vec<(&str, &TraceTarget, bool)> = report
+        .changed_symbols
+        .iter()
+        .map(|symbol_impact| {
+            (
+                symbol_impact.module_path.as_str(),
+                &symbol_impact.symbol,
+                symbol_impact.deleted,
+            )
+        })
+        .collect();
+    assert_eq!(
+        changed_symbols,
+        vec![
+            (
+                "title.js",
+                &TraceTarget::LocalVar(String::from("Title")),
+                false
+            ),
+            (
+                "title.js",
+                &TraceTarget::LocalVar(String::from("Unused")),
+                false
+            ),
+            (
+                "title.js",
+                &TraceTarget::LocalVar(String::from("Gone")),
+                true
+            ),
+            ("old.js", &TraceTarget::LocalVar(String::from("Old")), true),
+        ]
+    );
+    let deleted_old = &report.changed_symbols[3];
+    assert_eq!(deleted_old.routes, BTreeSet::from([String::from("/home")]));
+    assert_eq!(
+        deleted_old.entry_points,
+        vec![(String::from("routes.js"), TraceTarget::DefaultExport)]
+    );
+    assert!(report.skipped_files.is_empty());
+}
@@ -0,0 +1,72 @@
+use dt_core::{
+    analyzer::{Analyzer, AnalyzerConfig},
+    metrics::{compute_metrics, Metrics, RankBy},
+    tracker::TraceTarget,
+};
+
+#[test]
+fn hotspots() {
+    let mut analyzer = Analyzer::new(AnalyzerConfig::new("tests/fixture-impact")).unwrap();
+    let analysis = analyzer.run(|_| ()).unwrap();
+    let mut report = compute_metrics(
+        &analysis,
+        &[String::from("routes.js"), String::from("pages/*.js")],
+        None,
+    )
+    .unwrap();
+    assert_eq!(
+        report.unmatched_entry_points,
+        vec![String::from("pages/*.js")]
+    );
+
+    // the symbols form a single chain from routes.js to title.js
+    let title = report
+        .symbols
+        .iter()
+        .find(|symbol| {
+            symbol.module_path == "title.js"
+                && symbol.symbol == TraceTarget::NamedExport(String::from("Title"))
+        })
+        .unwrap();
+    assert_eq!(
+        title.metrics,
+        Metrics {
+            fan_in: 1,
+            fan_out: 1,
+            transitive_dependents: 6,
+            routes: 1,
+            depth: Some(4),
+            bridge_score: 12.0,
+        }
+    );
+
+    report.rank(RankBy::TransitiveDependents);
+    report.retain_exports();
+    report.truncate(2);
+    let hotspots: Vec<(&str, &TraceTarget)> = report
+        .symbols
+        .iter()
+        .map(|symbol| (symbol.module_path.as_str(), &symbol.symbol))
+        .collect();
+    assert_eq!(
+        hotspots,
+        vec![
+            ("title.js", &TraceTarget::NamedExport(String::from("Title"))),
+            ("home.js", &TraceTarget::DefaultExport),
+        ]
+    );
+    assert_eq!(report.modules[0].module_path, "title.js");
+    assert_eq!(
+        report.modules[0].metrics,
+        Metrics {
+            fan_in: 1,
+            fan_out: 0,
+            transitive_dependents: 2,
+            routes: 1,
+            depth: Some(2),
+            bridge_score: 0.0,
+        }
+    );
+    assert_eq!(report.modules[1].module_path, "home.js");
+    assert_eq!(report.modules[1].metrics.bridge_score, 1.0);
+}
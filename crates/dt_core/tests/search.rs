@@ -0,0 +1,100 @@
+use dt_core::{
+    graph::used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType},
+    portable::Portable,
+    search::search,
+    tracker::shared::{SharedTracker, DEFAULT_CACHE_CAPACITY},
+};
+use std::collections::{HashMap, HashSet};
+
+macro_rules! s {
+    ($s:expr) => {{
+        $s.to_string()
+    }};
+}
+
+// title.js:
+//     export const Title = () => t('i18n.title');
+// routes.js:
+//     import { Title } from './title';
+//     export default [{ path: '/home', component: Title }];
+fn create_portable() -> Portable {
+    let title = Module {
+        local_variable_table: HashMap::from([(
+            s!("Title"),
+            Some(vec![UsedBy::Itself(UsedByType::NamedExport(s!("Title")))]),
+        )]),
+        named_export_table: HashMap::from([(
+            s!("Title"),
+            Some(vec![UsedBy::Other(UsedByOther {
+                by: s!("routes.js"),
+                by_type: UsedByType::LocalVar(s!("Title")),
+            })]),
+        )]),
+        default_export: None,
+    };
+    let routes = Module {
+        local_variable_table: HashMap::from([
+            (
+                s!("Title"),
+                Some(vec![UsedBy::Itself(UsedByType::LocalVar(s!("routes")))]),
+            ),
+            (
+                s!("routes"),
+                Some(vec![UsedBy::Itself(UsedByType::DefaultExport)]),
+            ),
+        ]),
+        named_export_table: HashMap::new(),
+        default_export: None,
+    };
+    Portable::new(
+        s!("/project"),
+        HashMap::from([(s!("i18n.title"), s!("Title"))]),
+        HashMap::from([(
+            s!("i18n.title"),
+            HashMap::from([(s!("title.js"), HashSet::from([s!("Title")]))]),
+        )]),
+        HashMap::from([(
+            s!("routes.js"),
+            HashMap::from([(s!("routes"), vec![s!("/home")])]),
+        )]),
+        UsedByGraph {
+            modules: HashMap::from([(s!("title.js"), title), (s!("routes.js"), routes)]),
+        },
+    )
+}
+
+#[test]
+fn search_portable() {
+    let portable = create_portable();
+    let tracker = SharedTracker::new(DEFAULT_CACHE_CAPACITY);
+    let search_result = search(&portable, &tracker, "Tit", false).unwrap();
+    let graphs = &search_result["i18n.title"]["/home"]["Title"];
+    assert_eq!(graphs.len(), 1);
+    let steps: Vec<(&str, &str)> = graphs[0]
+        .nodes
+        .iter()
+        .map(|step| (step.module_path.as_str(), step.symbol_name.as_str()))
+        .collect();
+    assert_eq!(
+        steps,
+        vec![
+            ("title.js", "Title"),
+            ("title.js", "Title"),
+            ("routes.js", "Title"),
+            ("routes.js", "routes"),
+        ]
+    );
+    assert_eq!(graphs[0].edges, vec![(0, 1), (1, 2), (2, 3)]);
+
+    assert!(search(&portable, &tracker, "Tit", true).unwrap().is_empty());
+
+    // the graph is cached by the first search
+    let cache_size = tracker.cache_size();
+    assert!(cache_size > 0);
+    let search_result = search(&portable, &tracker, "Title", true).unwrap();
+    assert_eq!(
+        search_result["i18n.title"]["/home"]["Title"][0].edges.len(),
+        3
+    );
+    assert_eq!(tracker.cache_size(), cache_size);
+}
@@ -0,0 +1,242 @@
+use dt_core::{
+    graph::used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType},
+    portable::Portable,
+    snapshot_diff::{Dependency, Snapshot, Symbol, SymbolKind},
+};
+use std::collections::{HashMap, HashSet};
+
+macro_rules! s {
+    ($s:expr) => {{
+        $s.to_string()
+    }};
+}
+
+fn module(
+    local_variables: Vec<(&str, Vec<UsedBy>)>,
+    named_exports: Vec<(&str, Vec<UsedBy>)>,
+) -> Module {
+    let to_table = |symbols: Vec<(&str, Vec<UsedBy>)>| {
+        symbols
+            .into_iter()
+            .map(|(name, used_by)| match used_by.is_empty() {
+                true => (s!(name), None),
+                false => (s!(name), Some(used_by)),
+            })
+            .collect()
+    };
+    Module {
+        local_variable_table: to_table(local_variables),
+        named_export_table: to_table(named_exports),
+        default_export: None,
+    }
+}
+
+fn exported(name: &str) -> Vec<UsedBy> {
+    vec![UsedBy::Itself(UsedByType::NamedExport(s!(name)))]
+}
+
+fn imported_by(by: &str, names: Vec<&str>) -> Vec<UsedBy> {
+    names
+        .into_iter()
+        .map(|name| {
+            UsedBy::Other(UsedByOther {
+                by: s!(by),
+                by_type: UsedByType::LocalVar(s!(name)),
+            })
+        })
+        .collect()
+}
+
+// title.js
+//     export const Title = () => <h1>{t('i18n.title')}</h1>;
+// home.js
+//     import { Title } from './title';
+//     const Home = () => <Title />;
+//     export default Home;
+// old.js
+//     export const Old = 1;
+fn old_portable() -> Portable {
+    Portable::new(
+        s!("/old"),
+        HashMap::new(),
+        HashMap::from([(
+            s!("i18n.title"),
+            HashMap::from([(s!("title.js"), HashSet::from([s!("Title")]))]),
+        )]),
+        HashMap::from([(
+            s!("home.js"),
+            HashMap::from([(s!("Home"), vec![s!("/home")])]),
+        )]),
+        UsedByGraph {
+            modules: HashMap::from([
+                (
+                    s!("title.js"),
+                    module(
+                        vec![("Title", exported("Title"))],
+                        vec![("Title", imported_by("home.js", vec!["Title"]))],
+                    ),
+                ),
+                (
+                    s!("home.js"),
+                    module(
+                        vec![
+                            ("Home", vec![UsedBy::Itself(UsedByType::DefaultExport)]),
+                            (
+                                "Title",
+                                vec![UsedBy::Itself(UsedByType::LocalVar(s!("Home")))],
+                            ),
+                        ],
+                        vec![],
+                    ),
+                ),
+                (
+                    s!("old.js"),
+                    module(vec![("Old", exported("Old"))], vec![("Old", vec![])]),
+                ),
+            ]),
+        },
+    )
+}
+
+// title.js
+//     export const Title = () => <h1>{t('i18n.title')}</h1>;
+//     export const Subtitle = () => <h2>{t('i18n.subtitle')}</h2>;
+// home.js
+//     import { Title, Subtitle } from './title';
+//     const Home = () => <><Title /><Subtitle /></>;
+//     export default Home;
+// new.js
+//     export const New = 1;
+fn new_portable() -> Portable {
+    Portable::new(
+        s!("/new"),
+        HashMap::new(),
+        HashMap::from([
+            (
+                s!("i18n.title"),
+                HashMap::from([(s!("title.js"), HashSet::from([s!("Title")]))]),
+            ),
+            (
+                s!("i18n.subtitle"),
+                HashMap::from([(s!("title.js"), HashSet::from([s!("Subtitle")]))]),
+            ),
+        ]),
+        HashMap::from([(
+            s!("home.js"),
+            HashMap::from([(s!("Home"), vec![s!("/home"), s!("/index")])]),
+        )]),
+        UsedByGraph {
+            modules: HashMap::from([
+                (
+                    s!("title.js"),
+                    module(
+                        vec![
+                            ("Title", exported("Title")),
+                            ("Subtitle", exported("Subtitle")),
+                        ],
+                        vec![
+                            ("Title", imported_by("home.js", vec!["Title"])),
+                            ("Subtitle", imported_by("home.js", vec!["Subtitle"])),
+                        ],
+                    ),
+                ),
+                (
+                    s!("home.js"),
+                    module(
+                        vec![
+                            ("Home", vec![UsedBy::Itself(UsedByType::DefaultExport)]),
+                            (
+                                "Title",
+                                vec![UsedBy::Itself(UsedByType::LocalVar(s!("Home")))],
+                            ),
+                            (
+                                "Subtitle",
+                                vec![UsedBy::Itself(UsedByType::LocalVar(s!("Home")))],
+                            ),
+                        ],
+                        vec![],
+                    ),
+                ),
+                (
+                    s!("new.js"),
+                    module(vec![("New", exported("New"))], vec![("New", vec![])]),
+                ),
+            ]),
+        },
+    )
+}
+
+#[test]
+fn portable_snapshots() {
+    let old = Snapshot::from_portable(&old_portable());
+    // the default export is found from the local variable exported as default
+    assert!(old
+        .symbols
+        .contains(&Symbol::new("home.js", SymbolKind::DefaultExport, "")));
+
+    let diff = old.diff(&Snapshot::from_portable(&new_portable()));
+    assert_eq!(diff.added_modules, vec![s!("new.js")]);
+    assert_eq!(diff.removed_modules, vec![s!("old.js")]);
+    // the symbols of new.js and old.js are not listed
+    assert_eq!(
+        diff.added_symbols,
+        vec![
+            Symbol::new("home.js", SymbolKind::LocalVariable, "Subtitle"),
+            Symbol::new("title.js", SymbolKind::LocalVariable, "Subtitle"),
+            Symbol::new("title.js", SymbolKind::NamedExport, "Subtitle"),
+        ]
+    );
+    assert!(diff.removed_symbols.is_empty());
+    assert_eq!(diff.added_dependencies.len(), 4);
+    assert_eq!(
+        diff.removed_dependencies,
+        vec![Dependency {
+            from: Symbol::new("old.js", SymbolKind::NamedExport, "Old"),
+            to: Symbol::new("old.js", SymbolKind::LocalVariable, "Old"),
+        }]
+    );
+
+    assert_eq!(
+        diff.to_string(),
+        r#"modules (+1 -1)
+  + new.js
+  - old.js
+
+symbols (+3 -0)
+  + home.js Subtitle
+  + title.js Subtitle
+  + title.js export Subtitle
+
+dependencies (+4 -1)
+  + home.js Home -> home.js Subtitle
+  + home.js Subtitle -> title.js export Subtitle
+  + new.js export New -> new.js New
+  + title.js export Subtitle -> title.js Subtitle
+  - old.js export Old -> old.js Old
+
+routes (+1 -0)
+  + /index: home.js Home
+
+i18n keys (+1 -0)
+  + i18n.subtitle: title.js Subtitle
+"#
+    );
+
+    let serialized = serde_json::to_value(&diff).unwrap();
+    assert_eq!(
+        serialized["added_symbols"][2],
+        serde_json::json!({
+            "module_path": "title.js",
+            "kind": "named_export",
+            "name": "Subtitle",
+        })
+    );
+}
+
+#[test]
+fn same_snapshots() {
+    let diff =
+        Snapshot::from_portable(&old_portable()).diff(&Snapshot::from_portable(&old_portable()));
+    assert!(diff.is_empty());
+    assert_eq!(diff.to_string(), "no changes\n");
+}
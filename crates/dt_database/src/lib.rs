@@ -1,7 +1,7 @@
 pub mod models;
 
 use models::{
-    Model, Module, Project, Route, RouteUsage, Symbol, SymbolDependency, Translation,
+    Model, Module, ModuleHash, Project, Route, RouteUsage, Symbol, SymbolDependency, Translation,
     TranslationUsage,
 };
 use rusqlite::Connection;
@@ -12,6 +12,7 @@ pub trait Database {
     where
         Self: Sized;
     fn create_tables(&self) -> anyhow::Result<()>;
+    fn create_indexes(&self) -> anyhow::Result<()>;
 }
 
 #[derive(Debug)]
@@ -26,6 +27,15 @@ impl SqliteDb {
 
         Ok(())
     }
+
+    fn create_indexes_if_not_exist<M: Model>(&self) -> anyhow::Result<()> {
+        for index in M::indexes() {
+            let sql = format!("CREATE INDEX if not exists {}", index);
+            self.conn.execute(&sql, ())?;
+        }
+
+        Ok(())
+    }
 }
 
 impl Database for SqliteDb {
@@ -38,12 +48,26 @@ impl Database for SqliteDb {
     fn create_tables(&self) -> anyhow::Result<()> {
         self.create_table_if_not_exists(&Project::table())?;
         self.create_table_if_not_exists(&Module::table())?;
+        self.create_table_if_not_exists(&ModuleHash::table())?;
         self.create_table_if_not_exists(&Symbol::table())?;
         self.create_table_if_not_exists(&SymbolDependency::table())?;
         self.create_table_if_not_exists(&Translation::table())?;
         self.create_table_if_not_exists(&TranslationUsage::table())?;
         self.create_table_if_not_exists(&Route::table())?;
         self.create_table_if_not_exists(&RouteUsage::table())?;
+        self.create_indexes()?;
+
+        Ok(())
+    }
+
+    // The databases created before the indexes get them when they're opened again.
+    fn create_indexes(&self) -> anyhow::Result<()> {
+        self.create_indexes_if_not_exist::<Module>()?;
+        self.create_indexes_if_not_exist::<Symbol>()?;
+        self.create_indexes_if_not_exist::<SymbolDependency>()?;
+        self.create_indexes_if_not_exist::<Translation>()?;
+        self.create_indexes_if_not_exist::<TranslationUsage>()?;
+        self.create_indexes_if_not_exist::<RouteUsage>()?;
 
         Ok(())
     }
@@ -1,7 +1,48 @@
 use rusqlite::{params, Connection, Row, ToSql};
+use std::collections::HashMap;
 
 pub trait Model {
     fn table() -> String;
+
+    // the columns looked up by the queries, e.g. "symbol_dependency_depend_on ON
+    // symbol_dependency (depend_on_symbol_id)"
+    fn indexes() -> Vec<String> {
+        vec![]
+    }
+}
+
+// The symbols reachable from the seeded ones in the `reachable` table, following the
+// edges from `from_column` to `to_column` of `symbol_dependency`. `UNION` drops the
+// symbols already found, so it ends at cycles. With `max_depth`, the symbols are found
+// with their depth and the ones `max_depth` away are not followed, the depth is written
+// into the query since it's a constant of the recursion.
+fn reachable(from_column: &str, to_column: &str, max_depth: Option<usize>) -> String {
+    match max_depth {
+        None => format!(
+            "
+            reachable(id) AS (
+                SELECT id FROM seed
+                UNION
+                SELECT sd.{to_column}
+                FROM symbol_dependency sd
+                JOIN reachable r ON sd.{from_column} = r.id
+            )
+            "
+        ),
+        Some(max_depth) => format!(
+            "
+            reachable_at(id, depth) AS (
+                SELECT id, 0 FROM seed
+                UNION
+                SELECT sd.{to_column}, r.depth + 1
+                FROM symbol_dependency sd
+                JOIN reachable_at r ON sd.{from_column} = r.id
+                WHERE r.depth < {max_depth}
+            ),
+            reachable(id) AS (SELECT DISTINCT id FROM reachable_at)
+            "
+        ),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -100,6 +141,144 @@ impl Project {
     pub fn add_route(&self, conn: &Connection, path: &str) -> anyhow::Result<Route> {
         Route::create(conn, self, path)
     }
+
+    pub fn get_modules(&self, conn: &Connection) -> anyhow::Result<Vec<Module>> {
+        let modules: Vec<Module> = conn
+            .prepare("SELECT * FROM module WHERE project_id = ?1")?
+            .query_map(params![self.id], Module::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(modules)
+    }
+
+    pub fn get_symbols(&self, conn: &Connection) -> anyhow::Result<Vec<Symbol>> {
+        let symbols: Vec<Symbol> = conn
+            .prepare(
+                "
+                SELECT s.*
+                FROM symbol s
+                JOIN module m ON s.module_id = m.id
+                WHERE m.project_id = ?1;
+                ",
+            )?
+            .query_map(params![self.id], Symbol::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(symbols)
+    }
+
+    pub fn get_symbol_dependencies(
+        &self,
+        conn: &Connection,
+    ) -> anyhow::Result<Vec<SymbolDependency>> {
+        let symbol_dependencies: Vec<SymbolDependency> = conn
+            .prepare(
+                "
+                SELECT sd.*
+                FROM symbol_dependency sd
+                JOIN symbol s ON sd.symbol_id = s.id
+                JOIN module m ON s.module_id = m.id
+                WHERE m.project_id = ?1;
+                ",
+            )?
+            .query_map(params![self.id], SymbolDependency::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(symbol_dependencies)
+    }
+
+    pub fn get_translations(&self, conn: &Connection) -> anyhow::Result<Vec<Translation>> {
+        let translations: Vec<Translation> = conn
+            .prepare("SELECT * FROM translation WHERE project_id = ?1")?
+            .query_map(params![self.id], Translation::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(translations)
+    }
+
+    pub fn get_translation_usages(
+        &self,
+        conn: &Connection,
+    ) -> anyhow::Result<Vec<TranslationUsage>> {
+        let translation_usages: Vec<TranslationUsage> = conn
+            .prepare(
+                "
+                SELECT tu.*
+                FROM translation_usage tu
+                JOIN translation t ON tu.translation_id = t.id
+                WHERE t.project_id = ?1;
+                ",
+            )?
+            .query_map(params![self.id], TranslationUsage::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(translation_usages)
+    }
+
+    pub fn get_routes(&self, conn: &Connection) -> anyhow::Result<Vec<Route>> {
+        let routes: Vec<Route> = conn
+            .prepare("SELECT * FROM route WHERE project_id = ?1")?
+            .query_map(params![self.id], Route::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(routes)
+    }
+
+    pub fn get_route_usages(&self, conn: &Connection) -> anyhow::Result<Vec<RouteUsage>> {
+        let route_usages: Vec<RouteUsage> = conn
+            .prepare(
+                "
+                SELECT ru.*
+                FROM route_usage ru
+                JOIN route r ON ru.route_id = r.id
+                WHERE r.project_id = ?1;
+                ",
+            )?
+            .query_map(params![self.id], RouteUsage::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(route_usages)
+    }
+
+    /// The content hash of the modules emitted with one, see `ModuleHash`.
+    pub fn get_module_hashes(&self, conn: &Connection) -> anyhow::Result<HashMap<String, String>> {
+        let module_hashes = conn
+            .prepare(
+                "
+                SELECT m.path, mh.content_hash
+                FROM module_hash mh
+                JOIN module m ON mh.module_id = m.id
+                WHERE m.project_id = ?1;
+                ",
+            )?
+            .query_map(params![self.id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<HashMap<String, String>>>()?;
+        Ok(module_hashes)
+    }
+
+    /// Delete what's left after the modules are removed or emitted again: the symbols
+    /// of the modules without a content hash, i.e. not parsed, that no symbol depends on,
+    /// the modules without symbols, and the routes not used by any symbol. Foreign keys
+    /// are not enforced so the rows are deleted one table by one table.
+    pub fn delete_unreferenced(&self, conn: &Connection) -> anyhow::Result<()> {
+        for sql in [
+            "
+            DELETE FROM symbol
+            WHERE module_id IN (
+                SELECT id FROM module
+                WHERE project_id = ?1 AND id NOT IN (SELECT module_id FROM module_hash)
+            )
+            AND id NOT IN (SELECT depend_on_symbol_id FROM symbol_dependency)
+            ",
+            "
+            DELETE FROM module
+            WHERE project_id = ?1
+            AND id NOT IN (SELECT module_id FROM module_hash)
+            AND id NOT IN (SELECT module_id FROM symbol)
+            ",
+            "
+            DELETE FROM route
+            WHERE project_id = ?1
+            AND id NOT IN (SELECT route_id FROM route_usage)
+            ",
+        ] {
+            conn.execute(sql, params![self.id])?;
+        }
+        Ok(())
+    }
 }
 
 #[derive(Debug)]
@@ -120,6 +299,10 @@ impl Model for Module {
         "
         .to_string()
     }
+
+    fn indexes() -> Vec<String> {
+        vec!["module_project_path ON module (project_id, path)".to_string()]
+    }
 }
 
 impl Module {
@@ -194,6 +377,14 @@ impl Module {
         }
     }
 
+    pub fn get_symbols(&self, conn: &Connection) -> anyhow::Result<Vec<Symbol>> {
+        let symbols: Vec<Symbol> = conn
+            .prepare("SELECT * FROM symbol WHERE module_id = ?1")?
+            .query_map(params![self.id], Symbol::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(symbols)
+    }
+
     pub fn get_named_export_symbols(&self, conn: &Connection) -> anyhow::Result<Vec<Symbol>> {
         let named_export_symbols: Vec<Symbol> = conn
             .prepare("SELECT * FROM symbol WHERE (module_id, variant) = (?1, ?2)")?
@@ -205,6 +396,93 @@ impl Module {
             .collect();
         Ok(named_export_symbols)
     }
+
+    pub fn set_content_hash(&self, conn: &Connection, content_hash: &str) -> anyhow::Result<()> {
+        conn.execute(
+            "INSERT OR REPLACE INTO module_hash (module_id, content_hash) VALUES (?1, ?2)",
+            params![self.id, content_hash],
+        )?;
+        Ok(())
+    }
+
+    /// Delete the edges from the symbols of the module, their translation and route
+    /// usages, and the content hash, so the module can be emitted again. The edges to
+    /// its symbols are kept.
+    pub fn clear(&self, conn: &Connection) -> anyhow::Result<()> {
+        let symbol_ids = "SELECT id FROM symbol WHERE module_id = ?1";
+        for sql in [
+            format!(
+                "DELETE FROM symbol_dependency WHERE symbol_id IN ({})",
+                symbol_ids
+            ),
+            format!(
+                "DELETE FROM translation_usage WHERE symbol_id IN ({})",
+                symbol_ids
+            ),
+            format!(
+                "DELETE FROM route_usage WHERE symbol_id IN ({})",
+                symbol_ids
+            ),
+            "DELETE FROM module_hash WHERE module_id = ?1".to_string(),
+        ] {
+            conn.execute(&sql, params![self.id])?;
+        }
+        Ok(())
+    }
+
+    /// The edges to the symbols of the module as (symbol, depend-on symbol).
+    pub fn get_used_by_edges(&self, conn: &Connection) -> anyhow::Result<Vec<(Symbol, Symbol)>> {
+        let edges = conn
+            .prepare(
+                "
+                SELECT f.*, t.*
+                FROM symbol_dependency sd
+                JOIN symbol f ON f.id = sd.symbol_id
+                JOIN symbol t ON t.id = sd.depend_on_symbol_id
+                WHERE t.module_id = ?1
+                ORDER BY sd.id;
+                ",
+            )?
+            .query_map(params![self.id], |row| {
+                Ok((Symbol::from_row_at(row, 0)?, Symbol::from_row_at(row, 4)?))
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(edges)
+    }
+
+    /// Delete the symbols of the module and the edges to them.
+    pub fn delete_symbols(&self, conn: &Connection) -> anyhow::Result<()> {
+        for sql in [
+            "
+            DELETE FROM symbol_dependency
+            WHERE depend_on_symbol_id IN (SELECT id FROM symbol WHERE module_id = ?1)
+            ",
+            "DELETE FROM symbol WHERE module_id = ?1",
+        ] {
+            conn.execute(sql, params![self.id])?;
+        }
+        Ok(())
+    }
+}
+
+// The content hash of a parsed module, the module is emitted again only if it's changed.
+// The modules created for the imports only don't have one.
+#[derive(Debug)]
+pub struct ModuleHash {
+    pub module_id: usize,
+    pub content_hash: String,
+}
+
+impl Model for ModuleHash {
+    fn table() -> String {
+        "
+        module_hash (
+            module_id    INTEGER PRIMARY KEY REFERENCES module(id) ON DELETE CASCADE,
+            content_hash TEXT NOT NULL
+        )
+        "
+        .to_string()
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -255,15 +533,24 @@ impl Model for Symbol {
         "
         .to_string()
     }
+
+    fn indexes() -> Vec<String> {
+        vec!["symbol_module_variant_name ON symbol (module_id, variant, name)".to_string()]
+    }
 }
 
 impl Symbol {
     pub fn from_row(row: &Row) -> rusqlite::Result<Self> {
+        Self::from_row_at(row, 0)
+    }
+
+    // the columns of the symbol start at `offset` of the joined row
+    fn from_row_at(row: &Row, offset: usize) -> rusqlite::Result<Self> {
         Ok(Self {
-            id: row.get(0)?,
-            module_id: row.get(1)?,
-            variant: SymbolVariant::from(row.get::<_, usize>(2)?),
-            name: row.get(3)?,
+            id: row.get(offset)?,
+            module_id: row.get(offset + 1)?,
+            variant: SymbolVariant::from(row.get::<_, usize>(offset + 2)?),
+            name: row.get(offset + 3)?,
         })
     }
 
@@ -316,6 +603,51 @@ impl Symbol {
         Ok(used_by)
     }
 
+    pub fn get_depend_on(&self, conn: &Connection) -> anyhow::Result<Vec<Symbol>> {
+        let depend_on: Vec<Symbol> = conn
+            .prepare(
+                "
+                SELECT s.*
+                FROM symbol s
+                JOIN symbol_dependency sd ON s.id = sd.depend_on_symbol_id
+                WHERE sd.symbol_id = ?1;
+                ",
+            )?
+            .query_map(params![self.id], Symbol::from_row)?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(depend_on)
+    }
+
+    /// The edges between the symbols using this one, directly or not, in one query. The
+    /// edges of the symbols up to `max_depth` away are included, if it's given.
+    pub fn get_used_by_recursive(
+        &self,
+        conn: &Connection,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Vec<SymbolEdge>> {
+        SymbolEdge::query(conn, self, "depend_on_symbol_id", "symbol_id", max_depth)
+    }
+
+    /// The edges between the symbols this one depends on, directly or not, in one query.
+    /// See `get_used_by_recursive()` for `max_depth`.
+    pub fn get_depend_on_recursive(
+        &self,
+        conn: &Connection,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Vec<SymbolEdge>> {
+        SymbolEdge::query(conn, self, "symbol_id", "depend_on_symbol_id", max_depth)
+    }
+
+    /// The routes using this symbol and the ones using it, directly or not, in one query.
+    /// The symbols up to `max_depth` away are included, if it's given.
+    pub fn get_used_by_routes_recursive(
+        &self,
+        conn: &Connection,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Vec<SymbolRoute>> {
+        SymbolRoute::query(conn, "SELECT ?1", self.id, max_depth)
+    }
+
     pub fn get_used_by_routes(&self, conn: &Connection) -> anyhow::Result<Vec<Route>> {
         let used_by_routes: Vec<Route> = conn
             .prepare(
@@ -352,6 +684,13 @@ impl Model for SymbolDependency {
         "
         .to_string()
     }
+
+    fn indexes() -> Vec<String> {
+        vec![
+            "symbol_dependency_symbol ON symbol_dependency (symbol_id)".to_string(),
+            "symbol_dependency_depend_on ON symbol_dependency (depend_on_symbol_id)".to_string(),
+        ]
+    }
 }
 
 impl SymbolDependency {
@@ -382,6 +721,55 @@ impl SymbolDependency {
     }
 }
 
+// An edge of the recursive queries, `to` uses `from`, or `from` depends on `to` for the
+// depend-on direction, with the paths of their modules.
+#[derive(Debug)]
+pub struct SymbolEdge {
+    pub from: Symbol,
+    pub from_module_path: String,
+    pub to: Symbol,
+    pub to_module_path: String,
+}
+
+impl SymbolEdge {
+    fn query(
+        conn: &Connection,
+        symbol: &Symbol,
+        from_column: &str,
+        to_column: &str,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Vec<Self>> {
+        let recursive = reachable(from_column, to_column, max_depth);
+        let sql = format!(
+            "
+            WITH RECURSIVE
+                seed(id) AS (SELECT ?1),
+                {recursive}
+            SELECT f.*, fm.path, t.*, tm.path
+            FROM reachable r
+            JOIN symbol_dependency sd ON sd.{from_column} = r.id
+            JOIN symbol f ON f.id = sd.{from_column}
+            JOIN module fm ON fm.id = f.module_id
+            JOIN symbol t ON t.id = sd.{to_column}
+            JOIN module tm ON tm.id = t.module_id
+            ORDER BY sd.id;
+            "
+        );
+        let edges: Vec<Self> = conn
+            .prepare(&sql)?
+            .query_map(params![symbol.id], |row| {
+                Ok(Self {
+                    from: Symbol::from_row_at(row, 0)?,
+                    from_module_path: row.get(4)?,
+                    to: Symbol::from_row_at(row, 5)?,
+                    to_module_path: row.get(9)?,
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(edges)
+    }
+}
+
 #[derive(Debug)]
 pub struct Translation {
     pub id: usize,
@@ -402,6 +790,10 @@ impl Model for Translation {
         "
         .to_string()
     }
+
+    fn indexes() -> Vec<String> {
+        vec!["translation_project_key ON translation (project_id, key)".to_string()]
+    }
 }
 
 impl Translation {
@@ -442,6 +834,25 @@ impl Translation {
         Ok(translation)
     }
 
+    pub fn update_value(&self, conn: &Connection, value: &str) -> anyhow::Result<()> {
+        conn.execute(
+            "UPDATE translation SET value = ?2 WHERE id = ?1",
+            params![self.id, value],
+        )?;
+        Ok(())
+    }
+
+    /// Delete the translation and its usages.
+    pub fn delete(&self, conn: &Connection) -> anyhow::Result<()> {
+        for sql in [
+            "DELETE FROM translation_usage WHERE translation_id = ?1",
+            "DELETE FROM translation WHERE id = ?1",
+        ] {
+            conn.execute(sql, params![self.id])?;
+        }
+        Ok(())
+    }
+
     pub fn search_value_exact_match(
         conn: &Connection,
         project: &Project,
@@ -490,6 +901,69 @@ impl Translation {
             .collect();
         Ok(used_by)
     }
+
+    /// The routes using the symbols which use the translation, directly or not, in one
+    /// query.
+    pub fn get_used_by_routes_recursive(
+        &self,
+        conn: &Connection,
+    ) -> anyhow::Result<Vec<SymbolRoute>> {
+        SymbolRoute::query(
+            conn,
+            "SELECT symbol_id FROM translation_usage WHERE translation_id = ?1",
+            self.id,
+            None,
+        )
+    }
+}
+
+// A route using a symbol, with the path of its module.
+#[derive(Debug)]
+pub struct SymbolRoute {
+    pub module_path: String,
+    pub symbol: Symbol,
+    pub route: Route,
+}
+
+impl SymbolRoute {
+    // the routes of the symbols using the seeded ones, including themselves
+    fn query(
+        conn: &Connection,
+        seed: &str,
+        id: usize,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Vec<Self>> {
+        let recursive = reachable("depend_on_symbol_id", "symbol_id", max_depth);
+        let sql = format!(
+            "
+            WITH RECURSIVE
+                seed(id) AS ({seed}),
+                {recursive}
+            SELECT m.path, s.*, rt.*
+            FROM reachable r
+            JOIN route_usage ru ON ru.symbol_id = r.id
+            JOIN route rt ON rt.id = ru.route_id
+            JOIN symbol s ON s.id = r.id
+            JOIN module m ON m.id = s.module_id
+            ORDER BY ru.id;
+            "
+        );
+        let symbol_routes: Vec<Self> = conn
+            .prepare(&sql)?
+            .query_map(params![id], |row| {
+                Ok(Self {
+                    module_path: row.get(0)?,
+                    symbol: Symbol::from_row_at(row, 1)?,
+                    route: Route {
+                        id: row.get(5)?,
+                        project_id: row.get(6)?,
+                        path: row.get(7)?,
+                    },
+                })
+            })?
+            .collect::<rusqlite::Result<Vec<_>>>()?;
+        Ok(symbol_routes)
+    }
 }
 
 // Join Table
@@ -511,6 +985,13 @@ impl Model for TranslationUsage {
         "
         .to_string()
     }
+
+    fn indexes() -> Vec<String> {
+        vec![
+            "translation_usage_translation ON translation_usage (translation_id)".to_string(),
+            "translation_usage_symbol ON translation_usage (symbol_id)".to_string(),
+        ]
+    }
 }
 
 impl TranslationUsage {
@@ -604,6 +1085,10 @@ impl Model for RouteUsage {
         "
         .to_string()
     }
+
+    fn indexes() -> Vec<String> {
+        vec!["route_usage_symbol ON route_usage (symbol_id)".to_string()]
+    }
 }
 
 impl RouteUsage {
@@ -0,0 +1,100 @@
+use anyhow::{bail, Context};
+use std::{
+    path::{Path, PathBuf},
+    process::{self, Command},
+    sync::atomic::{AtomicUsize, Ordering},
+};
+
+fn run_git(repo_path: &str, args: &[&str]) -> anyhow::Result<String> {
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(args)
+        .output()
+        .context(format!("run git {}", args.join(" ")))?;
+    if !output.status.success() {
+        bail!(
+            "git {} failed: {}",
+            args.join(" "),
+            String::from_utf8_lossy(&output.stderr).trim()
+        );
+    }
+    String::from_utf8(output.stdout).context(format!("read output of git {}", args.join(" ")))
+}
+
+// The paths in the diff are relative to it.
+pub fn get_toplevel(repo_path: &str) -> anyhow::Result<String> {
+    Ok(run_git(repo_path, &["rev-parse", "--show-toplevel"])?
+        .trim_end()
+        .to_string())
+}
+
+// Diff `from` with `to`, or with the working tree if `to` is not given. Only the local
+// repository is read, nothing is fetched.
+pub fn diff_revisions(repo_path: &str, from: &str, to: Option<&str>) -> anyhow::Result<String> {
+    let mut args = vec![
+        "diff",
+        "--no-color",
+        "--no-ext-diff",
+        "--unified=0",
+        "--find-renames",
+        // don't let `diff.noprefix` or `diff.mnemonicPrefix` change the paths
+        "--src-prefix=a/",
+        "--dst-prefix=b/",
+        from,
+    ];
+    if let Some(to) = to {
+        args.push(to);
+    }
+    args.push("--");
+    run_git(repo_path, &args)
+}
+
+static WORKTREE_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+// A detached checkout of a revision in a temporary directory, so the revision can be
+// analyzed without touching the working tree. It's removed when dropped.
+#[derive(Debug)]
+pub struct Worktree {
+    repo_path: String,
+    path: PathBuf,
+}
+
+impl Worktree {
+    pub fn add(repo_path: &str, revision: &str) -> anyhow::Result<Self> {
+        let path = std::env::temp_dir().join(format!(
+            "dt-worktree-{}-{}",
+            process::id(),
+            WORKTREE_COUNT.fetch_add(1, Ordering::Relaxed)
+        ));
+        let path_str = path
+            .to_str()
+            .context(format!("get worktree path, path_buf: {:?}", path))?;
+        run_git(
+            repo_path,
+            &["worktree", "add", "--quiet", "--detach", path_str, revision],
+        )
+        .context(format!("check out {} to a worktree", revision))?;
+        Ok(Self {
+            repo_path: repo_path.to_string(),
+            path,
+        })
+    }
+
+    // the root of the repository at the revision
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for Worktree {
+    fn drop(&mut self) {
+        let removed = self
+            .path
+            .to_str()
+            .map(|path| run_git(&self.repo_path, &["worktree", "remove", "--force", path]));
+        if let Some(Err(e)) = removed {
+            eprintln!("failed to remove worktree {}: {:?}", self.path.display(), e);
+        }
+    }
+}
@@ -0,0 +1,219 @@
+pub mod git;
+
+use anyhow::{bail, Context};
+
+// The changes of one file. The line numbers are 1-based and belong to the new version
+// of the file, since the changed symbols are looked up from it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct FileDiff {
+    // None if the file is added
+    pub old_path: Option<String>,
+
+    // None if the file is deleted
+    pub new_path: Option<String>,
+
+    // inclusive ranges of the added lines, modified lines are added lines as well
+    pub added_lines: Vec<(usize, usize)>,
+
+    // the removed lines are right before these lines, e.g. 5 means the removed lines
+    // were between line 4 and line 5
+    pub removed_before_lines: Vec<usize>,
+
+    // inclusive ranges of the removed lines in the line numbers of the old version, to
+    // look up the deleted symbols from it
+    pub removed_lines: Vec<(usize, usize)>,
+}
+
+impl FileDiff {
+    fn new(old_path: Option<String>, new_path: Option<String>) -> Self {
+        Self {
+            old_path,
+            new_path,
+            added_lines: vec![],
+            removed_before_lines: vec![],
+            removed_lines: vec![],
+        }
+    }
+
+    fn add_line(&mut self, line: usize) {
+        match self.added_lines.last_mut() {
+            Some((_, end)) if *end + 1 == line => *end = line,
+            _ => self.added_lines.push((line, line)),
+        }
+    }
+
+    fn remove_line(&mut self, before_line: usize, old_line: usize) {
+        if self.removed_before_lines.last() != Some(&before_line) {
+            self.removed_before_lines.push(before_line);
+        }
+        match self.removed_lines.last_mut() {
+            Some((_, end)) if *end + 1 == old_line => *end = old_line,
+            _ => self.removed_lines.push((old_line, old_line)),
+        }
+    }
+
+    // Check if the lines from `start_line` to `end_line` (inclusive) are changed. The
+    // removed lines only count when they were inside the range, so removing a whole
+    // declaration doesn't change its neighbours.
+    pub fn is_changed(&self, start_line: usize, end_line: usize) -> bool {
+        self.added_lines
+            .iter()
+            .any(|(start, end)| *start <= end_line && start_line <= *end)
+            || self
+                .removed_before_lines
+                .iter()
+                .any(|line| start_line < *line && *line <= end_line)
+    }
+
+    // Check if any of the lines from `start_line` to `end_line` (inclusive) of the old
+    // version are removed.
+    pub fn is_removed(&self, start_line: usize, end_line: usize) -> bool {
+        self.removed_lines
+            .iter()
+            .any(|(start, end)| *start <= end_line && start_line <= *end)
+    }
+}
+
+// "a/src/foo.js\t2024-01-01 00:00:00" -> Some("src/foo.js")
+fn parse_path(path: &str, prefix: &str) -> Option<String> {
+    let path = path.split('\t').next().unwrap_or(path).trim_end();
+    match path {
+        "/dev/null" => None,
+        _ => Some(path.strip_prefix(prefix).unwrap_or(path).to_string()),
+    }
+}
+
+// "-12,3" -> (12, 3), "-12" -> (12, 1)
+fn parse_range(range: &str) -> anyhow::Result<(usize, usize)> {
+    let range = &range[1..];
+    let (start, count) = match range.split_once(',') {
+        Some((start, count)) => (start, count.parse()?),
+        None => (range, 1),
+    };
+    Ok((start.parse()?, count))
+}
+
+// "@@ -12,3 +12,4 @@ function foo() {" -> ((12, 3), (12, 4))
+fn parse_hunk_header(line: &str) -> anyhow::Result<((usize, usize), (usize, usize))> {
+    let mut parts = line.split(' ').skip(1);
+    match (parts.next(), parts.next()) {
+        (Some(old_range), Some(new_range))
+            if old_range.starts_with('-') && new_range.starts_with('+') =>
+        {
+            Ok((parse_range(old_range)?, parse_range(new_range)?))
+        }
+        _ => bail!("invalid hunk header {}", line),
+    }
+}
+
+struct Hunk {
+    old_remaining: usize,
+    new_remaining: usize,
+    // the line number of the next context or added line
+    cursor: usize,
+    // the line number of the next context or removed line of the old version
+    old_cursor: usize,
+}
+
+// Parse the output of `git diff` or `diff -u`. The paths are the ones in the diff, so
+// they are relative to the repository root for git. Files changed without content
+// changes, e.g. pure renames or mode changes, are returned without changed lines.
+pub fn parse_unified_diff(diff: &str) -> anyhow::Result<Vec<FileDiff>> {
+    let mut file_diffs: Vec<FileDiff> = vec![];
+    // whether the headers of the current file are from `diff --git`, then `---` and
+    // `+++` belong to it instead of starting a new file
+    let mut in_git_header = false;
+    let mut hunk: Option<Hunk> = None;
+
+    for (i, line) in diff.lines().enumerate() {
+        if let Some(h) = hunk.as_mut() {
+            if h.old_remaining > 0 || h.new_remaining > 0 {
+                let file_diff = file_diffs.last_mut().unwrap();
+                match line.chars().next() {
+                    Some('+') => {
+                        file_diff.add_line(h.cursor);
+                        h.cursor += 1;
+                        h.new_remaining = h.new_remaining.saturating_sub(1);
+                    }
+                    Some('-') => {
+                        file_diff.remove_line(h.cursor, h.old_cursor);
+                        h.old_cursor += 1;
+                        h.old_remaining = h.old_remaining.saturating_sub(1);
+                    }
+                    // some tools strip the trailing space of empty context lines
+                    Some(' ') | None => {
+                        h.cursor += 1;
+                        h.old_cursor += 1;
+                        h.old_remaining = h.old_remaining.saturating_sub(1);
+                        h.new_remaining = h.new_remaining.saturating_sub(1);
+                    }
+                    // "\ No newline at end of file"
+                    Some('\\') => (),
+                    _ => bail!("unexpected line {} in hunk: {}", i + 1, line),
+                }
+                continue;
+            }
+            hunk = None;
+        }
+
+        if let Some(paths) = line.strip_prefix("diff --git ") {
+            // the paths are overridden by `---` and `+++` if the content is changed
+            let (old_path, new_path) = match paths.split_once(" b/") {
+                Some((old_path, new_path)) => {
+                    (parse_path(old_path, "a/"), Some(new_path.to_string()))
+                }
+                None => (None, None),
+            };
+            file_diffs.push(FileDiff::new(old_path, new_path));
+            in_git_header = true;
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            if let Some(file_diff) = file_diffs.last_mut() {
+                file_diff.old_path = Some(path.to_string());
+            }
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            if let Some(file_diff) = file_diffs.last_mut() {
+                file_diff.new_path = Some(path.to_string());
+            }
+        } else if line.starts_with("new file mode") {
+            if let Some(file_diff) = file_diffs.last_mut() {
+                file_diff.old_path = None;
+            }
+        } else if line.starts_with("deleted file mode") {
+            if let Some(file_diff) = file_diffs.last_mut() {
+                file_diff.new_path = None;
+            }
+        } else if let Some(path) = line.strip_prefix("--- ") {
+            let old_path = parse_path(path, "a/");
+            match in_git_header {
+                true => file_diffs.last_mut().unwrap().old_path = old_path,
+                false => file_diffs.push(FileDiff::new(old_path, None)),
+            }
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            let file_diff = file_diffs
+                .last_mut()
+                .context(format!("line {}: +++ without ---", i + 1))?;
+            file_diff.new_path = parse_path(path, "b/");
+        } else if line.starts_with("@@ ") {
+            if file_diffs.is_empty() {
+                bail!("line {}: hunk without file headers", i + 1);
+            }
+            let ((old_start, old_count), (new_start, new_count)) =
+                parse_hunk_header(line).context(format!("parse line {}", i + 1))?;
+            in_git_header = false;
+            hunk = Some(Hunk {
+                old_remaining: old_count,
+                new_remaining: new_count,
+                // the start is the line before the hunk if nothing is left in new file
+                cursor: match new_count {
+                    0 => new_start + 1,
+                    _ => new_start,
+                },
+                old_cursor: old_start,
+            });
+        }
+        // other lines are ignored, e.g. "index ...", "similarity index ..." or the
+        // commit message before the diff
+    }
+
+    Ok(file_diffs)
+}
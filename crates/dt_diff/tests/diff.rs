@@ -0,0 +1,195 @@
+use dt_diff::{git, parse_unified_diff, FileDiff};
+use std::{fs, path::PathBuf, process::Command};
+
+macro_rules! s {
+    ($s:expr) => {{
+        Some($s.to_string())
+    }};
+}
+
+#[test]
+fn git_diff() {
+    let diff = r#"diff --git a/src/a.js b/src/a.js
+index 1111111..2222222 100644
+--- a/src/a.js
++++ b/src/a.js
+@@ -2,3 +2,4 @@ import b from './b';
+ const A = () => {
+-  return 1;
++  return b;
++  // changed
+ };
+@@ -10,2 +10,0 @@ export default A;
+-const C = 1;
+-export { C };
+diff --git a/src/b.js b/src/b.js
+deleted file mode 100644
+index 3333333..0000000
+--- a/src/b.js
++++ /dev/null
+@@ -1 +0,0 @@
+-export default 'b';
+diff --git a/src/c.js b/src/d.js
+similarity index 100%
+rename from src/c.js
+rename to src/d.js
+diff --git a/src/e.js b/src/e.js
+new file mode 100644
+index 0000000..4444444
+--- /dev/null
++++ b/src/e.js
+@@ -0,0 +1,2 @@
++export const E = 1;
++export const EE = 2;
+"#;
+    assert_eq!(
+        parse_unified_diff(diff).unwrap(),
+        vec![
+            FileDiff {
+                old_path: s!("src/a.js"),
+                new_path: s!("src/a.js"),
+                added_lines: vec![(3, 4)],
+                removed_before_lines: vec![3, 11],
+                removed_lines: vec![(3, 3), (10, 11)],
+            },
+            FileDiff {
+                old_path: s!("src/b.js"),
+                new_path: None,
+                added_lines: vec![],
+                removed_before_lines: vec![1],
+                removed_lines: vec![(1, 1)],
+            },
+            FileDiff {
+                old_path: s!("src/c.js"),
+                new_path: s!("src/d.js"),
+                added_lines: vec![],
+                removed_before_lines: vec![],
+                removed_lines: vec![],
+            },
+            FileDiff {
+                old_path: None,
+                new_path: s!("src/e.js"),
+                added_lines: vec![(1, 2)],
+                removed_before_lines: vec![],
+                removed_lines: vec![],
+            },
+        ]
+    );
+}
+
+#[test]
+fn plain_diff() {
+    let diff = r#"--- a.js	2024-01-01 00:00:00.000000000 +0800
++++ a.js	2024-01-02 00:00:00.000000000 +0800
+@@ -1,3 +1,3 @@
+ const a = 1;
+--- a comment
++const b = 2;
+
+"#;
+    assert_eq!(
+        parse_unified_diff(diff).unwrap(),
+        vec![FileDiff {
+            old_path: s!("a.js"),
+            new_path: s!("a.js"),
+            added_lines: vec![(2, 2)],
+            removed_before_lines: vec![2],
+            removed_lines: vec![(2, 2)],
+        }]
+    );
+}
+
+#[test]
+fn is_changed() {
+    let file_diff = FileDiff {
+        old_path: s!("a.js"),
+        new_path: s!("a.js"),
+        added_lines: vec![(3, 4)],
+        removed_before_lines: vec![8],
+        removed_lines: vec![(6, 7)],
+    };
+    assert!(file_diff.is_changed(1, 3));
+    assert!(file_diff.is_changed(4, 6));
+    assert!(!file_diff.is_changed(5, 7));
+    // the removed lines were between line 7 and line 8
+    assert!(file_diff.is_changed(7, 8));
+    assert!(!file_diff.is_changed(8, 9));
+    // the removed lines were line 6 and line 7 of the old version
+    assert!(file_diff.is_removed(7, 9));
+    assert!(!file_diff.is_removed(1, 5));
+}
+
+#[test]
+fn invalid_hunk_header() {
+    assert!(parse_unified_diff("--- a.js\n+++ a.js\n@@ -1,a +1 @@\n").is_err());
+    assert!(parse_unified_diff("@@ -1 +1 @@\n").is_err());
+}
+
+fn git(repo_path: &PathBuf, args: &[&str]) {
+    let status = Command::new("git")
+        .arg("-C")
+        .arg(repo_path)
+        .args(["-c", "user.name=dt", "-c", "user.email=dt@example.com"])
+        .args(args)
+        .status()
+        .unwrap();
+    assert!(status.success());
+}
+
+#[test]
+fn diff_revisions() {
+    let repo_path = std::env::temp_dir().join("dt_diff_diff_revisions");
+    let _ = fs::remove_dir_all(&repo_path);
+    fs::create_dir_all(&repo_path).unwrap();
+    git(&repo_path, &["init", "--quiet"]);
+    fs::write(repo_path.join("a.js"), "const a = 1;\nconst b = 2;\n").unwrap();
+    git(&repo_path, &["add", "a.js"]);
+    git(&repo_path, &["commit", "--quiet", "-m", "init"]);
+    fs::write(repo_path.join("a.js"), "const a = 1;\nconst b = 3;\n").unwrap();
+    let repo = repo_path.to_str().unwrap();
+
+    let diff = git::diff_revisions(repo, "HEAD", None).unwrap();
+    assert_eq!(
+        parse_unified_diff(&diff).unwrap(),
+        vec![FileDiff {
+            old_path: s!("a.js"),
+            new_path: s!("a.js"),
+            added_lines: vec![(2, 2)],
+            removed_before_lines: vec![2],
+            removed_lines: vec![(2, 2)],
+        }]
+    );
+
+    git(&repo_path, &["commit", "--quiet", "-am", "change b"]);
+    let diff = git::diff_revisions(repo, "HEAD~1", Some("HEAD")).unwrap();
+    assert_eq!(parse_unified_diff(&diff).unwrap().len(), 1);
+    assert!(git::diff_revisions(repo, "not-a-revision", None).is_err());
+}
+
+#[test]
+fn worktree() {
+    let repo_path = std::env::temp_dir().join("dt_diff_worktree");
+    let _ = fs::remove_dir_all(&repo_path);
+    fs::create_dir_all(&repo_path).unwrap();
+    git(&repo_path, &["init", "--quiet"]);
+    fs::write(repo_path.join("a.js"), "const a = 1;\n").unwrap();
+    git(&repo_path, &["add", "a.js"]);
+    git(&repo_path, &["commit", "--quiet", "-m", "init"]);
+    fs::write(repo_path.join("a.js"), "const a = 2;\n").unwrap();
+    let repo = repo_path.to_str().unwrap();
+
+    let worktree = git::Worktree::add(repo, "HEAD").unwrap();
+    let worktree_path = worktree.path().to_path_buf();
+    assert_eq!(
+        fs::read_to_string(worktree_path.join("a.js")).unwrap(),
+        "const a = 1;\n"
+    );
+    drop(worktree);
+    assert!(!worktree_path.exists());
+    // the working tree is untouched
+    assert_eq!(
+        fs::read_to_string(repo_path.join("a.js")).unwrap(),
+        "const a = 2;\n"
+    );
+    assert!(git::Worktree::add(repo, "not-a-revision").is_err());
+}
@@ -0,0 +1,158 @@
+use anyhow::{bail, Context};
+use std::collections::HashMap;
+
+// The binary format of the exported graphs:
+//
+//     magic (4 bytes) | version (u32) | string table | body
+//
+// Every string, e.g. module paths and symbol names, is stored once in the string table
+// and referred by its index in the body. Integers are little-endian u32.
+pub const VERSION: u32 = 1;
+
+// Collect the body and intern the strings while encoding, the string table is written
+// in front of the body by `finish()`.
+pub struct Encoder {
+    strings: Vec<String>,
+    string_ids: HashMap<String, u32>,
+    body: Vec<u8>,
+}
+
+impl Encoder {
+    pub fn new() -> Self {
+        Self {
+            strings: vec![],
+            string_ids: HashMap::new(),
+            body: vec![],
+        }
+    }
+
+    pub fn write_u8(&mut self, value: u8) {
+        self.body.push(value);
+    }
+
+    pub fn write_u32(&mut self, value: u32) {
+        self.body.extend_from_slice(&value.to_le_bytes());
+    }
+
+    pub fn write_len(&mut self, len: usize) {
+        self.write_u32(len as u32);
+    }
+
+    pub fn write_str(&mut self, s: &str) {
+        let id = match self.string_ids.get(s) {
+            Some(id) => *id,
+            None => {
+                let id = self.strings.len() as u32;
+                self.strings.push(s.to_string());
+                self.string_ids.insert(s.to_string(), id);
+                id
+            }
+        };
+        self.write_u32(id);
+    }
+
+    pub fn finish(self, magic: &[u8; 4]) -> Vec<u8> {
+        let mut bytes = magic.to_vec();
+        bytes.extend_from_slice(&VERSION.to_le_bytes());
+        bytes.extend_from_slice(&(self.strings.len() as u32).to_le_bytes());
+        for s in self.strings.iter() {
+            bytes.extend_from_slice(&(s.len() as u32).to_le_bytes());
+            bytes.extend_from_slice(s.as_bytes());
+        }
+        bytes.extend_from_slice(&self.body);
+        bytes
+    }
+}
+
+impl Default for Encoder {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+pub struct Decoder<'a> {
+    bytes: &'a [u8],
+    cursor: usize,
+    strings: Vec<String>,
+}
+
+impl<'a> Decoder<'a> {
+    // Check the header and read the string table, the decoder is at the start of the
+    // body afterwards.
+    pub fn new(bytes: &'a [u8], magic: &[u8; 4]) -> anyhow::Result<Self> {
+        if !is_binary(bytes, magic) {
+            bail!("not in the binary format");
+        }
+        let mut decoder = Self {
+            bytes,
+            cursor: magic.len(),
+            strings: vec![],
+        };
+        let version = decoder.read_u32()?;
+        if version != VERSION {
+            bail!(
+                "unsupported binary format version {}, expect {}",
+                version,
+                VERSION
+            );
+        }
+        let count = decoder.read_len()?;
+        // every string takes at least the 4 bytes of its length
+        decoder.strings = Vec::with_capacity(count.min(decoder.remaining() / 4));
+        for _ in 0..count {
+            let len = decoder.read_len()?;
+            let s = std::str::from_utf8(decoder.read_bytes(len)?)
+                .context("string table is not valid utf-8")?;
+            decoder.strings.push(s.to_string());
+        }
+        Ok(decoder)
+    }
+
+    // The bytes not read yet. The lengths read from the data can be anything if it's
+    // corrupted, cap the capacity to allocate for them by this.
+    pub fn remaining(&self) -> usize {
+        self.bytes.len() - self.cursor
+    }
+
+    fn read_bytes(&mut self, len: usize) -> anyhow::Result<&'a [u8]> {
+        if len > self.remaining() {
+            bail!("unexpected end of data at {}", self.cursor);
+        }
+        let end = self.cursor + len;
+        let bytes = &self.bytes[self.cursor..end];
+        self.cursor = end;
+        Ok(bytes)
+    }
+
+    pub fn read_u8(&mut self) -> anyhow::Result<u8> {
+        Ok(self.read_bytes(1)?[0])
+    }
+
+    pub fn read_u32(&mut self) -> anyhow::Result<u32> {
+        let bytes = self.read_bytes(4)?;
+        Ok(u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+    }
+
+    pub fn read_len(&mut self) -> anyhow::Result<usize> {
+        Ok(self.read_u32()? as usize)
+    }
+
+    pub fn read_string(&mut self) -> anyhow::Result<String> {
+        let id = self.read_u32()?;
+        match self.strings.get(id as usize) {
+            Some(s) => Ok(s.to_owned()),
+            None => bail!("string id {} out of range", id),
+        }
+    }
+
+    pub fn finish(self) -> anyhow::Result<()> {
+        if self.cursor != self.bytes.len() {
+            bail!("unexpected data after {}", self.cursor);
+        }
+        Ok(())
+    }
+}
+
+pub fn is_binary(bytes: &[u8], magic: &[u8; 4]) -> bool {
+    bytes.starts_with(magic)
+}
@@ -1,71 +1,167 @@
-use anyhow::Context;
+use anyhow::{bail, Context};
 use dt_parser::types::{FromOtherModule, FromType, ModuleExport, SymbolDependency};
-use dt_path_resolver::PathResolver;
-use std::collections::HashMap;
+use dt_path_resolver::{to_absolute_path, to_relative_path, PathResolver, ToCanonicalString};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    fmt,
+    path::PathBuf,
+};
 
+// A name exported by more than one wildcard export of a module. It's excluded from the
+// named exports of the module, just like what ES modules do.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AmbiguousExport {
+    pub module_path: String,
+    pub name: String,
+    // the modules exporting the name with `export * from`
+    pub from: Vec<String>,
+}
+
+impl fmt::Display for AmbiguousExport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} is ambiguous, it's exported by {}",
+            self.name,
+            self.module_path,
+            self.from.join(", ")
+        )
+    }
+}
+
+// The module paths in the graph are relative to the project root, see
+// `dt_path_resolver::to_relative_path()`. The added modules can have either absolute or
+// relative paths.
 #[derive(Debug)]
 pub struct DependOnGraph {
     pub table: HashMap<String, SymbolDependency>,
+    project_root: String,
     path_resolver: PathResolver,
+    allow_partial_wildcard_expansion: bool,
+    ambiguous_exports: Vec<AmbiguousExport>,
+    // the modules doing `export * from` before expansion, so they can be expanded again
+    // when the modules they export from are replaced or removed
+    wildcard_exports: HashMap<String, SymbolDependency>,
 }
 
 impl DependOnGraph {
     pub fn new(root: &str) -> Self {
         Self {
             table: HashMap::new(),
+            project_root: PathBuf::from(root)
+                .to_canonical_string()
+                .unwrap_or(root.to_string()),
             path_resolver: PathResolver::new(root),
+            allow_partial_wildcard_expansion: false,
+            ambiguous_exports: vec![],
+            wildcard_exports: HashMap::new(),
         }
     }
 
+    // Modules in a wildcard export cycle can't be added by topological order. Allow this
+    // to skip the wildcard exports from modules that haven't been added yet instead of
+    // failing, see `ParserCandidateScheduler::break_cycle()`.
+    pub fn set_allow_partial_wildcard_expansion(&mut self, allow: bool) {
+        self.allow_partial_wildcard_expansion = allow;
+    }
+
+    // The names excluded from the wildcard exports so far, in the order of the modules
+    // being added.
+    pub fn get_ambiguous_exports(&self) -> &Vec<AmbiguousExport> {
+        &self.ambiguous_exports
+    }
+
+    // Follow the re-exports to the module declaring the exported name, so two wildcard
+    // exports of the same binding are not ambiguous.
+    fn resolve_export_origin(&self, module_path: &str, name: &str) -> (String, String) {
+        let mut origin = (module_path.to_string(), name.to_string());
+        let mut visited = HashSet::new();
+        while visited.insert(origin.clone()) {
+            match self
+                .table
+                .get(&origin.0)
+                .and_then(|module| module.named_export_table.get(&origin.1))
+            {
+                Some(ModuleExport::ReExportFrom(FromOtherModule {
+                    from,
+                    from_type: FromType::Named(name),
+                })) => origin = (from.clone(), name.clone()),
+                _ => break,
+            }
+        }
+        origin
+    }
+
+    // Expand `export * from` with the named exports of the imported modules:
+    // - the explicit exports of this module shadow the wildcard exports
+    // - names exported by more than one wildcard export are excluded unless they are
+    //   the same binding, they are reported by `get_ambiguous_exports()`
     fn handle_re_export_star_from(
         &mut self,
         symbol_dependency: &mut SymbolDependency,
+        allow_missing_module: bool,
     ) -> anyhow::Result<()> {
-        if symbol_dependency.re_export_star_from.is_some() {
-            let re_export_star_from = symbol_dependency.re_export_star_from.take().unwrap();
-            for from_path in re_export_star_from.iter() {
-                let resolved_path = self
-                    .path_resolver
-                    .resolve_path(&symbol_dependency.canonical_path, from_path)
-                    .context(format!(
-                        "resolve path {} from current path {} failed",
-                        from_path, symbol_dependency.canonical_path,
-                    ))?;
-                let imported_module = self
-                    .table
-                    .get(&resolved_path)
-                    .context(format!("imported module {} not exists", resolved_path))?;
-                for (key, _) in imported_module.named_export_table.iter() {
-                    assert_eq!(
-                        symbol_dependency.named_export_table.contains_key(key),
-                        false,
-                        "named export {} conflicts",
-                        key
-                    );
-                    symbol_dependency.named_export_table.insert(
-                        key.to_string(),
-                        ModuleExport::ReExportFrom(FromOtherModule {
-                            from: resolved_path.clone(),
-                            from_type: FromType::Named(key.to_string()),
-                        }),
-                    );
+        let re_export_star_from = match symbol_dependency.re_export_star_from.take() {
+            Some(re_export_star_from) => re_export_star_from,
+            None => return Ok(()),
+        };
+        // name => the modules exporting it, sorted by name to report in a stable order
+        let mut star_exports: BTreeMap<String, Vec<String>> = BTreeMap::new();
+        for resolved_path in re_export_star_from.iter() {
+            let imported_module = match self.table.get(resolved_path) {
+                Some(imported_module) => imported_module,
+                None if allow_missing_module => continue,
+                None => bail!("imported module {} not exists", resolved_path),
+            };
+            for key in imported_module.named_export_table.keys() {
+                if symbol_dependency.named_export_table.contains_key(key) {
+                    continue;
+                }
+                let from = star_exports.entry(key.to_string()).or_default();
+                if !from.contains(resolved_path) {
+                    from.push(resolved_path.clone());
                 }
             }
         }
+
+        for (key, from) in star_exports.into_iter() {
+            let origin = self.resolve_export_origin(&from[0], &key);
+            let is_ambiguous = from[1..]
+                .iter()
+                .any(|other| self.resolve_export_origin(other, &key) != origin);
+            if is_ambiguous {
+                self.ambiguous_exports.push(AmbiguousExport {
+                    module_path: symbol_dependency.canonical_path.to_owned(),
+                    name: key,
+                    from,
+                });
+                continue;
+            }
+            symbol_dependency.named_export_table.insert(
+                key.to_string(),
+                ModuleExport::ReExportFrom(FromOtherModule {
+                    from: from[0].clone(),
+                    from_type: FromType::Named(key),
+                }),
+            );
+        }
         Ok(())
     }
 
+    fn resolve_path(&self, current_path: &str, import_src: &str) -> anyhow::Result<String> {
+        let resolved_path = self.path_resolver.resolve_path(current_path, import_src)?;
+        Ok(to_relative_path(&self.project_root, &resolved_path))
+    }
+
     fn canonicalize_import_paths(
         &mut self,
         symbol_dependency: &mut SymbolDependency,
     ) -> anyhow::Result<()> {
+        let current_path = to_absolute_path(&self.project_root, &symbol_dependency.canonical_path);
         for (_, value) in symbol_dependency.local_variable_table.iter_mut() {
             match value.import_from {
                 Some(ref mut from_other_module) => {
-                    match self
-                        .path_resolver
-                        .resolve_path(&symbol_dependency.canonical_path, &from_other_module.from)
-                    {
+                    match self.resolve_path(&current_path, &from_other_module.from) {
                         Ok(resolved_path) => from_other_module.from = resolved_path,
                         Err(_) => (),
                     }
@@ -77,10 +173,7 @@ impl DependOnGraph {
             match value {
                 ModuleExport::Local(_) => (),
                 ModuleExport::ReExportFrom(ref mut from_other_module) => {
-                    match self
-                        .path_resolver
-                        .resolve_path(&symbol_dependency.canonical_path, &from_other_module.from)
-                    {
+                    match self.resolve_path(&current_path, &from_other_module.from) {
                         Ok(resolved_path) => from_other_module.from = resolved_path,
                         Err(_) => (),
                     }
@@ -91,35 +184,194 @@ impl DependOnGraph {
             match default_export {
                 ModuleExport::Local(_) => (),
                 ModuleExport::ReExportFrom(ref mut from_other_module) => {
-                    match self
-                        .path_resolver
-                        .resolve_path(&symbol_dependency.canonical_path, &from_other_module.from)
-                    {
+                    match self.resolve_path(&current_path, &from_other_module.from) {
                         Ok(resolved_path) => from_other_module.from = resolved_path,
                         Err(_) => (),
                     }
                 }
             }
         }
+        if let Some(re_export_star_from) = symbol_dependency.re_export_star_from.as_mut() {
+            for from_path in re_export_star_from.iter_mut() {
+                *from_path = self
+                    .resolve_path(&current_path, from_path)
+                    .context(format!(
+                        "resolve path {} from current path {} failed",
+                        from_path, current_path,
+                    ))?;
+            }
+        }
+        symbol_dependency.canonical_path =
+            to_relative_path(&self.project_root, &symbol_dependency.canonical_path);
         Ok(())
     }
 
-    pub fn add_symbol_dependency(
+    fn insert_symbol_dependency(
         &mut self,
         mut symbol_dependency: SymbolDependency,
     ) -> anyhow::Result<()> {
-        assert_eq!(
-            self.table.contains_key(&symbol_dependency.canonical_path),
-            false,
-            "can't add the same module twice {}",
-            symbol_dependency.canonical_path
-        );
         self.canonicalize_import_paths(&mut symbol_dependency)?;
-        self.handle_re_export_star_from(&mut symbol_dependency)?;
+        if symbol_dependency.re_export_star_from.is_some() {
+            self.wildcard_exports.insert(
+                symbol_dependency.canonical_path.to_owned(),
+                symbol_dependency.clone(),
+            );
+            self.handle_re_export_star_from(
+                &mut symbol_dependency,
+                self.allow_partial_wildcard_expansion,
+            )?;
+        }
         self.table.insert(
             symbol_dependency.canonical_path.to_owned(),
             symbol_dependency,
         );
         Ok(())
     }
+
+    pub fn add_symbol_dependency(
+        &mut self,
+        symbol_dependency: SymbolDependency,
+    ) -> anyhow::Result<()> {
+        let module_path = to_relative_path(&self.project_root, &symbol_dependency.canonical_path);
+        if self.table.contains_key(&module_path) {
+            bail!("can't add the same module twice {}", module_path);
+        }
+        self.insert_symbol_dependency(symbol_dependency)
+    }
+
+    // The modules doing `export * from` the module, directly or through other modules.
+    // They are sorted so that a module comes after the modules it exports from.
+    pub fn get_wildcard_dependents(&self, module_path: &str) -> Vec<String> {
+        let mut visited = HashSet::from([module_path.to_string()]);
+        let mut dependents = vec![];
+        // depth-first with an explicit stack, the chains of wildcard exports can be long
+        let mut stack = vec![(
+            module_path.to_string(),
+            self.direct_wildcard_dependents(module_path).into_iter(),
+        )];
+        while let Some((_, direct_dependents)) = stack.last_mut() {
+            match direct_dependents.next() {
+                Some(dependent) => {
+                    if visited.insert(dependent.clone()) {
+                        let next = self.direct_wildcard_dependents(&dependent).into_iter();
+                        stack.push((dependent, next));
+                    }
+                }
+                None => {
+                    let (dependent, _) = stack.pop().unwrap();
+                    if !stack.is_empty() {
+                        dependents.push(dependent);
+                    }
+                }
+            }
+        }
+        // reversed post-order is a topological order
+        dependents.reverse();
+        dependents
+    }
+
+    // the modules doing `export * from` the module directly
+    fn direct_wildcard_dependents(&self, module_path: &str) -> Vec<String> {
+        self.wildcard_exports
+            .iter()
+            .filter(|(_, symbol_dependency)| {
+                let re_export_star_from = symbol_dependency.re_export_star_from.as_ref().unwrap();
+                re_export_star_from.iter().any(|from| from == module_path)
+            })
+            .map(|(dependent, _)| dependent.to_owned())
+            .collect()
+    }
+
+    fn expand_wildcard_dependents(
+        &mut self,
+        dependents: &[String],
+        allow_missing_module: bool,
+    ) -> anyhow::Result<()> {
+        for dependent in dependents.iter() {
+            let mut symbol_dependency = self.wildcard_exports.get(dependent).unwrap().clone();
+            self.ambiguous_exports
+                .retain(|ambiguous_export| &ambiguous_export.module_path != dependent);
+            self.handle_re_export_star_from(&mut symbol_dependency, allow_missing_module)
+                .context(format!("expand wildcard exports of module {}", dependent))?;
+            self.table.insert(dependent.to_owned(), symbol_dependency);
+        }
+        Ok(())
+    }
+
+    fn detach_module(&mut self, module_path: &str) -> Option<SymbolDependency> {
+        self.wildcard_exports.remove(module_path);
+        self.ambiguous_exports
+            .retain(|ambiguous_export| ambiguous_export.module_path != module_path);
+        self.table.remove(module_path)
+    }
+
+    // The modules imported by the module, the wildcard exports are expanded to the named
+    // exports so the modules they're from are included.
+    pub fn get_imported_modules(&self, module_path: &str) -> HashSet<String> {
+        match self.table.get(module_path) {
+            Some(symbol_dependency) => import_sources(symbol_dependency)
+                .map(|from| from.to_owned())
+                .collect(),
+            None => HashSet::new(),
+        }
+    }
+
+    // The modules importing the module by a path not resolved when they're added, e.g.
+    // the module is created after them. Replace them again to resolve the imports.
+    pub fn get_unresolved_importers(&self, module_path: &str) -> Vec<String> {
+        self.table
+            .iter()
+            .filter(|(importer, symbol_dependency)| {
+                let current_path = to_absolute_path(&self.project_root, importer);
+                import_sources(symbol_dependency).any(|from| {
+                    !self.table.contains_key(from)
+                        && self
+                            .resolve_path(&current_path, from)
+                            .is_ok_and(|resolved_path| resolved_path == module_path)
+                })
+            })
+            .map(|(importer, _)| importer.to_owned())
+            .collect()
+    }
+
+    // Remove the module and expand the wildcard exports of its dependents again, the
+    // names exported from it are gone. The imports from it are kept as they are, just
+    // like the imports of the modules not in the graph.
+    pub fn remove_module(&mut self, module_path: &str) -> anyhow::Result<SymbolDependency> {
+        let dependents = self.get_wildcard_dependents(module_path);
+        let removed = self
+            .detach_module(module_path)
+            .context(format!("module {} not exists", module_path))?;
+        self.expand_wildcard_dependents(&dependents, true)?;
+        Ok(removed)
+    }
+
+    // Replace the module, or add it if it's not in the graph yet, then expand the
+    // wildcard exports of its dependents again. Use `get_wildcard_dependents()` to know
+    // which modules are changed as well.
+    pub fn replace_module(&mut self, symbol_dependency: SymbolDependency) -> anyhow::Result<()> {
+        let module_path = to_relative_path(&self.project_root, &symbol_dependency.canonical_path);
+        let dependents = self.get_wildcard_dependents(&module_path);
+        self.detach_module(&module_path);
+        self.insert_symbol_dependency(symbol_dependency)?;
+        self.expand_wildcard_dependents(&dependents, self.allow_partial_wildcard_expansion)
+    }
+}
+
+fn import_sources(symbol_dependency: &SymbolDependency) -> impl Iterator<Item = &String> {
+    let local_variables = symbol_dependency
+        .local_variable_table
+        .values()
+        .filter_map(|variable| variable.import_from.as_ref());
+    let exports = symbol_dependency
+        .named_export_table
+        .values()
+        .chain(symbol_dependency.default_export.iter())
+        .filter_map(|module_export| match module_export {
+            ModuleExport::Local(_) => None,
+            ModuleExport::ReExportFrom(from_other_module) => Some(from_other_module),
+        });
+    local_variables
+        .chain(exports)
+        .map(|FromOtherModule { from, .. }| from)
 }
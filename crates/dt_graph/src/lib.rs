@@ -1,2 +1,4 @@
+pub mod binary;
 pub mod depend_on_graph;
+pub mod projection;
 pub mod used_by_graph;
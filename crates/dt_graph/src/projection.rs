@@ -0,0 +1,306 @@
+use super::used_by_graph::{UsedBy, UsedByGraph, UsedByOther, UsedByType};
+use dt_path_resolver::to_absolute_path;
+use serde::Serialize;
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap, VecDeque},
+    fs,
+    path::Path,
+};
+
+// The number of symbol edges between two nodes of a projection. The edges within a node,
+// e.g. a local variable used by another one, are not counted.
+#[derive(Serialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Weight {
+    // local variables importing the symbols
+    pub imports: usize,
+    // exports re-exporting the symbols
+    pub re_exports: usize,
+}
+
+impl Weight {
+    pub fn total(&self) -> usize {
+        self.imports + self.re_exports
+    }
+}
+
+// `UsedByGraph` collapsed into a coarser graph, e.g. module -> module or package ->
+// package. An edge from `A` to `B` means some symbols of `A` depend on the symbols of
+// `B`, so dependency-cruiser-style questions can be answered without the symbols.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Projection {
+    pub nodes: BTreeSet<String>,
+    // node => the nodes it depends on => the symbol edges between them
+    pub depend_on: BTreeMap<String, BTreeMap<String, Weight>>,
+    // node => the nodes depending on it, the same edges reversed
+    pub used_by: BTreeMap<String, BTreeMap<String, Weight>>,
+}
+
+impl Projection {
+    // Collapse the modules into the nodes given by `group`, which maps a module path to
+    // its node.
+    pub fn new(used_by_graph: &UsedByGraph, mut group: impl FnMut(&str) -> String) -> Self {
+        let mut projection = Self {
+            nodes: BTreeSet::new(),
+            depend_on: BTreeMap::new(),
+            used_by: BTreeMap::new(),
+        };
+        let mut groups: HashMap<&str, String> = HashMap::new();
+        for module_path in used_by_graph.modules.keys() {
+            let node = group(module_path);
+            projection.nodes.insert(node.clone());
+            groups.insert(module_path, node);
+        }
+
+        for (module_path, module) in used_by_graph.modules.iter() {
+            let used_by_lists = module
+                .local_variable_table
+                .values()
+                .chain(module.named_export_table.values())
+                .chain([&module.default_export]);
+            for used_by in used_by_lists.flatten().flatten() {
+                let UsedByOther { by, by_type } = match used_by {
+                    UsedBy::Itself(_) => continue,
+                    UsedBy::Other(used_by_other) => used_by_other,
+                };
+                let to = &groups[module_path.as_str()];
+                let from = match groups.get(by.as_str()) {
+                    Some(from) if from != to => from,
+                    _ => continue,
+                };
+                let weight = projection
+                    .depend_on
+                    .entry(from.to_owned())
+                    .or_default()
+                    .entry(to.to_owned())
+                    .or_default();
+                match by_type {
+                    UsedByType::LocalVar(_) => weight.imports += 1,
+                    UsedByType::NamedExport(_) | UsedByType::DefaultExport => {
+                        weight.re_exports += 1
+                    }
+                }
+            }
+        }
+        for (from, dependencies) in projection.depend_on.iter() {
+            for (to, weight) in dependencies.iter() {
+                projection
+                    .used_by
+                    .entry(to.to_owned())
+                    .or_default()
+                    .insert(from.to_owned(), *weight);
+            }
+        }
+        projection
+    }
+
+    pub fn modules(used_by_graph: &UsedByGraph) -> Self {
+        Self::new(used_by_graph, |module_path| module_path.to_owned())
+    }
+
+    // Group the modules by the first `depth` directories of their paths, e.g.
+    // "src/components" for "src/components/Button/index.tsx" with depth 2. The modules
+    // in shallower directories are grouped by their own directories, and "." is the
+    // project root.
+    pub fn directories(used_by_graph: &UsedByGraph, depth: usize) -> Self {
+        Self::new(used_by_graph, |module_path| {
+            let directories: Vec<&str> = module_path.split('/').collect();
+            let directories = &directories[..directories.len() - 1];
+            match directories.is_empty() || depth == 0 {
+                true => String::from("."),
+                false => directories[..depth.min(directories.len())].join("/"),
+            }
+        })
+    }
+
+    // Group the modules by the closest package.json within the project root, the
+    // packages are named by the "name" field or their directories.
+    pub fn packages(used_by_graph: &UsedByGraph, project_root: &str) -> Self {
+        let mut packages: HashMap<String, Option<String>> = HashMap::new();
+        Self::new(used_by_graph, |module_path| {
+            // "" is the project root
+            for directory in Path::new(module_path).ancestors().skip(1) {
+                let directory = directory.to_string_lossy().to_string();
+                let package = packages
+                    .entry(directory.clone())
+                    .or_insert_with(|| read_package_name(project_root, &directory));
+                if let Some(package) = package {
+                    return package.to_owned();
+                }
+            }
+            String::from(".")
+        })
+    }
+
+    pub fn get_dependencies(&self, node: &str) -> Vec<(&str, Weight)> {
+        Self::get_neighbors(&self.depend_on, node)
+    }
+
+    pub fn get_dependents(&self, node: &str) -> Vec<(&str, Weight)> {
+        Self::get_neighbors(&self.used_by, node)
+    }
+
+    fn get_neighbors<'a>(
+        edges: &'a BTreeMap<String, BTreeMap<String, Weight>>,
+        node: &str,
+    ) -> Vec<(&'a str, Weight)> {
+        match edges.get(node) {
+            Some(neighbors) => neighbors
+                .iter()
+                .map(|(neighbor, weight)| (neighbor.as_str(), *weight))
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    // The nodes the given one depends on directly or indirectly.
+    pub fn get_transitive_dependencies(&self, node: &str) -> BTreeSet<&str> {
+        Self::walk(&self.depend_on, node)
+    }
+
+    // The nodes depending on the given one directly or indirectly.
+    pub fn get_transitive_dependents(&self, node: &str) -> BTreeSet<&str> {
+        Self::walk(&self.used_by, node)
+    }
+
+    fn walk<'a>(
+        edges: &'a BTreeMap<String, BTreeMap<String, Weight>>,
+        node: &str,
+    ) -> BTreeSet<&'a str> {
+        let mut visited = BTreeSet::new();
+        let mut queue: VecDeque<&str> = VecDeque::from([node]);
+        while let Some(current) = queue.pop_front() {
+            for (next, _) in Self::get_neighbors(edges, current) {
+                if next != node && visited.insert(next) {
+                    queue.push_back(next);
+                }
+            }
+        }
+        visited
+    }
+
+    // One of the shortest dependency chains from `from` to `to`, e.g. why a page
+    // depends on a package.
+    pub fn find_path(&self, from: &str, to: &str) -> Option<Vec<&str>> {
+        let from = self.nodes.get(from)?.as_str();
+        let mut previous: HashMap<&str, &str> = HashMap::new();
+        let mut queue: VecDeque<&str> = VecDeque::from([from]);
+        while let Some(current) = queue.pop_front() {
+            if current == to {
+                let mut path = vec![current];
+                while let Some(node) = previous.get(path.last().unwrap()) {
+                    path.push(node);
+                }
+                path.reverse();
+                return Some(path);
+            }
+            for (next, _) in self.get_dependencies(current) {
+                if next != from && !previous.contains_key(next) {
+                    previous.insert(next, current);
+                    queue.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    // The strongly connected components with more than one node, the nodes in each
+    // component depend on each other.
+    pub fn find_cycles(&self) -> Vec<Vec<&str>> {
+        let mut tarjan = Tarjan {
+            projection: self,
+            index: 0,
+            indexes: HashMap::new(),
+            low_links: HashMap::new(),
+            stack: vec![],
+            on_stack: HashMap::new(),
+            components: vec![],
+        };
+        for node in self.nodes.iter() {
+            if !tarjan.indexes.contains_key(node.as_str()) {
+                tarjan.visit(node);
+            }
+        }
+        let mut cycles: Vec<Vec<&str>> = tarjan
+            .components
+            .into_iter()
+            .filter(|component| component.len() > 1)
+            .map(|mut component| {
+                component.sort();
+                component
+            })
+            .collect();
+        cycles.sort();
+        cycles
+    }
+
+    // The nodes neither depending on nor used by other nodes.
+    pub fn get_orphans(&self) -> Vec<&str> {
+        self.nodes
+            .iter()
+            .filter(|node| {
+                !self.depend_on.contains_key(node.as_str())
+                    && !self.used_by.contains_key(node.as_str())
+            })
+            .map(|node| node.as_str())
+            .collect()
+    }
+}
+
+fn read_package_name(project_root: &str, directory: &str) -> Option<String> {
+    let package_json_path = match directory.is_empty() {
+        true => to_absolute_path(project_root, "package.json"),
+        false => to_absolute_path(project_root, &format!("{}/package.json", directory)),
+    };
+    let package_json = fs::read_to_string(package_json_path).ok()?;
+    let name = serde_json::from_str::<serde_json::Value>(&package_json)
+        .ok()
+        .and_then(|package_json| package_json["name"].as_str().map(|name| name.to_owned()));
+    match (name, directory.is_empty()) {
+        (Some(name), _) => Some(name),
+        (None, true) => Some(String::from(".")),
+        (None, false) => Some(directory.to_owned()),
+    }
+}
+
+struct Tarjan<'a> {
+    projection: &'a Projection,
+    index: usize,
+    indexes: HashMap<&'a str, usize>,
+    low_links: HashMap<&'a str, usize>,
+    stack: Vec<&'a str>,
+    on_stack: HashMap<&'a str, bool>,
+    components: Vec<Vec<&'a str>>,
+}
+
+impl<'a> Tarjan<'a> {
+    fn visit(&mut self, node: &'a str) {
+        self.indexes.insert(node, self.index);
+        self.low_links.insert(node, self.index);
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(node, true);
+
+        for (next, _) in self.projection.get_dependencies(node) {
+            if !self.indexes.contains_key(next) {
+                self.visit(next);
+                let low_link = self.low_links[node].min(self.low_links[next]);
+                self.low_links.insert(node, low_link);
+            } else if self.on_stack[next] {
+                let low_link = self.low_links[node].min(self.indexes[next]);
+                self.low_links.insert(node, low_link);
+            }
+        }
+
+        if self.low_links[node] == self.indexes[node] {
+            let mut component = vec![];
+            while let Some(member) = self.stack.pop() {
+                self.on_stack.insert(member, false);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
@@ -1,11 +1,18 @@
-use super::depend_on_graph::DependOnGraph;
+use super::{
+    binary::{Decoder, Encoder},
+    depend_on_graph::DependOnGraph,
+};
+use anyhow::bail;
 use dt_parser::{
     anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT,
-    types::{FromOtherModule, FromType, ModuleExport, ModuleScopedVariable},
+    types::{FromOtherModule, FromType, ModuleExport, ModuleScopedVariable, SymbolDependency},
 };
 use serde::{Deserialize, Serialize};
 use serde_json;
-use std::{cmp::Ordering, collections::HashMap};
+use std::{
+    cmp::Ordering,
+    collections::{HashMap, HashSet},
+};
 
 // local variables can be used by:
 // - local variables
@@ -40,12 +47,14 @@ use std::{cmp::Ordering, collections::HashMap};
 //       in 'some-module':
 //       export { default } from 'this-module' -> DefaultExport is used by DefaultExport of 'some-module'
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+const BINARY_MAGIC: &[u8; 4] = b"DTUG";
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct UsedByGraph {
     pub modules: HashMap<String, Module>,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone)]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
 pub struct Module {
     pub local_variable_table: HashMap<String, Option<Vec<UsedBy>>>,
     pub named_export_table: HashMap<String, Option<Vec<UsedBy>>>,
@@ -98,26 +107,67 @@ impl Ord for UsedByType {
     }
 }
 
+impl Module {
+    fn new(symbol_dependency: &SymbolDependency) -> Self {
+        let mut local_variable_table: HashMap<String, Option<Vec<UsedBy>>> = HashMap::new();
+        for (symbol_name, _) in symbol_dependency.local_variable_table.iter() {
+            local_variable_table.insert(symbol_name.to_owned(), None);
+        }
+        let mut named_export_table: HashMap<String, Option<Vec<UsedBy>>> = HashMap::new();
+        for (exported_name, _) in symbol_dependency.named_export_table.iter() {
+            named_export_table.insert(exported_name.to_owned(), None);
+        }
+        Module {
+            local_variable_table,
+            named_export_table,
+            default_export: None,
+        }
+    }
+
+    // Remove the edges from the given modules, the emptied used-by lists become None
+    // like the ones never used.
+    fn remove_used_by_others(&mut self, by: &HashSet<String>) {
+        let retain = |used_by_list: &mut Option<Vec<UsedBy>>| {
+            if let Some(list) = used_by_list.as_mut() {
+                list.retain(|used_by| match used_by {
+                    UsedBy::Itself(_) => true,
+                    UsedBy::Other(UsedByOther { by: other, .. }) => !by.contains(other),
+                });
+                if list.is_empty() {
+                    *used_by_list = None;
+                }
+            }
+        };
+        self.local_variable_table.values_mut().for_each(retain);
+        self.named_export_table.values_mut().for_each(retain);
+        retain(&mut self.default_export);
+    }
+}
+
+fn depends_on_any(symbol_dependency: &SymbolDependency, module_ids: &HashSet<String>) -> bool {
+    let is_from_any = |module_export: &ModuleExport| match module_export {
+        ModuleExport::Local(_) => false,
+        ModuleExport::ReExportFrom(FromOtherModule { from, .. }) => module_ids.contains(from),
+    };
+    symbol_dependency
+        .local_variable_table
+        .values()
+        .any(|variable| match variable.import_from.as_ref() {
+            Some(FromOtherModule { from, .. }) => module_ids.contains(from),
+            None => false,
+        })
+        || symbol_dependency
+            .named_export_table
+            .values()
+            .any(is_from_any)
+        || symbol_dependency.default_export.iter().any(is_from_any)
+}
+
 impl UsedByGraph {
     fn new(depend_on_graph: &DependOnGraph) -> Self {
         let mut modules: HashMap<String, Module> = HashMap::new();
         for (module_id, symbol_dependency) in depend_on_graph.table.iter() {
-            let mut local_variable_table: HashMap<String, Option<Vec<UsedBy>>> = HashMap::new();
-            for (symbol_name, _) in symbol_dependency.local_variable_table.iter() {
-                local_variable_table.insert(symbol_name.to_owned(), None);
-            }
-            let mut named_export_table: HashMap<String, Option<Vec<UsedBy>>> = HashMap::new();
-            for (exported_name, _) in symbol_dependency.named_export_table.iter() {
-                named_export_table.insert(exported_name.to_owned(), None);
-            }
-            modules.insert(
-                module_id.to_owned(),
-                Module {
-                    local_variable_table,
-                    named_export_table,
-                    default_export: None,
-                },
-            );
+            modules.insert(module_id.to_owned(), Module::new(symbol_dependency));
         }
         Self { modules }
     }
@@ -185,121 +235,142 @@ impl UsedByGraph {
             });
     }
 
-    pub fn from(depend_on_graph: &DependOnGraph) -> Self {
-        let mut used_by_graph = Self::new(depend_on_graph);
-        for (module_id, symbol_dependency) in depend_on_graph.table.iter() {
-            for (
-                symbol_name,
-                ModuleScopedVariable {
-                    depend_on,
-                    import_from,
-                },
-            ) in symbol_dependency.local_variable_table.iter()
-            {
-                if let Some(depend_on) = depend_on {
-                    let used_by = UsedBy::Itself(UsedByType::LocalVar(symbol_name.to_owned()));
-                    for depend_on_name in depend_on.iter() {
-                        used_by_graph.add_used_by_to_local_variable(
-                            module_id,
-                            depend_on_name,
-                            used_by.clone(),
-                        );
-                    }
-                }
-                if let Some(FromOtherModule { from, from_type }) = import_from {
-                    let used_by = UsedBy::Other(UsedByOther {
-                        by: module_id.to_owned(),
-                        by_type: UsedByType::LocalVar(symbol_name.to_owned()),
-                    });
-                    match from_type {
-                        FromType::Named(exported_name) => {
-                            used_by_graph.add_used_by_to_named_export(
-                                from,
-                                exported_name,
-                                used_by.clone(),
-                            );
-                        }
-                        FromType::Default => {
-                            used_by_graph.add_used_by_to_default_export(from, used_by.clone());
-                        }
-                        FromType::Namespace => {
-                            used_by_graph.add_used_by_to_all_named_exports(from, used_by.clone());
-                        }
-                    }
+    // the edges between the symbols of the module
+    fn add_used_by_itself(&mut self, module_id: &str, symbol_dependency: &SymbolDependency) {
+        for (symbol_name, ModuleScopedVariable { depend_on, .. }) in
+            symbol_dependency.local_variable_table.iter()
+        {
+            if let Some(depend_on) = depend_on {
+                let used_by = UsedBy::Itself(UsedByType::LocalVar(symbol_name.to_owned()));
+                for depend_on_name in depend_on.iter() {
+                    self.add_used_by_to_local_variable(module_id, depend_on_name, used_by.clone());
                 }
             }
-            for (exported_name, module_export) in symbol_dependency.named_export_table.iter() {
-                match module_export {
-                    ModuleExport::Local(symbol_name) => {
-                        let used_by =
-                            UsedBy::Itself(UsedByType::NamedExport(exported_name.to_owned()));
-                        used_by_graph.add_used_by_to_local_variable(
-                            module_id,
-                            symbol_name,
-                            used_by.clone(),
-                        );
+        }
+        for (exported_name, module_export) in symbol_dependency.named_export_table.iter() {
+            if let ModuleExport::Local(symbol_name) = module_export {
+                let used_by = UsedBy::Itself(UsedByType::NamedExport(exported_name.to_owned()));
+                self.add_used_by_to_local_variable(module_id, symbol_name, used_by);
+            }
+        }
+        if let Some(ModuleExport::Local(symbol_name)) = symbol_dependency.default_export.as_ref() {
+            let used_by = UsedBy::Itself(UsedByType::DefaultExport);
+            self.add_used_by_to_local_variable(module_id, symbol_name, used_by);
+        }
+    }
+
+    // the edges from the module to the symbols of other modules
+    fn add_used_by_other(&mut self, module_id: &str, symbol_dependency: &SymbolDependency) {
+        for (symbol_name, ModuleScopedVariable { import_from, .. }) in
+            symbol_dependency.local_variable_table.iter()
+        {
+            if let Some(FromOtherModule { from, from_type }) = import_from {
+                let used_by = UsedBy::Other(UsedByOther {
+                    by: module_id.to_owned(),
+                    by_type: UsedByType::LocalVar(symbol_name.to_owned()),
+                });
+                match from_type {
+                    FromType::Named(exported_name) => {
+                        self.add_used_by_to_named_export(from, exported_name, used_by);
                     }
-                    ModuleExport::ReExportFrom(FromOtherModule { from, from_type }) => {
-                        let used_by = UsedBy::Other(UsedByOther {
-                            by: module_id.to_owned(),
-                            by_type: UsedByType::NamedExport(exported_name.to_owned()),
-                        });
-                        match from_type {
-                            FromType::Named(exported_name) => {
-                                used_by_graph.add_used_by_to_named_export(
-                                    from,
-                                    exported_name,
-                                    used_by.clone(),
-                                );
-                            }
-                            FromType::Default => {
-                                used_by_graph.add_used_by_to_default_export(from, used_by.clone());
-                            }
-                            FromType::Namespace => {
-                                used_by_graph
-                                    .add_used_by_to_all_named_exports(from, used_by.clone());
-                            }
-                        }
+                    FromType::Default => {
+                        self.add_used_by_to_default_export(from, used_by);
+                    }
+                    FromType::Namespace => {
+                        self.add_used_by_to_all_named_exports(from, used_by);
                     }
                 }
             }
-            if let Some(default_export) = symbol_dependency.default_export.as_ref() {
-                match default_export {
-                    ModuleExport::Local(symbol_name) => {
-                        let used_by = UsedBy::Itself(UsedByType::DefaultExport);
-                        used_by_graph.add_used_by_to_local_variable(
-                            module_id,
-                            symbol_name,
-                            used_by.clone(),
-                        );
+        }
+        for (exported_name, module_export) in symbol_dependency.named_export_table.iter() {
+            if let ModuleExport::ReExportFrom(FromOtherModule { from, from_type }) = module_export {
+                let used_by = UsedBy::Other(UsedByOther {
+                    by: module_id.to_owned(),
+                    by_type: UsedByType::NamedExport(exported_name.to_owned()),
+                });
+                match from_type {
+                    FromType::Named(exported_name) => {
+                        self.add_used_by_to_named_export(from, exported_name, used_by);
                     }
-                    ModuleExport::ReExportFrom(FromOtherModule { from, from_type }) => {
-                        let used_by = UsedBy::Other(UsedByOther {
-                            by: module_id.to_owned(),
-                            by_type: UsedByType::DefaultExport,
-                        });
-                        match from_type {
-                            FromType::Named(exported_name) => {
-                                used_by_graph.add_used_by_to_named_export(
-                                    from,
-                                    exported_name,
-                                    used_by.clone(),
-                                );
-                            }
-                            FromType::Default => {
-                                used_by_graph.add_used_by_to_default_export(from, used_by.clone());
-                            }
-                            FromType::Namespace => {
-                                unreachable!("can't not export namespace from other module as default export")
-                            }
-                        }
+                    FromType::Default => {
+                        self.add_used_by_to_default_export(from, used_by);
                     }
+                    FromType::Namespace => {
+                        self.add_used_by_to_all_named_exports(from, used_by);
+                    }
+                }
+            }
+        }
+        if let Some(ModuleExport::ReExportFrom(FromOtherModule { from, from_type })) =
+            symbol_dependency.default_export.as_ref()
+        {
+            let used_by = UsedBy::Other(UsedByOther {
+                by: module_id.to_owned(),
+                by_type: UsedByType::DefaultExport,
+            });
+            match from_type {
+                FromType::Named(exported_name) => {
+                    self.add_used_by_to_named_export(from, exported_name, used_by);
+                }
+                FromType::Default => {
+                    self.add_used_by_to_default_export(from, used_by);
+                }
+                FromType::Namespace => {
+                    unreachable!("can't not export namespace from other module as default export")
                 }
             }
         }
+    }
+
+    pub fn from(depend_on_graph: &DependOnGraph) -> Self {
+        let mut used_by_graph = Self::new(depend_on_graph);
+        for (module_id, symbol_dependency) in depend_on_graph.table.iter() {
+            used_by_graph.add_used_by_itself(module_id, symbol_dependency);
+            used_by_graph.add_used_by_other(module_id, symbol_dependency);
+        }
         used_by_graph
     }
 
+    // Rebuild the changed modules from the `DependOnGraph`, and the edges from the
+    // modules depending on them. Modules not in the `DependOnGraph` are removed.
+    fn update_modules(&mut self, depend_on_graph: &DependOnGraph, changed: HashSet<String>) {
+        let mut relinked = changed.clone();
+        for (module_id, symbol_dependency) in depend_on_graph.table.iter() {
+            if depends_on_any(symbol_dependency, &changed) {
+                relinked.insert(module_id.to_owned());
+            }
+        }
+
+        for module in self.modules.values_mut() {
+            module.remove_used_by_others(&relinked);
+        }
+        for module_id in changed.iter() {
+            self.modules.remove(module_id);
+            if let Some(symbol_dependency) = depend_on_graph.table.get(module_id) {
+                self.modules
+                    .insert(module_id.to_owned(), Module::new(symbol_dependency));
+                self.add_used_by_itself(module_id, symbol_dependency);
+            }
+        }
+        for module_id in relinked.iter() {
+            if let Some(symbol_dependency) = depend_on_graph.table.get(module_id) {
+                self.add_used_by_other(module_id, symbol_dependency);
+            }
+        }
+    }
+
+    // Call these after the module is removed from or replaced in the `DependOnGraph`.
+    // The modules doing wildcard exports from it are updated as well.
+    pub fn remove_module(&mut self, depend_on_graph: &DependOnGraph, module_id: &str) {
+        self.replace_module(depend_on_graph, module_id);
+    }
+
+    pub fn replace_module(&mut self, depend_on_graph: &DependOnGraph, module_id: &str) {
+        let mut changed = HashSet::from([module_id.to_owned()]);
+        changed.extend(depend_on_graph.get_wildcard_dependents(module_id));
+        self.update_modules(depend_on_graph, changed);
+    }
+
     pub fn export(&self) -> anyhow::Result<String> {
         Ok(serde_json::to_string(self)?)
     }
@@ -307,4 +378,152 @@ impl UsedByGraph {
     pub fn import(exported: &str) -> anyhow::Result<Self> {
         Ok(serde_json::from_str(exported)?)
     }
+
+    // The modules are numbered by the order they are encoded, `UsedByOther.by` is
+    // encoded as the number of the module, so it must be one of the modules.
+    pub fn encode(&self, encoder: &mut Encoder) -> anyhow::Result<()> {
+        let node_ids: HashMap<&str, u32> = self
+            .modules
+            .keys()
+            .enumerate()
+            .map(|(i, module_id)| (module_id.as_str(), i as u32))
+            .collect();
+        let encode_used_by_list =
+            |encoder: &mut Encoder, used_by_list: &Option<Vec<UsedBy>>| -> anyhow::Result<()> {
+                let used_by_list = match used_by_list {
+                    Some(used_by_list) => used_by_list,
+                    None => {
+                        encoder.write_u8(0);
+                        return Ok(());
+                    }
+                };
+                encoder.write_u8(1);
+                encoder.write_len(used_by_list.len());
+                for used_by in used_by_list.iter() {
+                    let used_by_type = match used_by {
+                        UsedBy::Itself(used_by_type) => {
+                            encoder.write_u8(0);
+                            used_by_type
+                        }
+                        UsedBy::Other(UsedByOther { by, by_type }) => {
+                            let node_id = match node_ids.get(by.as_str()) {
+                                Some(node_id) => *node_id,
+                                None => bail!("used by module {} not in the graph", by),
+                            };
+                            encoder.write_u8(1);
+                            encoder.write_u32(node_id);
+                            by_type
+                        }
+                    };
+                    match used_by_type {
+                        UsedByType::NamedExport(name) => {
+                            encoder.write_u8(0);
+                            encoder.write_str(name);
+                        }
+                        UsedByType::DefaultExport => encoder.write_u8(1),
+                        UsedByType::LocalVar(name) => {
+                            encoder.write_u8(2);
+                            encoder.write_str(name);
+                        }
+                    }
+                }
+                Ok(())
+            };
+
+        encoder.write_len(self.modules.len());
+        for module_id in self.modules.keys() {
+            encoder.write_str(module_id);
+        }
+        for module in self.modules.values() {
+            for table in [&module.local_variable_table, &module.named_export_table] {
+                encoder.write_len(table.len());
+                for (name, used_by_list) in table.iter() {
+                    encoder.write_str(name);
+                    encode_used_by_list(encoder, used_by_list)?;
+                }
+            }
+            encode_used_by_list(encoder, &module.default_export)?;
+        }
+        Ok(())
+    }
+
+    pub fn decode(decoder: &mut Decoder) -> anyhow::Result<Self> {
+        let decode_used_by_list =
+            |decoder: &mut Decoder, module_ids: &[String]| -> anyhow::Result<Option<Vec<UsedBy>>> {
+                if decoder.read_u8()? == 0 {
+                    return Ok(None);
+                }
+                let len = decoder.read_len()?;
+                let mut used_by_list = Vec::with_capacity(len.min(decoder.remaining()));
+                for _ in 0..len {
+                    let by = match decoder.read_u8()? {
+                        0 => None,
+                        _ => {
+                            let node_id = decoder.read_u32()? as usize;
+                            match module_ids.get(node_id) {
+                                Some(by) => Some(by.to_owned()),
+                                None => bail!("module id {} out of range", node_id),
+                            }
+                        }
+                    };
+                    let used_by_type = match decoder.read_u8()? {
+                        0 => UsedByType::NamedExport(decoder.read_string()?),
+                        1 => UsedByType::DefaultExport,
+                        2 => UsedByType::LocalVar(decoder.read_string()?),
+                        tag => bail!("invalid used-by type {}", tag),
+                    };
+                    used_by_list.push(match by {
+                        Some(by) => UsedBy::Other(UsedByOther {
+                            by,
+                            by_type: used_by_type,
+                        }),
+                        None => UsedBy::Itself(used_by_type),
+                    });
+                }
+                Ok(Some(used_by_list))
+            };
+
+        let module_count = decoder.read_len()?;
+        let mut module_ids = Vec::with_capacity(module_count.min(decoder.remaining()));
+        for _ in 0..module_count {
+            module_ids.push(decoder.read_string()?);
+        }
+        let mut modules = HashMap::with_capacity(module_ids.len());
+        for module_id in module_ids.iter() {
+            let mut tables = vec![];
+            for _ in 0..2 {
+                let len = decoder.read_len()?;
+                let mut table = HashMap::with_capacity(len.min(decoder.remaining()));
+                for _ in 0..len {
+                    let name = decoder.read_string()?;
+                    table.insert(name, decode_used_by_list(decoder, &module_ids)?);
+                }
+                tables.push(table);
+            }
+            let named_export_table = tables.pop().unwrap();
+            let local_variable_table = tables.pop().unwrap();
+            modules.insert(
+                module_id.to_owned(),
+                Module {
+                    local_variable_table,
+                    named_export_table,
+                    default_export: decode_used_by_list(decoder, &module_ids)?,
+                },
+            );
+        }
+        Ok(Self { modules })
+    }
+
+    pub fn export_binary(&self) -> anyhow::Result<Vec<u8>> {
+        let mut encoder = Encoder::new();
+        self.encode(&mut encoder)?;
+        Ok(encoder.finish(BINARY_MAGIC))
+    }
+
+    pub fn import_binary(exported: &[u8]) -> anyhow::Result<Self> {
+        let mut decoder = Decoder::new(exported, BINARY_MAGIC)?;
+        let used_by_graph = Self::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(used_by_graph)
+    }
 }
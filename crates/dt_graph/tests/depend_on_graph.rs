@@ -1,31 +1,18 @@
-use dt_graph::depend_on_graph::DependOnGraph;
+use dt_graph::depend_on_graph::{AmbiguousExport, DependOnGraph};
 use dt_parser::types::{
     FromOtherModule, FromType, ModuleExport, ModuleScopedVariable, SymbolDependency,
 };
-use dt_path_resolver::ToCanonicalString;
 use dt_test_utils::assert_hash_map;
-use std::{collections::HashMap, path::PathBuf};
+use std::collections::{HashMap, HashSet};
 
 #[test]
 fn add_two_modules() {
     let root = "tests/fixture/depend_on";
 
-    let canonical_path_hawk = PathBuf::from(root)
-        .join("hawk.js")
-        .to_canonical_string()
-        .unwrap();
-    let canonical_path_red_demon = PathBuf::from(root)
-        .join("red-demon.js")
-        .to_canonical_string()
-        .unwrap();
-    let canonical_path_grey_demon = PathBuf::from(root)
-        .join("grey-demon.js")
-        .to_canonical_string()
-        .unwrap();
-    let canonical_path_kirby = PathBuf::from(root)
-        .join("kirby.js")
-        .to_canonical_string()
-        .unwrap();
+    let canonical_path_hawk = String::from("hawk.js");
+    let canonical_path_red_demon = String::from("red-demon.js");
+    let canonical_path_grey_demon = String::from("grey-demon.js");
+    let canonical_path_kirby = String::from("kirby.js");
 
     let mut dt = DependOnGraph::new(root);
     let hawk = SymbolDependency {
@@ -117,3 +104,202 @@ fn add_two_modules() {
         )
     );
 }
+
+#[test]
+fn conflicting_wildcard_exports() {
+    let root = "tests/fixture/ambiguous";
+    let canonical_path = |name: &str| String::from(name);
+    let (canonical_path_a, canonical_path_b, canonical_path_index) = (
+        canonical_path("a.js"),
+        canonical_path("b.js"),
+        canonical_path("index.js"),
+    );
+    let local_export = |name: &str| (String::from(name), ModuleExport::Local(String::from(name)));
+
+    let mut dt = DependOnGraph::new(root);
+    // export const Shared, A, Same
+    dt.add_symbol_dependency(SymbolDependency {
+        canonical_path: canonical_path_a.clone(),
+        local_variable_table: HashMap::new(),
+        named_export_table: HashMap::from([
+            local_export("Shared"),
+            local_export("A"),
+            local_export("Same"),
+        ]),
+        default_export: None,
+        re_export_star_from: None,
+    })
+    .unwrap();
+    // export const Shared, B
+    // export { Same } from 'a'
+    dt.add_symbol_dependency(SymbolDependency {
+        canonical_path: canonical_path_b.clone(),
+        local_variable_table: HashMap::new(),
+        named_export_table: HashMap::from([
+            local_export("Shared"),
+            local_export("B"),
+            (
+                String::from("Same"),
+                ModuleExport::ReExportFrom(FromOtherModule {
+                    from: String::from("a"),
+                    from_type: FromType::Named(String::from("Same")),
+                }),
+            ),
+        ]),
+        default_export: None,
+        re_export_star_from: None,
+    })
+    .unwrap();
+    // export const A
+    // export * from 'a'
+    // export * from 'b'
+    dt.add_symbol_dependency(SymbolDependency {
+        canonical_path: canonical_path_index.clone(),
+        local_variable_table: HashMap::new(),
+        named_export_table: HashMap::from([local_export("A")]),
+        default_export: None,
+        re_export_star_from: Some(vec![String::from("a"), String::from("b")]),
+    })
+    .unwrap();
+
+    let index = dt.table.get(&canonical_path_index).unwrap();
+    assert_hash_map!(
+        index.named_export_table,
+        ("A", ModuleExport::Local(String::from("A"))),
+        (
+            "B",
+            ModuleExport::ReExportFrom(FromOtherModule {
+                from: canonical_path_b.clone(),
+                from_type: FromType::Named(String::from("B"))
+            })
+        ),
+        (
+            "Same",
+            ModuleExport::ReExportFrom(FromOtherModule {
+                from: canonical_path_a.clone(),
+                from_type: FromType::Named(String::from("Same"))
+            })
+        ),
+    );
+    assert_eq!(
+        dt.get_ambiguous_exports(),
+        &vec![AmbiguousExport {
+            module_path: canonical_path_index,
+            name: String::from("Shared"),
+            from: vec![canonical_path_a, canonical_path_b],
+        }]
+    );
+}
+
+#[test]
+fn replace_and_remove_module() {
+    let root = "tests/fixture/incremental";
+    let canonical_path = |name: &str| String::from(name);
+    let (canonical_path_a, canonical_path_b, canonical_path_index) = (
+        canonical_path("a.js"),
+        canonical_path("b.js"),
+        canonical_path("index.js"),
+    );
+    let module_a = |exported_names: &[&str]| SymbolDependency {
+        canonical_path: canonical_path_a.clone(),
+        local_variable_table: HashMap::new(),
+        named_export_table: exported_names
+            .iter()
+            .map(|name| {
+                (
+                    String::from(*name),
+                    ModuleExport::Local(String::from(*name)),
+                )
+            })
+            .collect(),
+        default_export: None,
+        re_export_star_from: None,
+    };
+    let re_export = |from: &str, name: &str| {
+        ModuleExport::ReExportFrom(FromOtherModule {
+            from: from.to_string(),
+            from_type: FromType::Named(String::from(name)),
+        })
+    };
+
+    let mut dt = DependOnGraph::new(root);
+    // export const A
+    dt.add_symbol_dependency(module_a(&["A"])).unwrap();
+    // export * from 'a'
+    dt.add_symbol_dependency(SymbolDependency {
+        canonical_path: canonical_path_b.clone(),
+        local_variable_table: HashMap::new(),
+        named_export_table: HashMap::new(),
+        default_export: None,
+        re_export_star_from: Some(vec![String::from("a")]),
+    })
+    .unwrap();
+    // export * from 'b'
+    dt.add_symbol_dependency(SymbolDependency {
+        canonical_path: canonical_path_index.clone(),
+        local_variable_table: HashMap::new(),
+        named_export_table: HashMap::new(),
+        default_export: None,
+        re_export_star_from: Some(vec![String::from("b")]),
+    })
+    .unwrap();
+    assert_eq!(
+        dt.get_wildcard_dependents(&canonical_path_a),
+        vec![canonical_path_b.clone(), canonical_path_index.clone()]
+    );
+
+    // a module is replaced instead of added again
+    assert!(dt.add_symbol_dependency(module_a(&["A"])).is_err());
+
+    // export const A, AA
+    dt.replace_module(module_a(&["A", "AA"])).unwrap();
+    assert_eq!(dt.table.len(), 3);
+    assert_hash_map!(
+        dt.table.get(&canonical_path_b).unwrap().named_export_table,
+        ("A", re_export(&canonical_path_a, "A")),
+        ("AA", re_export(&canonical_path_a, "AA")),
+    );
+    assert_hash_map!(
+        dt.table
+            .get(&canonical_path_index)
+            .unwrap()
+            .named_export_table,
+        ("A", re_export(&canonical_path_b, "A")),
+        ("AA", re_export(&canonical_path_b, "AA")),
+    );
+    assert_eq!(
+        dt.get_imported_modules(&canonical_path_index),
+        HashSet::from([canonical_path_b.clone()])
+    );
+
+    let removed = dt.remove_module(&canonical_path_a).unwrap();
+    assert_eq!(removed.named_export_table.len(), 2);
+    assert_eq!(dt.table.len(), 2);
+    assert_eq!(
+        dt.table
+            .get(&canonical_path_b)
+            .unwrap()
+            .named_export_table
+            .len(),
+        0
+    );
+    assert_eq!(
+        dt.table
+            .get(&canonical_path_index)
+            .unwrap()
+            .named_export_table
+            .len(),
+        0
+    );
+    assert!(dt.remove_module(&canonical_path_a).is_err());
+
+    // add it back
+    dt.replace_module(module_a(&["A"])).unwrap();
+    assert_hash_map!(
+        dt.table
+            .get(&canonical_path_index)
+            .unwrap()
+            .named_export_table,
+        ("A", re_export(&canonical_path_b, "A")),
+    );
+}
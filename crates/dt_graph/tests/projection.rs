@@ -0,0 +1,222 @@
+use dt_graph::{
+    projection::{Projection, Weight},
+    used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType},
+};
+use std::collections::{BTreeSet, HashMap};
+
+macro_rules! s {
+    ($s:expr) => {{
+        $s.to_string()
+    }};
+}
+
+fn module(
+    local_variables: Vec<(&str, Vec<UsedBy>)>,
+    named_exports: Vec<(&str, Vec<UsedBy>)>,
+) -> Module {
+    let to_table = |symbols: Vec<(&str, Vec<UsedBy>)>| {
+        symbols
+            .into_iter()
+            .map(|(name, used_by)| match used_by.is_empty() {
+                true => (s!(name), None),
+                false => (s!(name), Some(used_by)),
+            })
+            .collect()
+    };
+    Module {
+        local_variable_table: to_table(local_variables),
+        named_export_table: to_table(named_exports),
+        default_export: None,
+    }
+}
+
+fn other(by: &str, by_type: UsedByType) -> UsedBy {
+    UsedBy::Other(UsedByOther {
+        by: s!(by),
+        by_type,
+    })
+}
+
+// apps/web/main.js
+//     import { Button } from '@app/ui';
+//     import { Title } from './title';
+// apps/web/title.js
+//     import { color } from '@app/ui/theme';
+//     export const Title = color;
+// apps/web/a.js and apps/web/b.js import each other
+// apps/web/orphan.js
+// packages/ui/index.js
+//     export { Button } from './button';
+// packages/ui/button.js
+//     import { color } from './theme';
+//     export const Button = color;
+// packages/ui/theme.js
+//     export const color = 'red';
+fn used_by_graph() -> UsedByGraph {
+    let exported = |name: &str| vec![UsedBy::Itself(UsedByType::NamedExport(s!(name)))];
+    let imported_by = |by: &str, name: &str| vec![other(by, UsedByType::LocalVar(s!(name)))];
+    UsedByGraph {
+        modules: HashMap::from([
+            (
+                s!("apps/web/main.js"),
+                module(vec![("Button", vec![]), ("Title", vec![])], vec![]),
+            ),
+            (
+                s!("apps/web/title.js"),
+                module(
+                    vec![("color", vec![]), ("Title", exported("Title"))],
+                    vec![("Title", imported_by("apps/web/main.js", "Title"))],
+                ),
+            ),
+            (
+                s!("apps/web/a.js"),
+                module(
+                    vec![("A", exported("A")), ("B", vec![])],
+                    vec![("A", imported_by("apps/web/b.js", "A"))],
+                ),
+            ),
+            (
+                s!("apps/web/b.js"),
+                module(
+                    vec![("B", exported("B")), ("A", vec![])],
+                    vec![("B", imported_by("apps/web/a.js", "B"))],
+                ),
+            ),
+            (s!("apps/web/orphan.js"), module(vec![], vec![])),
+            (
+                s!("packages/ui/index.js"),
+                module(
+                    vec![],
+                    vec![("Button", imported_by("apps/web/main.js", "Button"))],
+                ),
+            ),
+            (
+                s!("packages/ui/button.js"),
+                module(
+                    vec![("color", vec![]), ("Button", exported("Button"))],
+                    vec![(
+                        "Button",
+                        vec![other(
+                            "packages/ui/index.js",
+                            UsedByType::NamedExport(s!("Button")),
+                        )],
+                    )],
+                ),
+            ),
+            (
+                s!("packages/ui/theme.js"),
+                module(
+                    vec![("color", exported("color"))],
+                    vec![(
+                        "color",
+                        vec![
+                            other("apps/web/title.js", UsedByType::LocalVar(s!("color"))),
+                            other("packages/ui/button.js", UsedByType::LocalVar(s!("color"))),
+                        ],
+                    )],
+                ),
+            ),
+        ]),
+    }
+}
+
+fn imports(imports: usize) -> Weight {
+    Weight {
+        imports,
+        re_exports: 0,
+    }
+}
+
+#[test]
+fn modules() {
+    let projection = Projection::modules(&used_by_graph());
+    assert_eq!(projection.nodes.len(), 8);
+    assert_eq!(
+        projection.get_dependencies("apps/web/main.js"),
+        vec![
+            ("apps/web/title.js", imports(1)),
+            ("packages/ui/index.js", imports(1)),
+        ]
+    );
+    assert_eq!(
+        projection.get_dependents("packages/ui/button.js"),
+        vec![(
+            "packages/ui/index.js",
+            Weight {
+                imports: 0,
+                re_exports: 1,
+            }
+        )]
+    );
+    assert_eq!(
+        projection.get_transitive_dependencies("apps/web/main.js"),
+        BTreeSet::from([
+            "apps/web/title.js",
+            "packages/ui/button.js",
+            "packages/ui/index.js",
+            "packages/ui/theme.js",
+        ])
+    );
+    assert_eq!(
+        projection.get_transitive_dependents("packages/ui/theme.js"),
+        BTreeSet::from([
+            "apps/web/main.js",
+            "apps/web/title.js",
+            "packages/ui/button.js",
+            "packages/ui/index.js",
+        ])
+    );
+    assert_eq!(
+        projection.find_path("apps/web/main.js", "packages/ui/theme.js"),
+        Some(vec![
+            "apps/web/main.js",
+            "apps/web/title.js",
+            "packages/ui/theme.js",
+        ])
+    );
+    assert_eq!(
+        projection.find_path("packages/ui/theme.js", "apps/web/main.js"),
+        None
+    );
+    assert_eq!(
+        projection.find_cycles(),
+        vec![vec!["apps/web/a.js", "apps/web/b.js"]]
+    );
+    assert_eq!(projection.get_orphans(), vec!["apps/web/orphan.js"]);
+}
+
+#[test]
+fn directories() {
+    let projection = Projection::directories(&used_by_graph(), 2);
+    assert_eq!(
+        projection.nodes,
+        BTreeSet::from([s!("apps/web"), s!("packages/ui")])
+    );
+    // the edges within a directory are dropped
+    assert_eq!(
+        projection.get_dependencies("apps/web"),
+        vec![("packages/ui", imports(2))]
+    );
+    assert!(projection.get_dependencies("packages/ui").is_empty());
+    assert!(projection.find_cycles().is_empty());
+
+    let projection = Projection::directories(&used_by_graph(), 1);
+    assert_eq!(
+        projection.nodes,
+        BTreeSet::from([s!("apps"), s!("packages")])
+    );
+}
+
+#[test]
+fn packages() {
+    let projection = Projection::packages(&used_by_graph(), "tests/fixture/projection");
+    // apps/web/package.json has no name
+    assert_eq!(
+        projection.nodes,
+        BTreeSet::from([s!("@app/ui"), s!("apps/web")])
+    );
+    assert_eq!(
+        projection.get_dependencies("apps/web"),
+        vec![("@app/ui", imports(2))]
+    );
+}
@@ -1,13 +1,15 @@
 use dt_graph::{
     depend_on_graph::DependOnGraph,
-    used_by_graph::{UsedBy, UsedByGraph, UsedByOther, UsedByType},
+    used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType},
 };
 use dt_parser::{
-    anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT, collect_symbol_dependency,
-    types::SymbolDependency, Input,
+    anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT,
+    collect_symbol_dependency,
+    types::{FromOtherModule, FromType, ModuleExport, ModuleScopedVariable, SymbolDependency},
+    Input,
 };
 use dt_path_resolver::ToCanonicalString;
-use std::path::PathBuf;
+use std::{collections::HashMap, path::PathBuf};
 
 macro_rules! s {
     ($s:expr) => {{
@@ -44,9 +46,13 @@ macro_rules! assert_used_by_table {
     }};
 }
 
-fn parse(module_path: &str) -> anyhow::Result<SymbolDependency> {
-    let module_ast = Input::Path(module_path).get_module_ast()?;
-    let symbol_dependency = collect_symbol_dependency(&module_ast, module_path)?;
+// the paths in the graphs are relative to the root
+fn parse(root: &str, module_path: &str) -> anyhow::Result<SymbolDependency> {
+    let module_path = PathBuf::from(root)
+        .join(module_path)
+        .to_canonical_string()?;
+    let module_ast = Input::Path(&module_path).get_module_ast()?;
+    let symbol_dependency = collect_symbol_dependency(&module_ast, &module_path)?;
     Ok(symbol_dependency)
 }
 
@@ -61,26 +67,21 @@ fn picnic_time() {
         "wild.js",
         "PicnicTime.js",
     ]
-    .map(|path| {
-        PathBuf::from(root)
-            .join(path)
-            .to_canonical_string()
-            .unwrap()
-    });
+    .map(|path| path.to_string());
     depend_on_graph
-        .add_symbol_dependency(parse(&happy_path).unwrap())
+        .add_symbol_dependency(parse(root, &happy_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&hawk_path).unwrap())
+        .add_symbol_dependency(parse(root, &hawk_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&kirby_path).unwrap())
+        .add_symbol_dependency(parse(root, &kirby_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&wild_path).unwrap())
+        .add_symbol_dependency(parse(root, &wild_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&picnic_time_path).unwrap())
+        .add_symbol_dependency(parse(root, &picnic_time_path).unwrap())
         .unwrap();
     let used_by_graph = UsedByGraph::from(&depend_on_graph);
 
@@ -330,26 +331,21 @@ fn export_and_import() {
         "wild.js",
         "PicnicTime.js",
     ]
-    .map(|path| {
-        PathBuf::from(root)
-            .join(path)
-            .to_canonical_string()
-            .unwrap()
-    });
+    .map(|path| path.to_string());
     depend_on_graph
-        .add_symbol_dependency(parse(&happy_path).unwrap())
+        .add_symbol_dependency(parse(root, &happy_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&hawk_path).unwrap())
+        .add_symbol_dependency(parse(root, &hawk_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&kirby_path).unwrap())
+        .add_symbol_dependency(parse(root, &kirby_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&wild_path).unwrap())
+        .add_symbol_dependency(parse(root, &wild_path).unwrap())
         .unwrap();
     depend_on_graph
-        .add_symbol_dependency(parse(&picnic_time_path).unwrap())
+        .add_symbol_dependency(parse(root, &picnic_time_path).unwrap())
         .unwrap();
     let picnic_time_graph = UsedByGraph::from(&depend_on_graph);
 
@@ -591,3 +587,238 @@ fn export_and_import() {
     );
     assert!(picnic_time_module.default_export.is_none());
 }
+
+// the local variables, named exports and default export of a module
+type SortedModule = (
+    Vec<(String, Vec<UsedBy>)>,
+    Vec<(String, Vec<UsedBy>)>,
+    Vec<UsedBy>,
+);
+
+// the used-by vectors are sorted since the order is undetermined
+fn sorted_modules(used_by_graph: &UsedByGraph) -> HashMap<String, SortedModule> {
+    let sorted_table = |table: &HashMap<String, Option<Vec<UsedBy>>>| {
+        let mut table: Vec<(String, Vec<UsedBy>)> = table
+            .iter()
+            .map(|(key, used_by)| {
+                let mut used_by = used_by.clone().unwrap_or_default();
+                used_by.sort();
+                (key.to_owned(), used_by)
+            })
+            .collect();
+        table.sort();
+        table
+    };
+    used_by_graph
+        .modules
+        .iter()
+        .map(|(module_id, module)| {
+            let mut default_export = module.default_export.clone().unwrap_or_default();
+            default_export.sort();
+            (
+                module_id.to_owned(),
+                (
+                    sorted_table(&module.local_variable_table),
+                    sorted_table(&module.named_export_table),
+                    default_export,
+                ),
+            )
+        })
+        .collect()
+}
+
+#[test]
+fn replace_and_remove_module() {
+    let root = "tests/fixture/incremental";
+    let [a_path, b_path, c_path] = ["a.js", "b.js", "c.js"].map(|path| path.to_string());
+    // export const A = 1; export const AA = A;
+    let module_a = |exported_names: &[&str]| SymbolDependency {
+        canonical_path: a_path.clone(),
+        local_variable_table: exported_names
+            .iter()
+            .map(|name| {
+                (
+                    s!(name),
+                    ModuleScopedVariable {
+                        depend_on: match *name {
+                            "A" => None,
+                            _ => Some(vec![s!("A")]),
+                        },
+                        import_from: None,
+                    },
+                )
+            })
+            .collect(),
+        named_export_table: exported_names
+            .iter()
+            .map(|name| (s!(name), ModuleExport::Local(s!(name))))
+            .collect(),
+        default_export: None,
+        re_export_star_from: None,
+    };
+    let import_from = |from: &str, from_type: FromType| ModuleScopedVariable {
+        depend_on: None,
+        import_from: Some(FromOtherModule {
+            from: s!(from),
+            from_type,
+        }),
+    };
+
+    let mut depend_on_graph = DependOnGraph::new(root);
+    depend_on_graph
+        .add_symbol_dependency(module_a(&["A"]))
+        .unwrap();
+    // export * from './a';
+    depend_on_graph
+        .add_symbol_dependency(SymbolDependency {
+            canonical_path: b_path.clone(),
+            local_variable_table: HashMap::new(),
+            named_export_table: HashMap::new(),
+            default_export: None,
+            re_export_star_from: Some(vec![s!("./a")]),
+        })
+        .unwrap();
+    // import { A } from './b';
+    // import * as NS from './a';
+    // export default A;
+    depend_on_graph
+        .add_symbol_dependency(SymbolDependency {
+            canonical_path: c_path.clone(),
+            local_variable_table: HashMap::from([
+                (s!("A"), import_from("./b", FromType::Named(s!("A")))),
+                (s!("NS"), import_from("./a", FromType::Namespace)),
+            ]),
+            named_export_table: HashMap::new(),
+            default_export: Some(ModuleExport::Local(s!("A"))),
+            re_export_star_from: None,
+        })
+        .unwrap();
+    let mut used_by_graph = UsedByGraph::from(&depend_on_graph);
+
+    depend_on_graph
+        .replace_module(module_a(&["A", "AA"]))
+        .unwrap();
+    used_by_graph.replace_module(&depend_on_graph, &a_path);
+    assert_eq!(
+        sorted_modules(&used_by_graph),
+        sorted_modules(&UsedByGraph::from(&depend_on_graph))
+    );
+    let a_module = used_by_graph.modules.get(&a_path).unwrap();
+    assert_used_by_table!(
+        a_module.named_export_table,
+        (
+            "A",
+            Some(vec![
+                UsedBy::Other(UsedByOther {
+                    by: b_path.to_owned(),
+                    by_type: UsedByType::NamedExport(s!("A")),
+                }),
+                UsedBy::Other(UsedByOther {
+                    by: c_path.to_owned(),
+                    by_type: UsedByType::LocalVar(s!("NS")),
+                }),
+            ])
+        ),
+        (
+            "AA",
+            Some(vec![
+                UsedBy::Other(UsedByOther {
+                    by: b_path.to_owned(),
+                    by_type: UsedByType::NamedExport(s!("AA")),
+                }),
+                UsedBy::Other(UsedByOther {
+                    by: c_path.to_owned(),
+                    by_type: UsedByType::LocalVar(s!("NS")),
+                }),
+            ])
+        ),
+    );
+    let b_module = used_by_graph.modules.get(&b_path).unwrap();
+    assert_used_by_table!(
+        b_module.named_export_table,
+        (
+            "A",
+            Some(vec![UsedBy::Other(UsedByOther {
+                by: c_path.to_owned(),
+                by_type: UsedByType::LocalVar(s!("A")),
+            })])
+        ),
+        ("AA", None::<Vec<UsedBy>>),
+    );
+
+    depend_on_graph.remove_module(&a_path).unwrap();
+    used_by_graph.remove_module(&depend_on_graph, &a_path);
+    assert_eq!(
+        sorted_modules(&used_by_graph),
+        sorted_modules(&UsedByGraph::from(&depend_on_graph))
+    );
+    assert_eq!(used_by_graph.modules.len(), 2);
+    let b_module = used_by_graph.modules.get(&b_path).unwrap();
+    assert_eq!(b_module.named_export_table.len(), 0);
+}
+
+#[test]
+fn export_and_import_binary() {
+    let root = "tests/fixture/used_by";
+    let mut depend_on_graph = DependOnGraph::new(root);
+    for path in [
+        "happy.js",
+        "hawk.js",
+        "kirby.js",
+        "wild.js",
+        "PicnicTime.js",
+    ] {
+        depend_on_graph
+            .add_symbol_dependency(parse(root, path).unwrap())
+            .unwrap();
+    }
+    let used_by_graph = UsedByGraph::from(&depend_on_graph);
+
+    let exported = used_by_graph.export_binary().unwrap();
+    assert_eq!(
+        UsedByGraph::import_binary(&exported).unwrap(),
+        used_by_graph
+    );
+    assert!(UsedByGraph::import_binary(used_by_graph.export().unwrap().as_bytes()).is_err());
+}
+
+#[test]
+fn invalid_binary() {
+    let mut used_by_graph = UsedByGraph {
+        modules: HashMap::from([(
+            s!("a.js"),
+            Module {
+                local_variable_table: HashMap::new(),
+                named_export_table: HashMap::from([(s!("A"), None)]),
+                default_export: None,
+            },
+        )]),
+    };
+    let exported = used_by_graph.export_binary().unwrap();
+    // magic | version | 2 strings | "a.js" | "A" | 1 module, the count of the modules is
+    // corrupted to a huge one
+    let module_count_at = 4 + 4 + 4 + (4 + 4) + (4 + 1);
+    assert_eq!(exported[module_count_at..module_count_at + 4], [1, 0, 0, 0]);
+    let mut corrupted = exported.clone();
+    corrupted[module_count_at..module_count_at + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(UsedByGraph::import_binary(&corrupted).is_err());
+    // so is the count of the strings
+    let mut corrupted = exported.clone();
+    corrupted[8..12].copy_from_slice(&u32::MAX.to_le_bytes());
+    assert!(UsedByGraph::import_binary(&corrupted).is_err());
+
+    // used by a module not in the graph
+    used_by_graph
+        .modules
+        .get_mut("a.js")
+        .unwrap()
+        .named_export_table
+        .insert(
+            s!("A"),
+            Some(vec![UsedBy::Other(UsedByOther {
+                by: s!("b.js"),
+                by_type: UsedByType::LocalVar(s!("A")),
+            })]),
+        );
+    assert!(used_by_graph.export_binary().is_err());
+}
@@ -19,6 +19,16 @@ impl I18nToSymbol {
         module_ast: &Module,
     ) -> anyhow::Result<()> {
         let i18n_usage = core::collect_translation(module_ast)?;
+        self.add_i18n_usage(module_path, &i18n_usage);
+        Ok(())
+    }
+
+    // Add the usage collected by `collect_translation()` before, e.g. from the cache.
+    pub fn add_i18n_usage(
+        &mut self,
+        module_path: &str,
+        i18n_usage: &HashMap<String, HashSet<String>>,
+    ) {
         for (symbol, i18n_keys) in i18n_usage.iter() {
             for i18n_key in i18n_keys.iter() {
                 if !self.table.contains_key(i18n_key) {
@@ -38,6 +48,5 @@ impl I18nToSymbol {
                     .insert(symbol.to_owned());
             }
         }
-        Ok(())
     }
 }
@@ -1,5 +1,6 @@
 pub mod anonymous_default_export;
 mod parser;
+mod symbol_span;
 mod to_symbol_name;
 pub mod types;
 mod visitors;
@@ -7,3 +8,4 @@ mod visitors;
 // pub use parser::parse;
 // pub use parser::parse_module;
 pub use parser::{collect_symbol_dependency, Input};
+pub use symbol_span::collect_symbol_spans;
@@ -30,6 +30,12 @@ pub enum Input<'input> {
 
 impl<'input> Input<'input> {
     pub fn get_module_ast(&self) -> anyhow::Result<Module> {
+        Ok(self.get_module_ast_and_source_map()?.0)
+    }
+
+    // The source map is needed to get the line numbers of the spans in the module ast,
+    // see `collect_symbol_spans()`.
+    pub fn get_module_ast_and_source_map(&self) -> anyhow::Result<(Module, Lrc<SourceMap>)> {
         let cm: Lrc<SourceMap> = Default::default();
         let fm = match self {
             Input::Path(module_path) => cm
@@ -70,7 +76,7 @@ impl<'input> Input<'input> {
             // ref: https://rustdoc.swc.rs/swc_ecma_transforms_base/fn.resolver.html
             module.fold_with(&mut resolver(Mark::new(), Mark::new(), true))
         });
-        Ok(module)
+        Ok((module, cm))
     }
 }
 
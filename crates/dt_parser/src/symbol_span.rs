@@ -0,0 +1,243 @@
+use super::{
+    anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT,
+    to_symbol_name::ToSymbolName,
+    types::{SpannedSymbol, SymbolSpan},
+};
+use swc_core::{
+    common::{SourceMap, Span, Spanned},
+    ecma::ast,
+};
+
+// Collect the spans of the module-scoped symbols, the symbols are named in the same way
+// as `collect_symbol_dependency()`. Only the top-level module items are considered, so
+// a change inside a function is attributed to the function.
+//
+// - imports are spanned by the whole import declaration
+// - declarations are spanned by themselves, `const a = 1, b = 2;` is split by declarators
+// - `export { a as b }` spans the named export `b`
+// - `export { a } from 'module'` spans the named export `a`
+// - `export * from 'module'` is not spanned since its names are unknown here
+pub fn collect_symbol_spans(module_ast: &ast::Module, source_map: &SourceMap) -> Vec<SymbolSpan> {
+    let mut collector = SymbolSpanCollector {
+        source_map,
+        spans: vec![],
+    };
+    for module_item in module_ast.body.iter() {
+        collector.collect_module_item(module_item);
+    }
+    collector.spans
+}
+
+struct SymbolSpanCollector<'cm> {
+    source_map: &'cm SourceMap,
+    spans: Vec<SymbolSpan>,
+}
+
+impl<'cm> SymbolSpanCollector<'cm> {
+    fn add(&mut self, symbol: SpannedSymbol, span: Span) {
+        self.spans.push(SymbolSpan {
+            symbol,
+            start_line: self.source_map.lookup_char_pos(span.lo).line,
+            end_line: self.source_map.lookup_char_pos(span.hi).line,
+        });
+    }
+
+    fn add_local_variable(&mut self, name: String, span: Span) {
+        self.add(SpannedSymbol::LocalVariable(name), span);
+    }
+
+    fn add_export(&mut self, name: String, span: Span) {
+        match name.as_str() {
+            "default" => self.add(SpannedSymbol::DefaultExport, span),
+            _ => self.add(SpannedSymbol::NamedExport(name), span),
+        }
+    }
+
+    // The first declarator owns the `const` keyword and the last one owns the `;`.
+    fn add_var_decl(&mut self, var_decl: &ast::VarDecl, item_span: Span) {
+        let last = var_decl.decls.len().saturating_sub(1);
+        for (i, decl) in var_decl.decls.iter().enumerate() {
+            if let ast::Pat::Ident(ast::BindingIdent { id, .. }) = &decl.name {
+                let mut span = decl.span;
+                if i == 0 {
+                    span.lo = item_span.lo;
+                }
+                if i == last {
+                    span.hi = item_span.hi;
+                }
+                self.add_local_variable(id.to_symbol_name(), span);
+            }
+        }
+    }
+
+    fn add_decl(&mut self, decl: &ast::Decl, item_span: Span) {
+        match decl {
+            ast::Decl::Class(ast::ClassDecl { ident, .. }) => {
+                self.add_local_variable(ident.to_symbol_name(), item_span)
+            }
+            ast::Decl::Fn(ast::FnDecl { ident, .. }) => {
+                self.add_local_variable(ident.to_symbol_name(), item_span)
+            }
+            ast::Decl::Var(var_decl) => self.add_var_decl(var_decl, item_span),
+            _ => (),
+        }
+    }
+
+    fn collect_module_item(&mut self, module_item: &ast::ModuleItem) {
+        let item_span = module_item.span();
+        match module_item {
+            ast::ModuleItem::ModuleDecl(module_decl) => match module_decl {
+                ast::ModuleDecl::Import(ast::ImportDecl { specifiers, .. }) => {
+                    for specifier in specifiers.iter() {
+                        let local = match specifier {
+                            ast::ImportSpecifier::Named(ast::ImportNamedSpecifier {
+                                local,
+                                ..
+                            }) => local,
+                            ast::ImportSpecifier::Default(ast::ImportDefaultSpecifier {
+                                local,
+                                ..
+                            }) => local,
+                            ast::ImportSpecifier::Namespace(ast::ImportStarAsSpecifier {
+                                local,
+                                ..
+                            }) => local,
+                        };
+                        self.add_local_variable(local.to_symbol_name(), item_span);
+                    }
+                }
+                ast::ModuleDecl::ExportDecl(ast::ExportDecl { decl, .. }) => {
+                    self.add_decl(decl, item_span)
+                }
+                ast::ModuleDecl::ExportNamed(ast::NamedExport {
+                    specifiers, src, ..
+                }) => {
+                    for specifier in specifiers.iter() {
+                        let exported = match specifier {
+                            ast::ExportSpecifier::Namespace(ast::ExportNamespaceSpecifier {
+                                name,
+                                ..
+                            }) => name,
+                            ast::ExportSpecifier::Named(ast::ExportNamedSpecifier {
+                                orig,
+                                exported,
+                                ..
+                            }) => exported.as_ref().unwrap_or(orig),
+                            ast::ExportSpecifier::Default(_) => continue,
+                        };
+                        if let ast::ModuleExportName::Ident(ident) = exported {
+                            // the source is shared by all the re-exported names
+                            let span = match src {
+                                Some(_) => item_span,
+                                None => specifier.span(),
+                            };
+                            self.add_export(ident.to_symbol_name(), span);
+                        }
+                    }
+                }
+                ast::ModuleDecl::ExportDefaultDecl(ast::ExportDefaultDecl { decl, .. }) => {
+                    let ident = match decl {
+                        ast::DefaultDecl::Class(ast::ClassExpr { ident, .. }) => ident,
+                        ast::DefaultDecl::Fn(ast::FnExpr { ident, .. }) => ident,
+                        ast::DefaultDecl::TsInterfaceDecl(_) => return,
+                    };
+                    match ident {
+                        Some(ident) => self.add_local_variable(ident.to_symbol_name(), item_span),
+                        None => self.add_local_variable(
+                            SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT.to_string(),
+                            item_span,
+                        ),
+                    }
+                }
+                ast::ModuleDecl::ExportDefaultExpr(ast::ExportDefaultExpr { expr, .. }) => {
+                    match &**expr {
+                        ast::Expr::Ident(_) => self.add(SpannedSymbol::DefaultExport, item_span),
+                        ast::Expr::Array(_) | ast::Expr::Object(_) | ast::Expr::Arrow(_) => self
+                            .add_local_variable(
+                                SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT.to_string(),
+                                item_span,
+                            ),
+                        _ => (),
+                    }
+                }
+                _ => (),
+            },
+            ast::ModuleItem::Stmt(ast::Stmt::Decl(decl)) => self.add_decl(decl, item_span),
+            ast::ModuleItem::Stmt(_) => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Input;
+
+    fn get_spans(code: &str) -> Vec<(SpannedSymbol, usize, usize)> {
+        let (module_ast, source_map) = Input::Code(code).get_module_ast_and_source_map().unwrap();
+        collect_symbol_spans(&module_ast, &source_map)
+            .into_iter()
+            .map(|s| (s.symbol, s.start_line, s.end_line))
+            .collect()
+    }
+
+    macro_rules! l {
+        ($s:expr) => {{
+            SpannedSymbol::LocalVariable($s.to_string())
+        }};
+    }
+
+    macro_rules! n {
+        ($s:expr) => {{
+            SpannedSymbol::NamedExport($s.to_string())
+        }};
+    }
+
+    #[test]
+    fn declarations() {
+        let code = r#"import A, { B as BB } from './a';
+const c = 1,
+  d = () => {
+    return c;
+  };
+function E() {
+  return d;
+}
+export class F {}
+"#;
+        assert_eq!(
+            get_spans(code),
+            vec![
+                (l!("A"), 1, 1),
+                (l!("BB"), 1, 1),
+                (l!("c"), 2, 2),
+                (l!("d"), 3, 5),
+                (l!("E"), 6, 8),
+                (l!("F"), 9, 9),
+            ]
+        );
+    }
+
+    #[test]
+    fn exports() {
+        let code = r#"const a = 1;
+export {
+  a as b,
+  a as default,
+};
+export { c } from './c';
+export * from './d';
+export default () => a;
+"#;
+        assert_eq!(
+            get_spans(code),
+            vec![
+                (l!("a"), 1, 1),
+                (n!("b"), 3, 3),
+                (SpannedSymbol::DefaultExport, 4, 4),
+                (n!("c"), 6, 6),
+                (l!(SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT), 8, 8),
+            ]
+        );
+    }
+}
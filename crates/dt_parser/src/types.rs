@@ -1,6 +1,7 @@
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SymbolDependency {
     pub canonical_path: String,
     pub local_variable_table: HashMap<String, ModuleScopedVariable>,
@@ -9,25 +10,25 @@ pub struct SymbolDependency {
     pub re_export_star_from: Option<Vec<String>>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct ModuleScopedVariable {
     pub depend_on: Option<Vec<String>>,
     pub import_from: Option<FromOtherModule>,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum ModuleExport {
     Local(String),
     ReExportFrom(FromOtherModule),
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub struct FromOtherModule {
     pub from: String,
     pub from_type: FromType,
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum FromType {
     // Used in those cases:
     // - import { A } from 'some-module'
@@ -47,3 +48,19 @@ pub enum FromType {
     // - export * as A from 'some-module'
     Namespace,
 }
+
+// The lines (1-based, inclusive) where a module-scoped symbol is declared, imported or
+// exported, see `collect_symbol_spans()`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolSpan {
+    pub symbol: SpannedSymbol,
+    pub start_line: usize,
+    pub end_line: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum SpannedSymbol {
+    LocalVariable(String),
+    NamedExport(String),
+    DefaultExport,
+}
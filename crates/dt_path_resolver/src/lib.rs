@@ -11,6 +11,23 @@ impl ToCanonicalString for PathBuf {
     }
 }
 
+// The module paths stored in the graphs and outputs are relative to the project root,
+// so they are the same wherever the project is. Paths outside of the project root are
+// kept as they are.
+pub fn to_relative_path(project_root: &str, path: &str) -> String {
+    match Path::new(path).strip_prefix(project_root) {
+        Ok(relative_path) => relative_path.to_string_lossy().to_string(),
+        Err(_) => path.to_string(),
+    }
+}
+
+pub fn to_absolute_path(project_root: &str, path: &str) -> String {
+    Path::new(project_root)
+        .join(path)
+        .to_string_lossy()
+        .to_string()
+}
+
 #[derive(Debug)]
 pub struct PathResolver {
     base_url: String,
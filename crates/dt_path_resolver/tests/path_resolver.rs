@@ -1,4 +1,4 @@
-use dt_path_resolver::{PathResolver, ToCanonicalString};
+use dt_path_resolver::{to_absolute_path, to_relative_path, PathResolver, ToCanonicalString};
 use std::path::PathBuf;
 
 macro_rules! assert_resolved {
@@ -39,3 +39,24 @@ fn resolve_alias_path() {
         ("tests/fixture/index.js", "tsx/a")      => "tests/fixture/tsx/a.tsx",
     );
 }
+
+#[test]
+fn relative_path() {
+    assert_eq!(
+        to_relative_path("/project", "/project/src/a.js"),
+        "src/a.js"
+    );
+    assert_eq!(
+        to_relative_path("/project", "/project-b/a.js"),
+        "/project-b/a.js"
+    );
+    assert_eq!(to_relative_path("/project", "react"), "react");
+    assert_eq!(
+        to_absolute_path("/project", "src/a.js"),
+        "/project/src/a.js"
+    );
+    assert_eq!(
+        to_absolute_path("/project", "/project/src/a.js"),
+        "/project/src/a.js"
+    );
+}
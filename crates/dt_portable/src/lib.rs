@@ -1,9 +1,25 @@
-use dt_graph::used_by_graph::UsedByGraph;
+use anyhow::Context;
+use dt_graph::{
+    binary::{is_binary, Decoder, Encoder},
+    used_by_graph::UsedByGraph,
+};
 use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 
-#[derive(Debug, Serialize, Deserialize)]
+const BINARY_MAGIC: &[u8; 4] = b"DTPB";
+
+// JSON is readable for debugging, the binary format is much smaller and faster to load
+// for large projects, see `dt_graph::binary`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Json,
+    Binary,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
 pub struct Portable {
+    // metadata only, the module paths below are relative to it so the portable can be
+    // used wherever the project is
     pub project_root: String,
     pub translation_json: HashMap<String, String>,
 
@@ -62,4 +78,104 @@ impl Portable {
     pub fn import(exported: &str) -> anyhow::Result<Self> {
         Ok(serde_json::from_str(exported)?)
     }
+
+    pub fn export_as(&self, format: Format) -> anyhow::Result<Vec<u8>> {
+        match format {
+            Format::Json => Ok(self.export()?.into_bytes()),
+            Format::Binary => self.export_binary(),
+        }
+    }
+
+    pub fn export_binary(&self) -> anyhow::Result<Vec<u8>> {
+        let mut encoder = Encoder::new();
+        encoder.write_str(&self.project_root);
+        encoder.write_len(self.translation_json.len());
+        for (key, value) in self.translation_json.iter() {
+            encoder.write_str(key);
+            encoder.write_str(value);
+        }
+        encoder.write_len(self.i18n_to_symbol.len());
+        for (i18n_key, module_to_symbols) in self.i18n_to_symbol.iter() {
+            encoder.write_str(i18n_key);
+            encoder.write_len(module_to_symbols.len());
+            for (module_path, symbols) in module_to_symbols.iter() {
+                encoder.write_str(module_path);
+                encoder.write_len(symbols.len());
+                for symbol in symbols.iter() {
+                    encoder.write_str(symbol);
+                }
+            }
+        }
+        encoder.write_len(self.symbol_to_route.len());
+        for (module_path, symbol_to_routes) in self.symbol_to_route.iter() {
+            encoder.write_str(module_path);
+            encoder.write_len(symbol_to_routes.len());
+            for (symbol, routes) in symbol_to_routes.iter() {
+                encoder.write_str(symbol);
+                encoder.write_len(routes.len());
+                for route in routes.iter() {
+                    encoder.write_str(route);
+                }
+            }
+        }
+        self.used_by_graph.encode(&mut encoder)?;
+        Ok(encoder.finish(BINARY_MAGIC))
+    }
+
+    pub fn import_binary(exported: &[u8]) -> anyhow::Result<Self> {
+        let mut decoder = Decoder::new(exported, BINARY_MAGIC)?;
+        let project_root = decoder.read_string()?;
+        let mut translation_json = HashMap::new();
+        for _ in 0..decoder.read_len()? {
+            translation_json.insert(decoder.read_string()?, decoder.read_string()?);
+        }
+        let mut i18n_to_symbol = HashMap::new();
+        for _ in 0..decoder.read_len()? {
+            let i18n_key = decoder.read_string()?;
+            let mut module_to_symbols = HashMap::new();
+            for _ in 0..decoder.read_len()? {
+                let module_path = decoder.read_string()?;
+                let mut symbols = HashSet::new();
+                for _ in 0..decoder.read_len()? {
+                    symbols.insert(decoder.read_string()?);
+                }
+                module_to_symbols.insert(module_path, symbols);
+            }
+            i18n_to_symbol.insert(i18n_key, module_to_symbols);
+        }
+        let mut symbol_to_route = HashMap::new();
+        for _ in 0..decoder.read_len()? {
+            let module_path = decoder.read_string()?;
+            let mut symbol_to_routes = HashMap::new();
+            for _ in 0..decoder.read_len()? {
+                let symbol = decoder.read_string()?;
+                let mut routes = vec![];
+                for _ in 0..decoder.read_len()? {
+                    routes.push(decoder.read_string()?);
+                }
+                symbol_to_routes.insert(symbol, routes);
+            }
+            symbol_to_route.insert(module_path, symbol_to_routes);
+        }
+        let used_by_graph = UsedByGraph::decode(&mut decoder)?;
+        decoder.finish()?;
+        Ok(Self::new(
+            project_root,
+            translation_json,
+            i18n_to_symbol,
+            symbol_to_route,
+            used_by_graph,
+        ))
+    }
+
+    // Import the portable in either format.
+    pub fn import_bytes(exported: &[u8]) -> anyhow::Result<Self> {
+        match is_binary(exported, BINARY_MAGIC) {
+            true => Self::import_binary(exported).context("import binary portable"),
+            false => {
+                let exported = std::str::from_utf8(exported).context("portable is not utf-8")?;
+                Self::import(exported).context("import json portable")
+            }
+        }
+    }
 }
@@ -0,0 +1,94 @@
+use dt_graph::used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType};
+use dt_portable::{Format, Portable};
+use std::collections::{HashMap, HashSet};
+
+macro_rules! s {
+    ($s:expr) => {{
+        $s.to_string()
+    }};
+}
+
+fn create_portable() -> Portable {
+    // title.js:
+    //     export const Title = () => t('i18n.title');
+    // routes.js:
+    //     import { Title } from './title';
+    //     export default [{ path: '/home', component: Title }];
+    let title = Module {
+        local_variable_table: HashMap::from([(
+            s!("Title"),
+            Some(vec![UsedBy::Itself(UsedByType::NamedExport(s!("Title")))]),
+        )]),
+        named_export_table: HashMap::from([(
+            s!("Title"),
+            Some(vec![UsedBy::Other(UsedByOther {
+                by: s!("/project/routes.js"),
+                by_type: UsedByType::LocalVar(s!("Title")),
+            })]),
+        )]),
+        default_export: None,
+    };
+    let routes = Module {
+        local_variable_table: HashMap::from([
+            (
+                s!("Title"),
+                Some(vec![UsedBy::Itself(UsedByType::LocalVar(s!("routes")))]),
+            ),
+            (
+                s!("routes"),
+                Some(vec![UsedBy::Itself(UsedByType::DefaultExport)]),
+            ),
+        ]),
+        named_export_table: HashMap::new(),
+        default_export: None,
+    };
+    Portable::new(
+        s!("/project"),
+        HashMap::from([(s!("i18n.title"), s!("Title"))]),
+        HashMap::from([(
+            s!("i18n.title"),
+            HashMap::from([(s!("/project/title.js"), HashSet::from([s!("Title")]))]),
+        )]),
+        HashMap::from([(
+            s!("/project/routes.js"),
+            HashMap::from([(s!("routes"), vec![s!("/home")])]),
+        )]),
+        UsedByGraph {
+            modules: HashMap::from([
+                (s!("/project/title.js"), title),
+                (s!("/project/routes.js"), routes),
+            ]),
+        },
+    )
+}
+
+#[test]
+fn binary_round_trip() {
+    let portable = create_portable();
+    let exported = portable.export_binary().unwrap();
+    assert_eq!(Portable::import_binary(&exported).unwrap(), portable);
+
+    // the module paths are stored once
+    let exported_json = portable.export().unwrap();
+    assert!(exported.len() < exported_json.len());
+}
+
+#[test]
+fn import_detects_format() {
+    let portable = create_portable();
+    for format in [Format::Json, Format::Binary] {
+        let exported = portable.export_as(format).unwrap();
+        assert_eq!(Portable::import_bytes(&exported).unwrap(), portable);
+    }
+}
+
+#[test]
+fn invalid_binary() {
+    let mut exported = create_portable().export_binary().unwrap();
+    // the data is truncated
+    assert!(Portable::import_binary(&exported[..exported.len() - 1]).is_err());
+    // the version is right after the magic
+    exported[4] = 0xff;
+    assert!(Portable::import_binary(&exported).is_err());
+    assert!(Portable::import_binary(b"{}").is_err());
+}
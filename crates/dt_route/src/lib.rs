@@ -2,6 +2,7 @@ use anyhow::bail;
 use dt_parser::{
     anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT, types::SymbolDependency,
 };
+use serde::{Deserialize, Serialize};
 use std::collections::{HashMap, HashSet};
 use swc_core::ecma::{
     ast::*,
@@ -83,6 +84,13 @@ impl SymbolToRoutes {
         Ok(())
     }
 
+    // Add the routes collected by `collect_route_dependency()` before, e.g. from the cache.
+    pub fn add_route_usage(&mut self, module_path: &str, routes: &Vec<Route>) {
+        if !routes.is_empty() {
+            self.aggregate(module_path, routes);
+        }
+    }
+
     fn aggregate(&mut self, module_path: &str, routes: &Vec<Route>) {
         let mut map = HashMap::new();
         for route in routes {
@@ -98,7 +106,7 @@ impl SymbolToRoutes {
     }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Route {
     pub path: String,
     pub depend_on: HashSet<String>,
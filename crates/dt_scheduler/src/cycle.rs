@@ -0,0 +1,191 @@
+use std::{
+    collections::{HashMap, HashSet, VecDeque},
+    fmt,
+    path::PathBuf,
+};
+
+// A chain of modules where each one does wildcard export or namespace import from
+// the next one, and the last one does it from the first one.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlockedCycle {
+    pub chain: Vec<PathBuf>,
+}
+
+impl fmt::Display for BlockedCycle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for path in self.chain.iter() {
+            write!(f, "{} -> ", path.display())?;
+        }
+        match self.chain.first() {
+            Some(first) => write!(f, "{}", first.display()),
+            None => Ok(()),
+        }
+    }
+}
+
+// `blocked_by` is the graph among the blocked candidates, a -> [b, c] means a is
+// blocked by b and c. Every strongly connected component with more than one module
+// (or a module blocked by itself) is a cycle that can never be unblocked.
+pub fn find_blocked_cycles(blocked_by: &HashMap<PathBuf, Vec<PathBuf>>) -> Vec<BlockedCycle> {
+    let mut tarjan = Tarjan {
+        graph: blocked_by,
+        index: 0,
+        indices: HashMap::new(),
+        low_links: HashMap::new(),
+        stack: vec![],
+        on_stack: HashSet::new(),
+        components: vec![],
+    };
+    let mut nodes: Vec<&PathBuf> = blocked_by.keys().collect();
+    nodes.sort();
+    for node in nodes {
+        if !tarjan.indices.contains_key(node) {
+            tarjan.strong_connect(node);
+        }
+    }
+
+    let mut cycles = vec![];
+    for component in tarjan.components.iter() {
+        let is_self_blocked = component.len() == 1
+            && blocked_by
+                .get(component[0])
+                .is_some_and(|next| next.contains(component[0]));
+        if component.len() > 1 || is_self_blocked {
+            cycles.push(BlockedCycle {
+                chain: get_chain(blocked_by, component),
+            });
+        }
+    }
+    cycles.sort_by(|a, b| a.chain.cmp(&b.chain));
+    cycles
+}
+
+// Find the shortest chain that starts from the smallest module of the component
+// and goes back to it.
+fn get_chain(
+    blocked_by: &HashMap<PathBuf, Vec<PathBuf>>,
+    component: &Vec<&PathBuf>,
+) -> Vec<PathBuf> {
+    let members: HashSet<&PathBuf> = component.iter().copied().collect();
+    let start = *component.iter().min().unwrap();
+
+    let mut parents: HashMap<&PathBuf, &PathBuf> = HashMap::new();
+    let mut queue: VecDeque<&PathBuf> = VecDeque::from([start]);
+    while let Some(current) = queue.pop_front() {
+        for next in blocked_by.get(current).into_iter().flatten() {
+            if !members.contains(next) {
+                continue;
+            }
+            if next == start {
+                let mut chain = vec![current.clone()];
+                let mut node = current;
+                while node != start {
+                    node = parents.get(node).unwrap();
+                    chain.push(node.clone());
+                }
+                chain.reverse();
+                return chain;
+            }
+            if !parents.contains_key(next) {
+                parents.insert(next, current);
+                queue.push_back(next);
+            }
+        }
+    }
+    unreachable!("strongly connected component without a cycle")
+}
+
+struct Tarjan<'g> {
+    graph: &'g HashMap<PathBuf, Vec<PathBuf>>,
+    index: usize,
+    indices: HashMap<&'g PathBuf, usize>,
+    low_links: HashMap<&'g PathBuf, usize>,
+    stack: Vec<&'g PathBuf>,
+    on_stack: HashSet<&'g PathBuf>,
+    components: Vec<Vec<&'g PathBuf>>,
+}
+
+impl<'g> Tarjan<'g> {
+    fn strong_connect(&mut self, node: &'g PathBuf) {
+        self.indices.insert(node, self.index);
+        self.low_links.insert(node, self.index);
+        self.index += 1;
+        self.stack.push(node);
+        self.on_stack.insert(node);
+
+        for next in self.graph.get(node).into_iter().flatten() {
+            if !self.graph.contains_key(next) {
+                // not blocked anymore
+                continue;
+            }
+            if !self.indices.contains_key(next) {
+                self.strong_connect(next);
+                let low_link = self.low_links[node].min(self.low_links[next]);
+                self.low_links.insert(node, low_link);
+            } else if self.on_stack.contains(next) {
+                let low_link = self.low_links[node].min(self.indices[next]);
+                self.low_links.insert(node, low_link);
+            }
+        }
+
+        if self.low_links[node] == self.indices[node] {
+            let mut component = vec![];
+            loop {
+                let member = self.stack.pop().unwrap();
+                self.on_stack.remove(member);
+                component.push(member);
+                if member == node {
+                    break;
+                }
+            }
+            self.components.push(component);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    macro_rules! p {
+        ($s:expr) => {{
+            PathBuf::from($s)
+        }};
+    }
+
+    #[test]
+    fn no_cycle() {
+        let blocked_by = HashMap::from([(p!("a"), vec![p!("b")]), (p!("b"), vec![p!("c")])]);
+        assert_eq!(find_blocked_cycles(&blocked_by), vec![]);
+    }
+
+    #[test]
+    fn cycles() {
+        let blocked_by = HashMap::from([
+            (p!("a"), vec![p!("b")]),
+            (p!("b"), vec![p!("c"), p!("x")]),
+            (p!("c"), vec![p!("a")]),
+            (p!("d"), vec![p!("a"), p!("d")]),
+            (p!("e"), vec![p!("f")]),
+            (p!("f"), vec![p!("e")]),
+        ]);
+        assert_eq!(
+            find_blocked_cycles(&blocked_by),
+            vec![
+                BlockedCycle {
+                    chain: vec![p!("a"), p!("b"), p!("c")]
+                },
+                BlockedCycle {
+                    chain: vec![p!("d")]
+                },
+                BlockedCycle {
+                    chain: vec![p!("e"), p!("f")]
+                },
+            ]
+        );
+        assert_eq!(
+            find_blocked_cycles(&blocked_by)[0].to_string(),
+            "a -> b -> c -> a"
+        );
+    }
+}
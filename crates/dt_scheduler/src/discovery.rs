@@ -0,0 +1,163 @@
+use anyhow::Context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use ignore::WalkBuilder;
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+#[derive(Debug, Clone)]
+pub struct DiscoveryOptions {
+    // globs relative to the root, only the matched files are collected
+    pub include: Vec<String>,
+
+    // globs relative to the root, the matched files are skipped and the matched
+    // directories won't be walked into
+    pub exclude: Vec<String>,
+
+    // respect `.gitignore`, `.ignore` and `.git/info/exclude`
+    pub respect_ignore_files: bool,
+
+    // skip the files and directories whose name starts with "."
+    pub skip_hidden: bool,
+
+    // symlink loops are detected and skipped
+    pub follow_symlinks: bool,
+}
+
+impl Default for DiscoveryOptions {
+    fn default() -> Self {
+        Self {
+            include: ["**/*.js", "**/*.jsx", "**/*.ts", "**/*.tsx"]
+                .map(String::from)
+                .to_vec(),
+            exclude: [
+                "**/*.spec.js",
+                "**/*.spec.jsx",
+                "**/*.spec.ts",
+                "**/*.spec.tsx",
+                "**/*.test.js",
+                "**/*.test.jsx",
+                "**/*.test.ts",
+                "**/*.test.tsx",
+                "**/node_modules",
+                "**/dist",
+                "**/coverage",
+            ]
+            .map(String::from)
+            .to_vec(),
+            respect_ignore_files: true,
+            skip_hidden: true,
+            follow_symlinks: true,
+        }
+    }
+}
+
+fn build_glob_set(globs: &[String]) -> anyhow::Result<GlobSet> {
+    let mut builder = GlobSetBuilder::new();
+    for glob in globs.iter() {
+        builder.add(Glob::new(glob).context(format!("invalid glob {}", glob))?);
+    }
+    Ok(builder.build()?)
+}
+
+/// Tells whether a path under the root would be discovered, e.g. for the paths
+/// reported by a file watcher. Ignore files and symlinks are not considered.
+pub struct ModuleMatcher {
+    root: PathBuf,
+    include: GlobSet,
+    exclude: GlobSet,
+    skip_hidden: bool,
+}
+
+impl ModuleMatcher {
+    pub fn new(root: &str, options: &DiscoveryOptions) -> anyhow::Result<Self> {
+        Ok(Self {
+            root: PathBuf::from(root),
+            include: build_glob_set(&options.include)?,
+            exclude: build_glob_set(&options.exclude)?,
+            skip_hidden: options.skip_hidden,
+        })
+    }
+
+    pub fn is_match(&self, path: &Path) -> bool {
+        match path.strip_prefix(&self.root) {
+            Ok(relative_path) => self.is_walked(path) && self.include.is_match(relative_path),
+            Err(_) => false,
+        }
+    }
+
+    /// Tells whether a path under the root would be walked into, e.g. a directory
+    /// holding some modules.
+    pub fn is_walked(&self, path: &Path) -> bool {
+        let relative_path = match path.strip_prefix(&self.root) {
+            Ok(relative_path) => relative_path,
+            Err(_) => return false,
+        };
+        if self.skip_hidden
+            && relative_path
+                .components()
+                .any(|component| component.as_os_str().to_string_lossy().starts_with('.'))
+        {
+            return false;
+        }
+        // the excluded directories are not walked into
+        if relative_path
+            .ancestors()
+            .any(|ancestor| !ancestor.as_os_str().is_empty() && self.exclude.is_match(ancestor))
+        {
+            return false;
+        }
+        true
+    }
+}
+
+/// Walks the root and returns the canonical paths of the modules to parse.
+/// Unreadable entries are skipped.
+pub fn discover_modules(root: &str, options: &DiscoveryOptions) -> anyhow::Result<Vec<PathBuf>> {
+    let include = build_glob_set(&options.include)?;
+    let exclude = build_glob_set(&options.exclude)?;
+
+    let root = PathBuf::from(root);
+    let mut walk_builder = WalkBuilder::new(&root);
+    walk_builder
+        .hidden(options.skip_hidden)
+        .follow_links(options.follow_symlinks)
+        .ignore(options.respect_ignore_files)
+        .git_ignore(options.respect_ignore_files)
+        .git_exclude(options.respect_ignore_files)
+        .parents(options.respect_ignore_files)
+        .git_global(false)
+        .require_git(false);
+    {
+        let root = root.clone();
+        walk_builder.filter_entry(move |entry| match entry.path().strip_prefix(&root) {
+            Ok(relative_path) => !exclude.is_match(relative_path),
+            Err(_) => true,
+        });
+    }
+
+    let mut modules = vec![];
+    let mut collected: HashSet<PathBuf> = HashSet::new();
+    for entry in walk_builder.build() {
+        let entry = match entry {
+            Ok(entry) => entry,
+            Err(_) => continue,
+        };
+        if !entry.path().is_file() {
+            continue;
+        }
+        let relative_path = entry.path().strip_prefix(&root).unwrap_or(entry.path());
+        if !include.is_match(relative_path) {
+            continue;
+        }
+        // the same module can be reached from different symlinks
+        if let Ok(canonical_path) = entry.path().canonicalize() {
+            if collected.insert(canonical_path.clone()) {
+                modules.push(canonical_path);
+            }
+        }
+    }
+
+    Ok(modules)
+}
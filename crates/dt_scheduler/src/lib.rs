@@ -1,3 +1,8 @@
+mod cycle;
+mod discovery;
+
+pub use cycle::BlockedCycle;
+pub use discovery::{discover_modules, DiscoveryOptions, ModuleMatcher};
 use dt_path_resolver::{PathResolver, ToCanonicalString};
 use std::{
     collections::{HashMap, HashSet, VecDeque},
@@ -30,7 +35,12 @@ pub struct ParserCandidateScheduler {
 
 impl ParserCandidateScheduler {
     pub fn new(root: &str) -> Self {
-        let paths = Self::collect_paths(&PathBuf::from(root));
+        Self::with_options(root, &DiscoveryOptions::default())
+            .expect("default discovery options should be valid")
+    }
+
+    pub fn with_options(root: &str, options: &DiscoveryOptions) -> anyhow::Result<Self> {
+        let paths = discover_modules(root, options)?;
 
         let mut scheduler = Self {
             good_candidates: VecDeque::new(),
@@ -39,31 +49,30 @@ impl ParserCandidateScheduler {
         };
 
         let path_resolver = PathResolver::new(root);
+        let candidates: HashSet<&PathBuf> = paths.iter().collect();
 
         for path in paths.iter() {
-            if Self::is_valid_path(path) {
-                match Self::get_blocked_by(path, &path_resolver) {
-                    Some(blocked_by_vec) => {
-                        scheduler
-                            .blocked_candidates
-                            .insert(path.clone(), blocked_by_vec.len());
-                        for blocked_by in blocked_by_vec.iter() {
-                            if !scheduler.blocking_table.contains_key(blocked_by) {
-                                scheduler.blocking_table.insert(blocked_by.clone(), vec![]);
-                            }
-                            scheduler
-                                .blocking_table
-                                .get_mut(blocked_by)
-                                .unwrap()
-                                .push(path.clone());
+            match Self::get_blocked_by(path, &path_resolver, &candidates) {
+                Some(blocked_by_vec) => {
+                    scheduler
+                        .blocked_candidates
+                        .insert(path.clone(), blocked_by_vec.len());
+                    for blocked_by in blocked_by_vec.iter() {
+                        if !scheduler.blocking_table.contains_key(blocked_by) {
+                            scheduler.blocking_table.insert(blocked_by.clone(), vec![]);
                         }
+                        scheduler
+                            .blocking_table
+                            .get_mut(blocked_by)
+                            .unwrap()
+                            .push(path.clone());
                     }
-                    None => scheduler.good_candidates.push_back(path.clone()),
                 }
+                None => scheduler.good_candidates.push_back(path.clone()),
             }
         }
 
-        scheduler
+        Ok(scheduler)
     }
 
     pub fn get_total_remaining_candidate_count(&self) -> usize {
@@ -95,56 +104,55 @@ impl ParserCandidateScheduler {
         }
     }
 
-    fn get_blocked_by(path: &PathBuf, path_resolver: &PathResolver) -> Option<Vec<PathBuf>> {
-        let blocked_by = BlockedByVisitor::get_blocked_by(path, &path_resolver);
-        match blocked_by.len() {
-            0 => None,
-            _ => Some(blocked_by.into_iter().collect()),
-        }
-    }
-
-    fn is_valid_path(path: &PathBuf) -> bool {
-        if !path.is_file() {
-            return false;
-        }
-        let path_str = path.to_str().expect(&format!("to_str() for {:?}", path));
-        if path_str.ends_with(".js")
-            || path_str.ends_with(".jsx")
-            || path_str.ends_with(".ts")
-            || path_str.ends_with(".tsx")
-        {
-            if !path_str.ends_with(".spec.js")
-                && !path_str.ends_with(".spec.jsx")
-                && !path_str.ends_with(".spec.ts")
-                && !path_str.ends_with(".spec.tsx")
-                && !path_str.ends_with(".test.js")
-                && !path_str.ends_with(".test.jsx")
-                && !path_str.ends_with(".test.ts")
-                && !path_str.ends_with(".test.tsx")
-            {
-                return true;
+    /// Returns the cycles among the blocked candidates. Candidates in a cycle (and the
+    /// ones blocked by them) will never be returned by `get_one_candidate`.
+    pub fn get_blocked_cycles(&self) -> Vec<BlockedCycle> {
+        let mut blocked_by: HashMap<Candidate, Vec<Candidate>> = self
+            .blocked_candidates
+            .keys()
+            .map(|candidate| (candidate.clone(), vec![]))
+            .collect();
+        for (blocking, blocked_list) in self.blocking_table.iter() {
+            if !self.blocked_candidates.contains_key(blocking) {
+                continue;
+            }
+            for blocked in blocked_list.iter() {
+                if let Some(blocked_by_vec) = blocked_by.get_mut(blocked) {
+                    blocked_by_vec.push(blocking.clone());
+                }
             }
         }
-        false
+        cycle::find_blocked_cycles(&blocked_by)
     }
 
-    fn collect_paths(path: &PathBuf) -> Vec<PathBuf> {
-        let mut files = vec![];
+    /// Fallback for circular wildcard exports and namespace imports. Unblock the first
+    /// candidate of the first cycle so it can be parsed before the modules it depends on,
+    /// the wildcard exports of that candidate are expanded partially.
+    ///
+    /// Returns the broken cycle, or `None` if there is no cycle.
+    pub fn break_cycle(&mut self) -> Option<BlockedCycle> {
+        let cycle = self.get_blocked_cycles().into_iter().next()?;
+        let candidate = cycle.chain[0].clone();
+        self.blocked_candidates.remove(&candidate);
+        self.good_candidates.push_back(candidate);
+        Some(cycle)
+    }
 
-        match path.is_dir() {
-            true => {
-                for entry in path.read_dir().unwrap() {
-                    if let Ok(entry) = entry {
-                        files.append(&mut Self::collect_paths(
-                            &entry.path().canonicalize().unwrap(),
-                        ));
-                    }
-                }
-            }
-            false => files.push(path.clone()),
+    fn get_blocked_by(
+        path: &PathBuf,
+        path_resolver: &PathResolver,
+        candidates: &HashSet<&PathBuf>,
+    ) -> Option<Vec<PathBuf>> {
+        let blocked_by = BlockedByVisitor::get_blocked_by(path, &path_resolver);
+        // A module that will never be parsed can't block others.
+        let blocked_by: Vec<PathBuf> = blocked_by
+            .into_iter()
+            .filter(|blocked_by| candidates.contains(blocked_by))
+            .collect();
+        match blocked_by.len() {
+            0 => None,
+            _ => Some(blocked_by),
         }
-
-        files
     }
 }
 
@@ -0,0 +1,95 @@
+use dt_scheduler::{discover_modules, DiscoveryOptions, ModuleMatcher};
+use std::{
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+fn discover(options: &DiscoveryOptions) -> HashSet<PathBuf> {
+    let modules = discover_modules("tests/fixture-discovery", options).unwrap();
+    let count = modules.len();
+    let modules: HashSet<PathBuf> = modules.into_iter().collect();
+    assert_eq!(modules.len(), count, "duplicated modules");
+    modules
+}
+
+fn canonicalize<const N: usize>(paths: [&str; N]) -> HashSet<PathBuf> {
+    paths
+        .map(|s| {
+            PathBuf::from("tests/fixture-discovery")
+                .join(s)
+                .canonicalize()
+                .unwrap()
+        })
+        .into_iter()
+        .collect()
+}
+
+#[test]
+fn default_options() {
+    // - tests, `node_modules` and `dist` are excluded
+    // - `.storybook` is hidden
+    // - `generated` is ignored by `.ignore`
+    // - `src/components/loop` is a symlink loop
+    assert_eq!(
+        discover(&DiscoveryOptions::default()),
+        canonicalize(["src/a.js", "src/components/b.tsx"])
+    );
+}
+
+#[test]
+fn include_and_exclude() {
+    let options = DiscoveryOptions {
+        include: vec![String::from("src/**/*.tsx")],
+        exclude: vec![],
+        ..Default::default()
+    };
+    assert_eq!(
+        discover(&options),
+        canonicalize(["src/components/b.tsx", "src/components/b.test.tsx"])
+    );
+}
+
+#[test]
+fn hidden_and_ignored() {
+    let options = DiscoveryOptions {
+        respect_ignore_files: false,
+        skip_hidden: false,
+        ..Default::default()
+    };
+    // `generated/link.js` is a symlink to `src/a.js`
+    assert_eq!(
+        discover(&options),
+        canonicalize([
+            "src/a.js",
+            "src/components/b.tsx",
+            ".storybook/main.js",
+            "generated/c.js",
+        ])
+    );
+}
+
+#[test]
+fn invalid_glob() {
+    let options = DiscoveryOptions {
+        include: vec![String::from("src/[a")],
+        ..Default::default()
+    };
+    assert!(discover_modules("tests/fixture-discovery", &options).is_err());
+}
+
+#[test]
+fn module_matcher() {
+    let matcher = ModuleMatcher::new("/root", &DiscoveryOptions::default()).unwrap();
+    assert!(matcher.is_match(Path::new("/root/src/a.js")));
+    // the deleted modules can be matched too
+    assert!(matcher.is_match(Path::new("/root/src/deleted.tsx")));
+    assert!(!matcher.is_match(Path::new("/root/src/README.md")));
+    assert!(!matcher.is_match(Path::new("/root/src/b.test.tsx")));
+    assert!(!matcher.is_match(Path::new("/root/node_modules/kirby/index.js")));
+    assert!(!matcher.is_match(Path::new("/root/.storybook/main.js")));
+    assert!(!matcher.is_match(Path::new("/another/src/a.js")));
+    // the directories of the modules
+    assert!(matcher.is_walked(Path::new("/root/src")));
+    assert!(!matcher.is_walked(Path::new("/root/node_modules/kirby")));
+    assert!(!matcher.is_walked(Path::new("/root/.git/objects")));
+}
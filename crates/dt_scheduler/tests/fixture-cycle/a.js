@@ -0,0 +1,3 @@
+export * from "./b";
+
+export const A = "A";
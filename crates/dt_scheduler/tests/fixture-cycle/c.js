@@ -0,0 +1,3 @@
+import * as A from "./a";
+
+export const C = A;
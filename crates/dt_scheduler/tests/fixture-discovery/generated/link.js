@@ -0,0 +1 @@
+../src/a.js
\ No newline at end of file
@@ -0,0 +1,3 @@
+import { A } from "../a";
+
+test("A", () => {});
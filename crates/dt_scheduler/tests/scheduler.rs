@@ -59,3 +59,40 @@ fn topological_order() {
     }
     assert_eq!(scheduler.get_total_remaining_candidate_count(), 0);
 }
+
+#[test]
+fn blocked_cycles() {
+    let root = "tests/fixture-cycle";
+    let mut scheduler = ParserCandidateScheduler::new(root);
+
+    let [a, b, c, d] = ["a.js", "b.js", "c.js", "d.js"]
+        .map(|s| PathBuf::from(root).join(s).canonicalize().unwrap());
+
+    // `a.js` and `b.js` do wildcard exports from each other, `c.js` is blocked by `a.js`
+    assert_eq!(scheduler.get_one_candidate(), Some(d.clone()));
+    scheduler.mark_candidate_as_parsed(d);
+    assert_eq!(scheduler.get_one_candidate(), None);
+    assert_eq!(scheduler.get_total_remaining_candidate_count(), 3);
+
+    let cycles = scheduler.get_blocked_cycles();
+    assert_eq!(cycles.len(), 1);
+    assert_eq!(cycles[0].chain, vec![a.clone(), b.clone()]);
+    assert_eq!(
+        cycles[0].to_string(),
+        format!("{} -> {} -> {}", a.display(), b.display(), a.display())
+    );
+
+    assert_eq!(scheduler.break_cycle(), Some(cycles[0].clone()));
+    assert_eq!(scheduler.get_blocked_cycles(), vec![]);
+    assert_eq!(scheduler.get_one_candidate(), Some(a.clone()));
+    scheduler.mark_candidate_as_parsed(a);
+
+    let mut not_parsed = HashSet::from([b, c]);
+    while let Some(candidate) = scheduler.get_one_candidate() {
+        assert!(not_parsed.remove(&candidate));
+        scheduler.mark_candidate_as_parsed(candidate);
+    }
+    assert_eq!(not_parsed.len(), 0);
+    assert_eq!(scheduler.get_total_remaining_candidate_count(), 0);
+    assert_eq!(scheduler.break_cycle(), None);
+}
@@ -1,132 +1,497 @@
-use crate::TraceTarget;
-
-use super::ModuleSymbol;
+use super::{
+    store::GraphStore,
+    tracer::{EdgeLookup, Tracer},
+    ModuleSymbol, MultiTraceGraph, TraceGraph, TraceLimits, TraceResult, TraceTarget,
+};
 use anyhow::Context;
 use dt_database::{models, SqliteDb};
-use std::collections::HashMap;
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet},
+};
+
+// The `symbol_dependency` table can be walked in both directions, `Direction` tells
+// `DbLookup` which one to follow.
+enum Direction {
+    UsedBy,
+    DependOn,
+}
 
-pub struct DependencyTracker<'db> {
-    cache: HashMap<ModuleSymbol, Vec<Vec<ModuleSymbol>>>,
+struct DbLookup<'db> {
     db: &'db SqliteDb,
     project: models::Project,
-    trace_full_path_only: bool,
+    direction: Direction,
+    // The edges loaded so far. A symbol is loaded with everything reachable from it in
+    // one recursive query, or everything within the depth the tracer will look up, so a
+    // loaded symbol has all its edges.
+    next: RefCell<HashMap<ModuleSymbol, Vec<ModuleSymbol>>>,
+    // the routes of the loaded symbols, they're loaded with the used-by edges only
+    routes: RefCell<HashMap<ModuleSymbol, Vec<String>>>,
 }
 
-impl<'db> DependencyTracker<'db> {
-    pub fn new(db: &'db SqliteDb, project: models::Project, trace_full_path_only: bool) -> Self {
+impl<'db> DbLookup<'db> {
+    fn new(db: &'db SqliteDb, project: models::Project, direction: Direction) -> Self {
         Self {
-            cache: HashMap::new(),
             db,
             project,
-            trace_full_path_only,
+            direction,
+            next: RefCell::new(HashMap::new()),
+            routes: RefCell::new(HashMap::new()),
         }
     }
 
-    // Current implementation is mimick version of the trace with in-memory graph.
-    // We can refactor it after the database feature gets validated.
-    pub fn trace(&mut self, module_symbol: ModuleSymbol) -> anyhow::Result<Vec<Vec<ModuleSymbol>>> {
-        // Treat routeNmaes specially since they cause a lot of circular dependencies in
-        // some of our codebases. One assumption of this tool is "no circular dependency"
-        // , so let's workaround here for now.
-        if module_symbol.1.to_string() == "routeNames" {
-            return Ok(vec![]);
-        }
-
-        // early return if cached
-        if let Some(cached) = self.cache.get(&module_symbol) {
-            return Ok(cached.clone());
-        }
-
+    fn get_symbol(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<models::Symbol> {
         let module = self
             .project
             .get_module(&self.db.conn, &module_symbol.0)
             .context(format!("module {} not found", module_symbol.0))?;
 
         let symbol = match &module_symbol.1 {
-            crate::TraceTarget::NamedExport(name) => module
+            TraceTarget::NamedExport(name) => module
                 .get_symbol(&self.db.conn, models::SymbolVariant::NamedExport, name)
                 .context(format!(
                     "module {} doesn't have named export symbol {}",
                     module.path, name
                 ))?,
-            crate::TraceTarget::DefaultExport => module
+            TraceTarget::DefaultExport => module
                 .get_symbol(&self.db.conn, models::SymbolVariant::DefaultExport, "")
                 .context(format!(
                     "module {} doesn't have default export symbol",
                     module.path
                 ))?,
-            crate::TraceTarget::LocalVar(name) => module
+            TraceTarget::LocalVar(name) => module
                 .get_symbol(&self.db.conn, models::SymbolVariant::LocalVariable, name)
                 .context(format!(
                     "module {} doesn't have local variable symbol {}",
                     module.path, name
                 ))?,
         };
+        Ok(symbol)
+    }
+
+    fn load(&self, module_symbol: &ModuleSymbol, max_depth: Option<usize>) -> anyhow::Result<()> {
+        let symbol = self.get_symbol(module_symbol)?;
+        let edges = match self.direction {
+            Direction::UsedBy => symbol
+                .get_used_by_recursive(&self.db.conn, max_depth)
+                .context(format!("get used-by edges for symbol {}", symbol.name))?,
+            Direction::DependOn => symbol
+                .get_depend_on_recursive(&self.db.conn, max_depth)
+                .context(format!("get depend-on edges for symbol {}", symbol.name))?,
+        };
 
-        let used_by = symbol
-            .get_used_by(&self.db.conn)
-            .context(format!("get used-by vector for symbol {}", symbol.name))?;
-
-        let mut res: Vec<Vec<ModuleSymbol>> = vec![];
-        for next_target in used_by.iter() {
-            let mut paths = match next_target.module_id == symbol.module_id {
-                true => {
-                    // used by symbol from the same module
-                    match next_target.variant {
-                        models::SymbolVariant::NamedExport => self.trace((
-                            module_symbol.0.clone(),
-                            TraceTarget::NamedExport(next_target.name.to_string()),
-                        ))?,
-                        models::SymbolVariant::DefaultExport => {
-                            self.trace((module_symbol.0.clone(), TraceTarget::DefaultExport))?
+        // the symbols without edges are loaded as well
+        let mut loaded: HashMap<ModuleSymbol, Vec<ModuleSymbol>> =
+            HashMap::from([(module_symbol.clone(), vec![])]);
+        for edge in edges {
+            let to = to_module_symbol(edge.to_module_path, &edge.to);
+            loaded.entry(to.clone()).or_default();
+            loaded
+                .entry(to_module_symbol(edge.from_module_path, &edge.from))
+                .or_default()
+                .push(to);
+        }
+        // The symbols `max_depth` away are the last ones with their edges, the ones after
+        // them are loaded when they're looked up.
+        if let Some(max_depth) = max_depth {
+            let mut within = HashSet::from([module_symbol.clone()]);
+            let mut frontier = vec![module_symbol.clone()];
+            for _ in 0..max_depth {
+                let mut next_frontier = vec![];
+                for current in frontier.iter() {
+                    for next_target in loaded[current].iter() {
+                        if within.insert(next_target.clone()) {
+                            next_frontier.push(next_target.clone());
                         }
-                        models::SymbolVariant::LocalVariable => self.trace((
-                            module_symbol.0.clone(),
-                            TraceTarget::LocalVar(next_target.name.to_string()),
-                        ))?,
                     }
                 }
-                false => {
-                    // used by symbol from other module
-                    let other_module =
-                        models::Module::retrieve_by_id(&self.db.conn, next_target.module_id)?;
-                    match next_target.variant {
-                        models::SymbolVariant::NamedExport => self.trace((
-                            other_module.path.clone(),
-                            TraceTarget::NamedExport(next_target.name.to_string()),
-                        ))?,
-                        models::SymbolVariant::DefaultExport => {
-                            self.trace((other_module.path.clone(), TraceTarget::DefaultExport))?
-                        }
-                        models::SymbolVariant::LocalVariable => self.trace((
-                            other_module.path.clone(),
-                            TraceTarget::LocalVar(next_target.name.to_string()),
-                        ))?,
-                    }
+                frontier = next_frontier;
+            }
+            loaded.retain(|module_symbol, _| within.contains(module_symbol));
+        }
+        if let Direction::UsedBy = self.direction {
+            let symbol_routes = symbol
+                .get_used_by_routes_recursive(&self.db.conn, max_depth)
+                .context(format!("get routes for symbol {}", symbol.name))?;
+            let mut routes = self.routes.borrow_mut();
+            for symbol_route in symbol_routes {
+                let module_symbol =
+                    to_module_symbol(symbol_route.module_path, &symbol_route.symbol);
+                if !loaded.contains_key(&module_symbol)
+                    || self.next.borrow().contains_key(&module_symbol)
+                {
+                    continue;
                 }
-            };
-            res.append(&mut paths);
+                routes
+                    .entry(module_symbol)
+                    .or_default()
+                    .push(symbol_route.route.path);
+            }
+        }
+        // the ones loaded before have all their edges already
+        let mut next = self.next.borrow_mut();
+        for (module_symbol, next_targets) in loaded {
+            next.entry(module_symbol).or_insert(next_targets);
         }
+        Ok(())
+    }
 
-        // append current ModuleSymbol to each path
-        for path in res.iter_mut() {
-            path.push(module_symbol.clone());
+    fn get_routes(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<String>> {
+        if !self.next.borrow().contains_key(module_symbol) {
+            self.load(module_symbol, None)?;
         }
-        if self.trace_full_path_only {
-            // because we only want to trace the full path, we only need to add a new path
-            // when this ModuleSymbol is not using by anyone.
-            if res.len() == 0 {
-                res.push(vec![module_symbol.clone()]);
-            }
-        } else {
-            // always append the current ModuleSymbol since we want to list every single path
-            // that is reachable from the target.
-            res.push(vec![module_symbol.clone()]);
+        Ok(self
+            .routes
+            .borrow()
+            .get(module_symbol)
+            .cloned()
+            .unwrap_or_default())
+    }
+
+    fn get_next_loaded(
+        &self,
+        module_symbol: &ModuleSymbol,
+        max_depth: Option<usize>,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        if !self.next.borrow().contains_key(module_symbol) {
+            self.load(module_symbol, max_depth)?;
+        }
+        Ok(self.next.borrow()[module_symbol].clone())
+    }
+}
+
+fn to_module_symbol(module_path: String, symbol: &models::Symbol) -> ModuleSymbol {
+    let trace_target = match symbol.variant {
+        models::SymbolVariant::NamedExport => TraceTarget::NamedExport(symbol.name.to_string()),
+        models::SymbolVariant::DefaultExport => TraceTarget::DefaultExport,
+        models::SymbolVariant::LocalVariable => TraceTarget::LocalVar(symbol.name.to_string()),
+    };
+    (module_path, trace_target)
+}
+
+impl<'db> EdgeLookup for DbLookup<'db> {
+    fn get_next(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.get_next_loaded(module_symbol, None)
+    }
+
+    fn get_next_within(
+        &self,
+        module_symbol: &ModuleSymbol,
+        depth: usize,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.get_next_loaded(module_symbol, Some(depth))
+    }
+}
+
+// The database as a `GraphStore`, trace it with `super::DependencyTracker`. The used-by
+// edges and the routes of a symbol are loaded with everything reachable from it.
+pub struct DbStore<'db> {
+    lookup: DbLookup<'db>,
+}
+
+impl<'db> DbStore<'db> {
+    pub fn new(db: &'db SqliteDb, project: models::Project) -> Self {
+        Self {
+            lookup: DbLookup::new(db, project, Direction::UsedBy),
         }
+    }
+}
+
+impl<'db> GraphStore for DbStore<'db> {
+    fn modules(&self) -> anyhow::Result<Vec<String>> {
+        let mut module_paths: Vec<String> = self
+            .lookup
+            .project
+            .get_modules(&self.lookup.db.conn)?
+            .into_iter()
+            .map(|module| module.path)
+            .collect();
+        module_paths.sort();
+        Ok(module_paths)
+    }
+
+    fn symbols(&self, module_path: &str) -> anyhow::Result<Vec<TraceTarget>> {
+        let module = self
+            .lookup
+            .project
+            .get_module(&self.lookup.db.conn, module_path)
+            .context(format!("module {} not found", module_path))?;
+        Ok(module
+            .get_symbols(&self.lookup.db.conn)?
+            .iter()
+            .map(|symbol| to_module_symbol(String::new(), symbol).1)
+            .collect())
+    }
+
+    fn used_by(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.lookup.get_next(module_symbol)
+    }
+
+    fn used_by_within(
+        &self,
+        module_symbol: &ModuleSymbol,
+        depth: usize,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.lookup.get_next_within(module_symbol, depth)
+    }
+
+    fn routes(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<String>> {
+        self.lookup.get_routes(module_symbol)
+    }
+
+    fn search_translations(&self, search: &str, exact_match: bool) -> anyhow::Result<Vec<String>> {
+        let mut i18n_keys: Vec<String> = self
+            .lookup
+            .project
+            .search_translation(&self.lookup.db.conn, search, exact_match)?
+            .into_iter()
+            .map(|translation| translation.key)
+            .collect();
+        i18n_keys.sort();
+        Ok(i18n_keys)
+    }
+
+    fn translation_usages(&self, i18n_key: &str) -> anyhow::Result<Vec<ModuleSymbol>> {
+        let translation = self
+            .lookup
+            .project
+            .get_translation(&self.lookup.db.conn, i18n_key)
+            .context(format!("i18n key {} not found", i18n_key))?;
+        let mut usages = vec![];
+        for symbol in translation.get_used_by(&self.lookup.db.conn)? {
+            let module = models::Module::retrieve_by_id(&self.lookup.db.conn, symbol.module_id)?;
+            usages.push(to_module_symbol(module.path, &symbol));
+        }
+        Ok(usages)
+    }
+}
+
+// Trace the symbols the given one depends on, see `super::DependOnTracker`.
+pub struct DependOnTracker<'db> {
+    tracer: Tracer,
+    lookup: DbLookup<'db>,
+}
+
+impl<'db> DependOnTracker<'db> {
+    pub fn new(db: &'db SqliteDb, project: models::Project, trace_full_path_only: bool) -> Self {
+        Self {
+            tracer: Tracer::new(trace_full_path_only),
+            lookup: DbLookup::new(db, project, Direction::DependOn),
+        }
+    }
+
+    // see `super::DependencyTracker::set_max_depth()`
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.tracer.set_max_depth(max_depth);
+    }
+
+    // see `super::DependencyTracker::set_limits()`
+    pub fn set_limits(&mut self, limits: TraceLimits) -> anyhow::Result<()> {
+        self.tracer.set_limits(limits)
+    }
+
+    pub fn trace(&mut self, module_symbol: ModuleSymbol) -> anyhow::Result<TraceResult> {
+        self.tracer.trace(&self.lookup, module_symbol)
+    }
+
+    // see `super::DependencyTracker::trace_graph()`
+    pub fn trace_graph(&self, module_symbol: ModuleSymbol) -> anyhow::Result<TraceGraph> {
+        self.tracer.trace_graph(&self.lookup, module_symbol)
+    }
+
+    // see `super::DependencyTracker::trace_sources()`
+    pub fn trace_sources(
+        &self,
+        module_symbols: Vec<ModuleSymbol>,
+    ) -> anyhow::Result<MultiTraceGraph> {
+        self.tracer.trace_sources(&self.lookup, module_symbols)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DependencyTracker;
+    use dt_database::Database;
+
+    // a.js
+    // const A = 'a'; export { A };
+    //
+    // b.js
+    // import { A } from 'a';
+    // const B = () => { A; C; };
+    // const C = () => { B; };
+    fn create_db() -> anyhow::Result<(SqliteDb, models::Project)> {
+        let db = SqliteDb::open(":memory:")?;
+        db.create_tables()?;
+        let conn = &db.conn;
+        let project = models::Project::create(conn, ".", "default_project")?;
+        let a = project.add_module(conn, "a")?;
+        let a_local = a.add_symbol(conn, models::SymbolVariant::LocalVariable, "A")?;
+        let a_export = a.add_symbol(conn, models::SymbolVariant::NamedExport, "A")?;
+        let b = project.add_module(conn, "b")?;
+        let b_a = b.add_symbol(conn, models::SymbolVariant::LocalVariable, "A")?;
+        let b_b = b.add_symbol(conn, models::SymbolVariant::LocalVariable, "B")?;
+        let b_c = b.add_symbol(conn, models::SymbolVariant::LocalVariable, "C")?;
+        for (symbol, depend_on) in [
+            (&a_export, &a_local),
+            (&b_a, &a_export),
+            (&b_b, &b_a),
+            (&b_b, &b_c),
+            (&b_c, &b_b),
+        ] {
+            models::SymbolDependency::create(conn, symbol, depend_on)?;
+        }
+        let translation = project.add_translation(conn, "i18n.a", "a")?;
+        models::TranslationUsage::create(conn, &translation, &a_local)?;
+        let route = project.add_route(conn, "/b")?;
+        models::RouteUsage::create(conn, &route, &b_b)?;
+        Ok((db, project))
+    }
+
+    #[test]
+    fn trace_with_recursive_queries() {
+        let (db, project) = create_db().unwrap();
+        let a_local = (String::from("a"), TraceTarget::LocalVar(String::from("A")));
+        let a_export = (
+            String::from("a"),
+            TraceTarget::NamedExport(String::from("A")),
+        );
+        let b_a = (String::from("b"), TraceTarget::LocalVar(String::from("A")));
+        let b_b = (String::from("b"), TraceTarget::LocalVar(String::from("B")));
+        let b_c = (String::from("b"), TraceTarget::LocalVar(String::from("C")));
+
+        let store = DbStore::new(&db, project.clone());
+        let dt = DependencyTracker::new(&store, true);
+        let trace_graph = dt.trace_graph(a_local.clone()).unwrap();
+        assert_eq!(
+            trace_graph.nodes,
+            vec![
+                a_local.clone(),
+                a_export.clone(),
+                b_a.clone(),
+                b_b.clone(),
+                b_c.clone()
+            ]
+        );
+        assert_eq!(trace_graph.edges, vec![(0, 1), (1, 2), (2, 3), (3, 4)]);
+        assert_eq!(trace_graph.cycles.len(), 1);
+        // everything reachable is loaded by the first query, with the routes
+        assert_eq!(store.lookup.next.borrow().len(), 5);
+        assert_eq!(store.routes(&b_b).unwrap(), vec![String::from("/b")]);
+        assert_eq!(store.lookup.next.borrow().len(), 5);
+        assert!(dt
+            .trace_graph((String::from("a"), TraceTarget::LocalVar(String::from("X"))))
+            .is_err());
+
+        let dt = DependOnTracker::new(&db, project.clone(), true);
+        let trace_graph = dt.trace_graph(b_b.clone()).unwrap();
+        assert_eq!(
+            trace_graph.nodes,
+            vec![b_b.clone(), b_a, a_export, a_local.clone(), b_c]
+        );
+
+        assert_eq!(
+            store.search_translations("a", true).unwrap(),
+            vec!["i18n.a"]
+        );
+        assert_eq!(
+            store.translation_usages("i18n.a").unwrap(),
+            vec![a_local.clone()]
+        );
+        let translation = project.get_translation(&db.conn, "i18n.a").unwrap();
+        let symbol_routes = translation.get_used_by_routes_recursive(&db.conn).unwrap();
+        assert_eq!(symbol_routes.len(), 1);
+        assert_eq!(symbol_routes[0].module_path, "b");
+        assert_eq!(symbol_routes[0].symbol.name, "B");
+        assert_eq!(symbol_routes[0].route.path, "/b");
+    }
+
+    #[test]
+    fn load_within_max_depth() {
+        let (db, project) = create_db().unwrap();
+        let a_local = (String::from("a"), TraceTarget::LocalVar(String::from("A")));
+        let a_export = (
+            String::from("a"),
+            TraceTarget::NamedExport(String::from("A")),
+        );
+        let b_a = (String::from("b"), TraceTarget::LocalVar(String::from("A")));
+
+        let store = DbStore::new(&db, project.clone());
+        let mut dt = DependencyTracker::new(&store, true);
+        dt.set_max_depth(Some(1));
+        let trace_graph = dt.trace_graph(a_local.clone()).unwrap();
+        assert_eq!(trace_graph.nodes, vec![a_local.clone(), a_export.clone()]);
+        assert_eq!(
+            trace_graph.truncation.max_depth_reached,
+            vec![a_export.clone()]
+        );
+        // the symbols after the depth limit are not loaded
+        assert_eq!(store.lookup.next.borrow().len(), 2);
+
+        // and loaded with their edges when they're looked up
+        dt.set_max_depth(None);
+        let trace_graph = dt.trace_graph(a_local.clone()).unwrap();
+        assert_eq!(trace_graph.nodes.len(), 5);
+        assert_eq!(trace_graph.nodes[..3], [a_local, a_export, b_a]);
+        assert_eq!(trace_graph.cycles.len(), 1);
+        assert_eq!(store.lookup.next.borrow().len(), 5);
+    }
+
+    #[test]
+    fn trace_deep_chain() {
+        // chain.js
+        // const a0 = 0, a1 = a0, a2 = a1, ..., a1999 = a1998;
+        let n = 2000;
+        let a = |i: usize| {
+            (
+                String::from("chain"),
+                TraceTarget::LocalVar(format!("a{}", i)),
+            )
+        };
+
+        // see `crate::tests::trace_deep_chain()`
+        std::thread::Builder::new()
+            .stack_size(128 * 1024)
+            .spawn(move || {
+                let db = SqliteDb::open(":memory:").unwrap();
+                db.create_tables().unwrap();
+                let conn = &db.conn;
+                let project = models::Project::create(conn, ".", "default_project").unwrap();
+                let chain = project.add_module(conn, "chain").unwrap();
+                let mut symbols: Vec<models::Symbol> = vec![];
+                for i in 0..n {
+                    let symbol = chain
+                        .add_symbol(
+                            conn,
+                            models::SymbolVariant::LocalVariable,
+                            &format!("a{}", i),
+                        )
+                        .unwrap();
+                    if let Some(depend_on) = symbols.last() {
+                        models::SymbolDependency::create(conn, &symbol, depend_on).unwrap();
+                    }
+                    symbols.push(symbol);
+                }
 
-        // update cache
-        self.cache.insert(module_symbol.clone(), res.clone());
+                let store = DbStore::new(&db, project.clone());
+                let mut dt = DependencyTracker::new(&store, true);
+                let res = dt.trace(a(0)).unwrap();
+                assert_eq!(res.paths, vec![(0..n).rev().map(a).collect::<Vec<_>>()]);
+                let trace_graph = dt.trace_graph(a(0)).unwrap();
+                assert_eq!(trace_graph.nodes, (0..n).map(a).collect::<Vec<_>>());
+                assert_eq!(trace_graph.paths().next().unwrap().len(), n);
 
-        Ok(res)
+                let mut dt = DependOnTracker::new(&db, project, true);
+                let res = dt.trace(a(n - 1)).unwrap();
+                assert_eq!(res.paths, vec![(0..n).map(a).collect::<Vec<_>>()]);
+                dt.set_max_depth(Some(n));
+                let trace_graph = dt.trace_graph(a(n - 1)).unwrap();
+                assert_eq!(trace_graph.nodes, (0..n).rev().map(a).collect::<Vec<_>>());
+                assert!(!trace_graph.truncation.is_truncated());
+            })
+            .unwrap()
+            .join()
+            .unwrap();
     }
 }
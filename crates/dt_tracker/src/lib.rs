@@ -1,9 +1,18 @@
 pub mod db_version;
+pub mod shared;
+pub mod store;
+mod tracer;
 
 use anyhow::{bail, Context};
-use dt_graph::used_by_graph::{UsedBy, UsedByGraph, UsedByOther, UsedByType};
+use dt_graph::{depend_on_graph::DependOnGraph, used_by_graph::UsedByGraph};
+use dt_parser::{
+    anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT,
+    types::{FromOtherModule, FromType, ModuleExport},
+};
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::{collections::HashSet, fmt, time::Duration};
+use store::GraphStore;
+use tracer::{EdgeLookup, Tracer};
 
 #[derive(Debug, Eq, PartialEq, Hash, Clone, Deserialize)]
 pub enum TraceTarget {
@@ -33,145 +42,499 @@ impl Serialize for TraceTarget {
 
 pub type ModuleSymbol = (String, TraceTarget);
 
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraceResult {
+    pub paths: Vec<Vec<ModuleSymbol>>,
+
+    // The symbols using each other, a cycle starts and ends with the same symbol and
+    // follows the traced edges. [A, B, A] means A is used by B and B is used by A, or A
+    // depends on B and B depends on A for forward tracing.
+    // The paths stop right before going around a cycle again.
+    pub cycles: Vec<Vec<ModuleSymbol>>,
+
+    pub truncation: Truncation,
+}
+
+// Where tracing stops, see `DependencyTracker::set_limits()`.
+#[derive(Debug, Clone, Default)]
+pub struct TraceLimits {
+    // stop following the edges at the symbols `max_depth` steps away from the traced one,
+    // e.g. 2 for the direct and second-level users
+    pub max_depth: Option<usize>,
+
+    // stop after finding this many paths, `trace_graph()` finds no paths so it's not
+    // limited by it
+    pub max_paths: Option<usize>,
+
+    // stop when tracing takes longer than this
+    pub time_budget: Option<Duration>,
+
+    // The symbols, and the modules matching the globs, where tracing stops, e.g. the app
+    // shell or a `Layout` component. They're in the result but their edges are not
+    // followed, unless it's the traced symbol or its module.
+    pub stop_symbols: Vec<ModuleSymbol>,
+    pub stop_modules: Vec<String>,
+}
+
+// Why a result is not complete, see `TraceLimits`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Truncation {
+    // the symbols having edges not followed because of `max_depth`
+    pub max_depth_reached: Vec<ModuleSymbol>,
+    // the stop symbols, or the symbols of the stop modules, having edges not followed
+    pub stopped_at: Vec<ModuleSymbol>,
+    pub max_paths_reached: bool,
+    pub time_budget_exceeded: bool,
+}
+
+impl Truncation {
+    pub fn is_truncated(&self) -> bool {
+        !self.max_depth_reached.is_empty()
+            || !self.stopped_at.is_empty()
+            || self.max_paths_reached
+            || self.time_budget_exceeded
+    }
+
+    fn merge(&mut self, other: Truncation) {
+        for module_symbol in other.max_depth_reached {
+            if !self.max_depth_reached.contains(&module_symbol) {
+                self.max_depth_reached.push(module_symbol);
+            }
+        }
+        for module_symbol in other.stopped_at {
+            if !self.stopped_at.contains(&module_symbol) {
+                self.stopped_at.push(module_symbol);
+            }
+        }
+        self.max_paths_reached |= other.max_paths_reached;
+        self.time_budget_exceeded |= other.time_budget_exceeded;
+    }
+}
+
+impl fmt::Display for Truncation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut reasons = vec![];
+        if !self.max_depth_reached.is_empty() {
+            reasons.push(format!(
+                "{} symbols at the max depth",
+                self.max_depth_reached.len()
+            ));
+        }
+        if !self.stopped_at.is_empty() {
+            reasons.push(format!("{} stop symbols", self.stopped_at.len()));
+        }
+        if self.max_paths_reached {
+            reasons.push(String::from("max paths reached"));
+        }
+        if self.time_budget_exceeded {
+            reasons.push(String::from("time budget exceeded"));
+        }
+        match reasons.is_empty() {
+            true => write!(f, "not truncated"),
+            false => write!(f, "truncated: {}", reasons.join(", ")),
+        }
+    }
+}
+
+// The symbols reachable from the traced one and the edges between them, the compact form
+// of `TraceResult`. It's as large as the traced part of the graph, while the number of
+// paths can grow exponentially with it.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct TraceGraph {
+    // the traced symbol comes first
+    pub nodes: Vec<ModuleSymbol>,
+
+    // The indexes of `nodes`, (A, B) means A is used by B, or A depends on B for forward
+    // tracing. The edges closing the cycles are left out and reported in `cycles`, so the
+    // graph is acyclic.
+    pub edges: Vec<(usize, usize)>,
+
+    // see `TraceResult::cycles`
+    pub cycles: Vec<Vec<ModuleSymbol>>,
+
+    pub truncation: Truncation,
+}
+
+impl TraceGraph {
+    // Enumerate the full paths lazily, in the same form as `TraceResult::paths`. Take as
+    // many as needed, e.g. `graph.paths().take(100)`.
+    pub fn paths(&self) -> TracePaths<'_> {
+        let mut next = vec![vec![]; self.nodes.len()];
+        for (from, to) in self.edges.iter() {
+            next[*from].push(*to);
+        }
+        TracePaths {
+            graph: self,
+            next,
+            stack: match self.nodes.is_empty() {
+                true => vec![],
+                false => vec![(0, 0)],
+            },
+        }
+    }
+
+    // The part of the graph on the way from the traced symbol to the given nodes, e.g. the
+    // symbols attached to a route.
+    pub fn subgraph_to(&self, targets: &[usize]) -> TraceGraph {
+        let mut prev = vec![vec![]; self.nodes.len()];
+        for (from, to) in self.edges.iter() {
+            prev[*to].push(*from);
+        }
+        let mut kept = vec![false; self.nodes.len()];
+        let mut stack = targets.to_vec();
+        while let Some(node) = stack.pop() {
+            if !kept[node] {
+                kept[node] = true;
+                stack.extend(prev[node].iter());
+            }
+        }
+
+        self.retain(&kept, 0)
+    }
+
+    // The graph of the kept nodes, with `first` moved to the front.
+    fn retain(&self, kept: &[bool], first: usize) -> TraceGraph {
+        let mut subgraph = TraceGraph::default();
+        let mut index = vec![None; self.nodes.len()];
+        let order = std::iter::once(first).chain((0..self.nodes.len()).filter(|i| *i != first));
+        for i in order {
+            if kept[i] {
+                index[i] = Some(subgraph.nodes.len());
+                subgraph.nodes.push(self.nodes[i].clone());
+            }
+        }
+        subgraph.edges = self
+            .edges
+            .iter()
+            .filter_map(|(from, to)| Some((index[*from]?, index[*to]?)))
+            .collect();
+        let kept_symbols: HashSet<&ModuleSymbol> = subgraph.nodes.iter().collect();
+        subgraph.cycles = self
+            .cycles
+            .iter()
+            .filter(|cycle| cycle.iter().all(|s| kept_symbols.contains(s)))
+            .cloned()
+            .collect();
+        subgraph.truncation = Truncation {
+            max_depth_reached: self
+                .truncation
+                .max_depth_reached
+                .iter()
+                .filter(|s| kept_symbols.contains(s))
+                .cloned()
+                .collect(),
+            stopped_at: self
+                .truncation
+                .stopped_at
+                .iter()
+                .filter(|s| kept_symbols.contains(s))
+                .cloned()
+                .collect(),
+            ..self.truncation.clone()
+        };
+        subgraph
+    }
+}
+
+// The union of the graphs traced from several symbols, e.g. Adj+ of
+// { UserProfileHeader, FriendList }, as if they were a super node.
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct MultiTraceGraph {
+    // the traced symbols without duplicates, they're also the first nodes of `graph`
+    pub sources: Vec<ModuleSymbol>,
+
+    // `nodes[..sources.len()]` are the sources, so it doesn't end at a single symbol and
+    // `TraceGraph::paths()` only walks from the first one, see `graph_of()`
+    pub graph: TraceGraph,
+
+    // the indexes of `sources` reaching each node of `graph`
+    pub reached_by: Vec<Vec<usize>>,
+}
+
+impl MultiTraceGraph {
+    // The graph traced from one of the sources alone.
+    pub fn graph_of(&self, source: usize) -> TraceGraph {
+        let kept: Vec<bool> = self
+            .reached_by
+            .iter()
+            .map(|sources| sources.contains(&source))
+            .collect();
+        self.graph.retain(&kept, source)
+    }
+}
+
+// The iterator of `TraceGraph::paths()`.
+pub struct TracePaths<'a> {
+    graph: &'a TraceGraph,
+    next: Vec<Vec<usize>>,
+    // the current path and the number of edges walked from each node of it
+    stack: Vec<(usize, usize)>,
+}
+
+impl<'a> Iterator for TracePaths<'a> {
+    type Item = Vec<ModuleSymbol>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, walked)) = self.stack.last_mut() {
+            if let Some(next_node) = self.next[*node].get(*walked) {
+                *walked += 1;
+                self.stack.push((*next_node, 0));
+                continue;
+            }
+            // a path ends at the node without edges
+            let path = match *walked {
+                0 => Some(
+                    self.stack
+                        .iter()
+                        .rev()
+                        .map(|(node, _)| self.graph.nodes[*node].clone())
+                        .collect(),
+                ),
+                _ => None,
+            };
+            self.stack.pop();
+            if path.is_some() {
+                return path;
+            }
+        }
+        None
+    }
+}
+
+// The used-by edges of a store are the edges to trace.
+struct StoreLookup<'store, S: GraphStore + ?Sized>(&'store S);
+
+impl<'store, S: GraphStore + ?Sized> EdgeLookup for StoreLookup<'store, S> {
+    fn get_next(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.0.used_by(module_symbol)
+    }
+
+    fn get_next_within(
+        &self,
+        module_symbol: &ModuleSymbol,
+        depth: usize,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.0.used_by_within(module_symbol, depth)
+    }
+}
+
+// Trace the symbols using the given one over any `GraphStore`, the `UsedByGraph` by
+// default.
 #[derive(Debug)]
-pub struct DependencyTracker<'graph> {
-    cache: HashMap<ModuleSymbol, Vec<Vec<ModuleSymbol>>>,
-    graph: &'graph UsedByGraph,
-    trace_full_path_only: bool,
+pub struct DependencyTracker<'store, S: GraphStore + ?Sized = UsedByGraph> {
+    tracer: Tracer,
+    store: &'store S,
 }
 
-impl<'graph> DependencyTracker<'graph> {
-    pub fn new(graph: &'graph UsedByGraph, trace_full_path_only: bool) -> Self {
+impl<'store, S: GraphStore + ?Sized> DependencyTracker<'store, S> {
+    pub fn new(store: &'store S, trace_full_path_only: bool) -> Self {
         Self {
-            cache: HashMap::new(),
-            graph,
-            trace_full_path_only,
+            tracer: Tracer::new(trace_full_path_only),
+            store,
         }
     }
 
     pub fn validate_module_path(&self, module_path: &str) -> anyhow::Result<()> {
-        match self.graph.modules.contains_key(module_path) {
-            true => Ok(()),
-            false => bail!("module {} not found", module_path),
-        }
+        self.store.symbols(module_path).map(|_| ())
     }
 
-    pub fn get_traceable_named_exports(&self, module_path: &str) -> anyhow::Result<Vec<&str>> {
+    pub fn get_traceable_named_exports(&self, module_path: &str) -> anyhow::Result<Vec<String>> {
         Ok(self
-            .graph
-            .modules
-            .get(module_path)
-            .context(format!("module {} not found", module_path))?
-            .named_export_table
-            .keys()
-            .map(|s| s.as_str())
-            .collect::<Vec<&str>>())
+            .store
+            .symbols(module_path)?
+            .into_iter()
+            .filter_map(|symbol| match symbol {
+                TraceTarget::NamedExport(name) => Some(name),
+                _ => None,
+            })
+            .collect())
     }
 
-    pub fn get_traceable_local_variables(&self, module_path: &str) -> anyhow::Result<Vec<&str>> {
+    pub fn get_traceable_local_variables(&self, module_path: &str) -> anyhow::Result<Vec<String>> {
         Ok(self
-            .graph
-            .modules
-            .get(module_path)
-            .context(format!("module {} not found", module_path))?
-            .local_variable_table
-            .keys()
-            .map(|s| s.as_str())
-            .collect::<Vec<&str>>())
-    }
-
-    pub fn trace(&mut self, module_symbol: ModuleSymbol) -> anyhow::Result<Vec<Vec<ModuleSymbol>>> {
-        // Treat routeNmaes specially since they cause a lot of circular dependencies in
-        // some of our codebases. One assumption of this tool is "no circular dependency"
-        // , so let's workaround here for now.
-        if module_symbol.1.to_string() == "routeNames" {
-            return Ok(vec![]);
+            .store
+            .symbols(module_path)?
+            .into_iter()
+            .filter_map(|symbol| match symbol {
+                TraceTarget::LocalVar(name) => Some(name),
+                _ => None,
+            })
+            .collect())
+    }
+
+    // Stop following the edges at the symbols `max_depth` steps away from the traced
+    // one, `None` means no limit.
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.tracer.set_max_depth(max_depth);
+    }
+
+    // Limit how far and how long to trace, the results tell whether they're truncated by
+    // the limits in `truncation`.
+    pub fn set_limits(&mut self, limits: TraceLimits) -> anyhow::Result<()> {
+        self.tracer.set_limits(limits)
+    }
+
+    pub fn trace(&mut self, module_symbol: ModuleSymbol) -> anyhow::Result<TraceResult> {
+        self.tracer.trace(&StoreLookup(self.store), module_symbol)
+    }
+
+    // Trace the reachable subgraph instead of enumerating the paths, see `TraceGraph`.
+    pub fn trace_graph(&self, module_symbol: ModuleSymbol) -> anyhow::Result<TraceGraph> {
+        self.tracer
+            .trace_graph(&StoreLookup(self.store), module_symbol)
+    }
+
+    // Trace several symbols at once, the parts of the graph they share are traced once,
+    // see `MultiTraceGraph`.
+    pub fn trace_sources(
+        &self,
+        module_symbols: Vec<ModuleSymbol>,
+    ) -> anyhow::Result<MultiTraceGraph> {
+        self.tracer
+            .trace_sources(&StoreLookup(self.store), module_symbols)
+    }
+}
+
+struct DependOnLookup<'graph>(&'graph DependOnGraph);
+
+impl<'graph> DependOnLookup<'graph> {
+    fn other_module_symbols(
+        &self,
+        FromOtherModule { from, from_type }: &FromOtherModule,
+    ) -> Vec<ModuleSymbol> {
+        match from_type {
+            FromType::Named(name) => vec![(from.clone(), TraceTarget::NamedExport(name.clone()))],
+            FromType::Default => vec![(from.clone(), TraceTarget::DefaultExport)],
+            // the namespace depends on all the named exports, modules outside of the graph
+            // like packages are not traced
+            FromType::Namespace => match self.0.table.get(from) {
+                Some(module) => module
+                    .named_export_table
+                    .keys()
+                    .filter(|name| *name != SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT)
+                    .map(|name| (from.clone(), TraceTarget::NamedExport(name.clone())))
+                    .collect(),
+                None => vec![],
+            },
         }
+    }
 
-        // early return if cached
-        if let Some(cached) = self.cache.get(&module_symbol) {
-            return Ok(cached.clone());
+    fn module_export_symbols(
+        &self,
+        module_path: &str,
+        module_export: &ModuleExport,
+    ) -> Vec<ModuleSymbol> {
+        match module_export {
+            ModuleExport::Local(name) => {
+                vec![(module_path.to_string(), TraceTarget::LocalVar(name.clone()))]
+            }
+            ModuleExport::ReExportFrom(from_other_module) => {
+                self.other_module_symbols(from_other_module)
+            }
         }
+    }
+}
 
-        let module = self
-            .graph
-            .modules
-            .get(&module_symbol.0)
-            .context(format!("module {} not found", module_symbol.0))?;
-
-        let used_by = match module_symbol.1 {
-            TraceTarget::NamedExport(ref name) => module
-                .named_export_table
-                .get(name)
-                .context(format!("exported name {} not found", name))?,
-            TraceTarget::DefaultExport => &module.default_export,
-            TraceTarget::LocalVar(ref name) => module
-                .local_variable_table
-                .get(name)
-                .context(format!("local symbol {} not found", name))?,
+impl<'graph> EdgeLookup for DependOnLookup<'graph> {
+    fn get_next(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        let module_path = &module_symbol.0;
+        let module = match self.0.table.get(module_path) {
+            Some(module) => module,
+            // imported from a package or a file that is not analyzed
+            None => return Ok(vec![]),
         };
 
-        let mut res: Vec<Vec<ModuleSymbol>> = vec![];
-        if let Some(used_by) = used_by {
-            for next_target in used_by.iter() {
-                let mut paths = match next_target {
-                    UsedBy::Itself(used_by_type) => match used_by_type {
-                        UsedByType::NamedExport(name) => self.trace((
-                            module_symbol.0.clone(),
-                            TraceTarget::NamedExport(name.to_string()),
-                        ))?,
-                        UsedByType::DefaultExport => {
-                            self.trace((module_symbol.0.clone(), TraceTarget::DefaultExport))?
-                        }
-                        UsedByType::LocalVar(name) => self.trace((
-                            module_symbol.0.clone(),
-                            TraceTarget::LocalVar(name.to_string()),
-                        ))?,
-                    },
-                    UsedBy::Other(UsedByOther { by, by_type }) => match by_type {
-                        UsedByType::NamedExport(name) => {
-                            self.trace((by.clone(), TraceTarget::NamedExport(name.to_string())))?
-                        }
-                        UsedByType::DefaultExport => {
-                            self.trace((by.clone(), TraceTarget::DefaultExport))?
-                        }
-                        UsedByType::LocalVar(name) => {
-                            self.trace((by.clone(), TraceTarget::LocalVar(name.to_string())))?
-                        }
-                    },
-                };
-                res.append(&mut paths);
+        let res = match module_symbol.1 {
+            TraceTarget::NamedExport(ref name) => {
+                let module_export = module
+                    .named_export_table
+                    .get(name)
+                    .context(format!("exported name {} not found", name))?;
+                self.module_export_symbols(module_path, module_export)
             }
-        }
-
-        // append current ModuleSymbol to each path
-        for path in res.iter_mut() {
-            path.push(module_symbol.clone());
-        }
-        if self.trace_full_path_only {
-            // because we only want to trace the full path, we only need to add a new path
-            // when this ModuleSymbol is not using by anyone.
-            if res.len() == 0 {
-                res.push(vec![module_symbol.clone()]);
+            TraceTarget::DefaultExport => match module.default_export.as_ref() {
+                Some(module_export) => self.module_export_symbols(module_path, module_export),
+                None => bail!("module {} doesn't have default export", module_path),
+            },
+            TraceTarget::LocalVar(ref name) => {
+                let variable = module
+                    .local_variable_table
+                    .get(name)
+                    .context(format!("local symbol {} not found", name))?;
+                let mut res = vec![];
+                if let Some(depend_on) = variable.depend_on.as_ref() {
+                    for depend_on_name in depend_on.iter() {
+                        res.push((
+                            module_path.clone(),
+                            TraceTarget::LocalVar(depend_on_name.clone()),
+                        ));
+                    }
+                }
+                if let Some(import_from) = variable.import_from.as_ref() {
+                    res.extend(self.other_module_symbols(import_from));
+                }
+                res
             }
-        } else {
-            // always append the current ModuleSymbol since we want to list every single path
-            // that is reachable from the target.
-            res.push(vec![module_symbol.clone()]);
+        };
+        Ok(res)
+    }
+}
+
+// Trace what a symbol depends on, e.g. the modules and i18n keys a route component pulls
+// in. The paths have the same structure as `DependencyTracker`, they start from the
+// symbols depending on nothing and end with the traced symbol. The symbols imported from
+// modules outside of the graph are the ends of the paths.
+#[derive(Debug)]
+pub struct DependOnTracker<'graph> {
+    tracer: Tracer,
+    graph: &'graph DependOnGraph,
+}
+
+impl<'graph> DependOnTracker<'graph> {
+    pub fn new(graph: &'graph DependOnGraph, trace_full_path_only: bool) -> Self {
+        Self {
+            tracer: Tracer::new(trace_full_path_only),
+            graph,
         }
+    }
 
-        // update cache
-        self.cache.insert(module_symbol.clone(), res.clone());
+    // see `DependencyTracker::set_max_depth()`
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.tracer.set_max_depth(max_depth);
+    }
 
-        Ok(res)
+    // see `DependencyTracker::set_limits()`
+    pub fn set_limits(&mut self, limits: TraceLimits) -> anyhow::Result<()> {
+        self.tracer.set_limits(limits)
+    }
+
+    pub fn trace(&mut self, module_symbol: ModuleSymbol) -> anyhow::Result<TraceResult> {
+        self.tracer
+            .trace(&DependOnLookup(self.graph), module_symbol)
+    }
+
+    // see `DependencyTracker::trace_graph()`
+    pub fn trace_graph(&self, module_symbol: ModuleSymbol) -> anyhow::Result<TraceGraph> {
+        self.tracer
+            .trace_graph(&DependOnLookup(self.graph), module_symbol)
+    }
+
+    // see `DependencyTracker::trace_sources()`
+    pub fn trace_sources(
+        &self,
+        module_symbols: Vec<ModuleSymbol>,
+    ) -> anyhow::Result<MultiTraceGraph> {
+        self.tracer
+            .trace_sources(&DependOnLookup(self.graph), module_symbols)
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
-    use dt_graph::used_by_graph::Module;
-    use dt_parser::anonymous_default_export::SYMBOL_NAME_FOR_ANONYMOUS_DEFAULT_EXPORT;
+    use dt_graph::used_by_graph::{Module, UsedBy, UsedByOther, UsedByType};
+    use dt_parser::types::{ModuleScopedVariable, SymbolDependency};
+    use std::collections::HashMap;
 
     macro_rules! s {
         ($s:expr) => {{
@@ -460,12 +823,13 @@ mod tests {
         };
 
         let mut dt = DependencyTracker::new(&graph, false);
-        let paths = dt
+        let res = dt
             .trace((String::from("kirby"), TraceTarget::LocalVar(s!("Power"))))
             .unwrap();
+        assert!(res.cycles.is_empty());
 
         assert_eq!(
-            paths,
+            res.paths,
             vec![
                 vec![
                     (s!("PicnicTime"), TraceTarget::LocalVar(s!("Power"))),
@@ -552,4 +916,732 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn cycles() {
+        // a.js
+        // import B from './b';
+        // export const A = () => B;
+        //
+        // b.js
+        // import { A } from './a';
+        // export const B = () => A;
+        // export default B;
+        let graph = UsedByGraph {
+            modules: HashMap::from([
+                (
+                    s!("a"),
+                    Module {
+                        local_variable_table: HashMap::from([(
+                            s!("A"),
+                            Some(vec![UsedBy::Itself(UsedByType::NamedExport(s!("A")))]),
+                        )]),
+                        named_export_table: HashMap::from([(
+                            s!("A"),
+                            Some(vec![UsedBy::Other(UsedByOther {
+                                by: s!("b"),
+                                by_type: UsedByType::LocalVar(s!("B")),
+                            })]),
+                        )]),
+                        default_export: None,
+                    },
+                ),
+                (
+                    s!("b"),
+                    Module {
+                        local_variable_table: HashMap::from([(
+                            s!("B"),
+                            Some(vec![
+                                UsedBy::Itself(UsedByType::NamedExport(s!("B"))),
+                                UsedBy::Itself(UsedByType::DefaultExport),
+                            ]),
+                        )]),
+                        named_export_table: HashMap::from([(
+                            s!("B"),
+                            Some(vec![UsedBy::Other(UsedByOther {
+                                by: s!("a"),
+                                by_type: UsedByType::LocalVar(s!("A")),
+                            })]),
+                        )]),
+                        default_export: None,
+                    },
+                ),
+            ]),
+        };
+        let a_local = (s!("a"), TraceTarget::LocalVar(s!("A")));
+        let a_export = (s!("a"), TraceTarget::NamedExport(s!("A")));
+        let b_local = (s!("b"), TraceTarget::LocalVar(s!("B")));
+        let b_export = (s!("b"), TraceTarget::NamedExport(s!("B")));
+        let b_default = (s!("b"), TraceTarget::DefaultExport);
+
+        let mut dt = DependencyTracker::new(&graph, true);
+        let res = dt.trace(a_local.clone()).unwrap();
+        assert_eq!(
+            res.paths,
+            vec![
+                vec![
+                    b_export.clone(),
+                    b_local.clone(),
+                    a_export.clone(),
+                    a_local.clone()
+                ],
+                vec![
+                    b_default.clone(),
+                    b_local.clone(),
+                    a_export.clone(),
+                    a_local.clone()
+                ],
+            ]
+        );
+        assert_eq!(
+            res.cycles,
+            vec![vec![
+                a_local.clone(),
+                a_export.clone(),
+                b_local.clone(),
+                b_export.clone(),
+                a_local.clone()
+            ]]
+        );
+
+        // the symbols in the cycle are not cached, the cycle is entered from B this time
+        let res = dt.trace(b_local.clone()).unwrap();
+        assert_eq!(
+            res.paths,
+            vec![
+                vec![
+                    a_export.clone(),
+                    a_local.clone(),
+                    b_export.clone(),
+                    b_local.clone()
+                ],
+                vec![b_default.clone(), b_local.clone()],
+            ]
+        );
+        assert_eq!(
+            res.cycles,
+            vec![vec![
+                b_local.clone(),
+                b_export.clone(),
+                a_local.clone(),
+                a_export.clone(),
+                b_local.clone()
+            ]]
+        );
+
+        // the edge closing the cycle is left out of the graph, so it has the same paths
+        let trace_graph = dt.trace_graph(a_local.clone()).unwrap();
+        assert_eq!(
+            trace_graph.nodes,
+            vec![
+                a_local.clone(),
+                a_export.clone(),
+                b_local.clone(),
+                b_export.clone(),
+                b_default.clone()
+            ]
+        );
+        assert_eq!(trace_graph.edges, vec![(0, 1), (1, 2), (2, 3), (2, 4)]);
+        assert_eq!(trace_graph.cycles.len(), 1);
+        assert_eq!(
+            trace_graph.paths().collect::<Vec<Vec<ModuleSymbol>>>(),
+            vec![
+                vec![
+                    b_export.clone(),
+                    b_local.clone(),
+                    a_export.clone(),
+                    a_local.clone()
+                ],
+                vec![
+                    b_default.clone(),
+                    b_local.clone(),
+                    a_export.clone(),
+                    a_local.clone()
+                ],
+            ]
+        );
+        assert_eq!(trace_graph.paths().take(1).count(), 1);
+
+        let subgraph = trace_graph.subgraph_to(&[4]);
+        assert_eq!(subgraph.nodes, vec![a_local, a_export, b_local, b_default]);
+        assert_eq!(subgraph.edges, vec![(0, 1), (1, 2), (2, 3)]);
+        assert!(subgraph.cycles.is_empty());
+    }
+
+    #[test]
+    fn trace_depend_on() {
+        let variable =
+            |depend_on: &[&str], import_from: Option<(&str, FromType)>| ModuleScopedVariable {
+                depend_on: match depend_on.is_empty() {
+                    true => None,
+                    false => Some(depend_on.iter().map(|name| s!(name)).collect()),
+                },
+                import_from: import_from.map(|(from, from_type)| FromOtherModule {
+                    from: s!(from),
+                    from_type,
+                }),
+            };
+        let mut graph = DependOnGraph::new(".");
+        // import Home from 'home';
+        // const routes = [{ path: '/home', component: Home }];
+        // export default routes;
+        graph.table.insert(
+            s!("routes"),
+            SymbolDependency {
+                canonical_path: s!("routes"),
+                local_variable_table: HashMap::from([
+                    (s!("Home"), variable(&[], Some(("home", FromType::Default)))),
+                    (s!("routes"), variable(&["Home"], None)),
+                ]),
+                named_export_table: HashMap::new(),
+                default_export: Some(ModuleExport::Local(s!("routes"))),
+                re_export_star_from: None,
+            },
+        );
+        // import { Title } from 'title';
+        // import { t } from 'i18n';
+        // const Home = () => <Title>{t('i18n.home')}</Title>;
+        // export default Home;
+        graph.table.insert(
+            s!("home"),
+            SymbolDependency {
+                canonical_path: s!("home"),
+                local_variable_table: HashMap::from([
+                    (
+                        s!("Title"),
+                        variable(&[], Some(("title", FromType::Named(s!("Title"))))),
+                    ),
+                    (
+                        s!("t"),
+                        variable(&[], Some(("i18n", FromType::Named(s!("t"))))),
+                    ),
+                    (s!("Home"), variable(&["Title", "t"], None)),
+                ]),
+                named_export_table: HashMap::new(),
+                default_export: Some(ModuleExport::Local(s!("Home"))),
+                re_export_star_from: None,
+            },
+        );
+        // export const Title = () => null;
+        graph.table.insert(
+            s!("title"),
+            SymbolDependency {
+                canonical_path: s!("title"),
+                local_variable_table: HashMap::from([(s!("Title"), variable(&[], None))]),
+                named_export_table: HashMap::from([(
+                    s!("Title"),
+                    ModuleExport::Local(s!("Title")),
+                )]),
+                default_export: None,
+                re_export_star_from: None,
+            },
+        );
+
+        let home_to_routes = vec![
+            (s!("home"), TraceTarget::LocalVar(s!("Home"))),
+            (s!("home"), TraceTarget::DefaultExport),
+            (s!("routes"), TraceTarget::LocalVar(s!("Home"))),
+            (s!("routes"), TraceTarget::LocalVar(s!("routes"))),
+            (s!("routes"), TraceTarget::DefaultExport),
+        ];
+        let mut dt = DependOnTracker::new(&graph, true);
+        let res = dt
+            .trace((s!("routes"), TraceTarget::DefaultExport))
+            .unwrap();
+        assert!(res.cycles.is_empty());
+        assert_eq!(
+            res.paths,
+            vec![
+                [
+                    vec![
+                        (s!("title"), TraceTarget::LocalVar(s!("Title"))),
+                        (s!("title"), TraceTarget::NamedExport(s!("Title"))),
+                        (s!("home"), TraceTarget::LocalVar(s!("Title"))),
+                    ],
+                    home_to_routes.clone(),
+                ]
+                .concat(),
+                // the module outside of the graph is the end
+                [
+                    vec![
+                        (s!("i18n"), TraceTarget::NamedExport(s!("t"))),
+                        (s!("home"), TraceTarget::LocalVar(s!("t"))),
+                    ],
+                    home_to_routes.clone(),
+                ]
+                .concat(),
+            ]
+        );
+
+        dt.set_max_depth(Some(3));
+        let res = dt
+            .trace((s!("routes"), TraceTarget::DefaultExport))
+            .unwrap();
+        assert_eq!(res.paths, vec![home_to_routes[1..].to_vec()]);
+        assert_eq!(
+            res.truncation.max_depth_reached,
+            vec![(s!("home"), TraceTarget::DefaultExport)]
+        );
+
+        dt.set_max_depth(Some(0));
+        let res = dt
+            .trace((s!("routes"), TraceTarget::DefaultExport))
+            .unwrap();
+        assert_eq!(
+            res.paths,
+            vec![vec![(s!("routes"), TraceTarget::DefaultExport)]]
+        );
+    }
+
+    #[test]
+    fn trace_limits() {
+        let mut graph = DependOnGraph::new(".");
+        let variable =
+            |depend_on: &[&str], import_from: Option<(&str, &str)>| ModuleScopedVariable {
+                depend_on: match depend_on.is_empty() {
+                    true => None,
+                    false => Some(depend_on.iter().map(|name| s!(name)).collect()),
+                },
+                import_from: import_from.map(|(from, name)| FromOtherModule {
+                    from: s!(from),
+                    from_type: FromType::Named(s!(name)),
+                }),
+            };
+        // import { Layout } from 'layout';
+        // import { Button } from 'button';
+        // export const App = () => <Layout><Button /></Layout>;
+        graph.table.insert(
+            s!("app"),
+            SymbolDependency {
+                canonical_path: s!("app"),
+                local_variable_table: HashMap::from([
+                    (s!("Layout"), variable(&[], Some(("layout", "Layout")))),
+                    (s!("Button"), variable(&[], Some(("button", "Button")))),
+                    (s!("App"), variable(&["Layout", "Button"], None)),
+                ]),
+                named_export_table: HashMap::from([(s!("App"), ModuleExport::Local(s!("App")))]),
+                default_export: None,
+                re_export_star_from: None,
+            },
+        );
+        // import { Header } from 'header';
+        // export const Layout = ({ children }) => <><Header />{children}</>;
+        graph.table.insert(
+            s!("layout"),
+            SymbolDependency {
+                canonical_path: s!("layout"),
+                local_variable_table: HashMap::from([
+                    (s!("Header"), variable(&[], Some(("header", "Header")))),
+                    (s!("Layout"), variable(&["Header"], None)),
+                ]),
+                named_export_table: HashMap::from([(
+                    s!("Layout"),
+                    ModuleExport::Local(s!("Layout")),
+                )]),
+                default_export: None,
+                re_export_star_from: None,
+            },
+        );
+
+        let app = (s!("app"), TraceTarget::NamedExport(s!("App")));
+        let layout = (s!("layout"), TraceTarget::NamedExport(s!("Layout")));
+        let mut dt = DependOnTracker::new(&graph, true);
+        let res = dt.trace(app.clone()).unwrap();
+        assert_eq!(res.paths.len(), 2);
+        assert!(!res.truncation.is_truncated());
+
+        // the layout module is a boundary, unless it's traced from
+        dt.set_limits(TraceLimits {
+            stop_modules: vec![s!("lay*")],
+            ..TraceLimits::default()
+        })
+        .unwrap();
+        let res = dt.trace(app.clone()).unwrap();
+        assert_eq!(res.paths[0][0], layout);
+        assert_eq!(res.truncation.stopped_at, vec![layout.clone()]);
+        assert_eq!(res.truncation.to_string(), "truncated: 1 stop symbols");
+        let res = dt.trace(layout.clone()).unwrap();
+        assert!(!res.truncation.is_truncated());
+        let trace_graph = dt.trace_graph(app.clone()).unwrap();
+        assert_eq!(trace_graph.nodes.len(), 6);
+        assert_eq!(trace_graph.truncation.stopped_at, vec![layout.clone()]);
+
+        dt.set_limits(TraceLimits {
+            stop_symbols: vec![layout.clone()],
+            max_paths: Some(1),
+            ..TraceLimits::default()
+        })
+        .unwrap();
+        let res = dt.trace(app.clone()).unwrap();
+        assert_eq!(res.paths.len(), 1);
+        assert!(res.truncation.max_paths_reached);
+        assert_eq!(res.truncation.stopped_at, vec![layout.clone()]);
+
+        dt.set_limits(TraceLimits {
+            time_budget: Some(Duration::ZERO),
+            ..TraceLimits::default()
+        })
+        .unwrap();
+        let res = dt.trace(app.clone()).unwrap();
+        assert!(res.truncation.time_budget_exceeded);
+        let trace_graph = dt.trace_graph(app).unwrap();
+        assert_eq!(trace_graph.nodes.len(), 1);
+        assert!(trace_graph.truncation.time_budget_exceeded);
+    }
+
+    #[test]
+    fn trace_stop_symbol_first() {
+        let mut graph = DependOnGraph::new(".");
+        let mut insert = |module: &str, name: &str, depend_on: Option<&str>| {
+            let mut local_variable_table = HashMap::from([(
+                s!(name),
+                ModuleScopedVariable {
+                    depend_on: depend_on.map(|_| vec![s!("Next")]),
+                    import_from: None,
+                },
+            )]);
+            if let Some(from) = depend_on {
+                local_variable_table.insert(
+                    s!("Next"),
+                    ModuleScopedVariable {
+                        depend_on: None,
+                        import_from: Some(FromOtherModule {
+                            from: s!(from),
+                            from_type: FromType::Named(s!(from.to_uppercase())),
+                        }),
+                    },
+                );
+            }
+            graph.table.insert(
+                s!(module),
+                SymbolDependency {
+                    canonical_path: s!(module),
+                    local_variable_table,
+                    named_export_table: HashMap::from([(s!(name), ModuleExport::Local(s!(name)))]),
+                    default_export: None,
+                    re_export_star_from: None,
+                },
+            );
+        };
+        // a.A -> b.B -> c.C
+        insert("a", "A", Some("b"));
+        insert("b", "B", Some("c"));
+        insert("c", "C", None);
+
+        let a = (s!("a"), TraceTarget::NamedExport(s!("A")));
+        let b = (s!("b"), TraceTarget::NamedExport(s!("B")));
+        let c = (s!("c"), TraceTarget::NamedExport(s!("C")));
+        let mut dt = DependOnTracker::new(&graph, true);
+        for limits in [
+            TraceLimits {
+                stop_symbols: vec![b.clone()],
+                ..TraceLimits::default()
+            },
+            TraceLimits {
+                stop_modules: vec![s!("b")],
+                ..TraceLimits::default()
+            },
+        ] {
+            dt.set_limits(limits).unwrap();
+            // the stop symbol is followed when it's traced, but not when it's reached later
+            let res = dt.trace(b.clone()).unwrap();
+            assert!(res.paths[0].contains(&c));
+            assert!(!res.truncation.is_truncated());
+            let res = dt.trace(a.clone()).unwrap();
+            assert_eq!(res.paths.len(), 1);
+            assert_eq!(res.paths[0][0], b);
+            assert!(!res.paths[0].contains(&c));
+            assert_eq!(res.truncation.stopped_at, vec![b.clone()]);
+        }
+    }
+
+    #[test]
+    fn trace_sources() {
+        let mut graph = DependOnGraph::new(".");
+        let variable =
+            |depend_on: &[&str], import_from: Option<(&str, &str)>| ModuleScopedVariable {
+                depend_on: match depend_on.is_empty() {
+                    true => None,
+                    false => Some(depend_on.iter().map(|name| s!(name)).collect()),
+                },
+                import_from: import_from.map(|(from, name)| FromOtherModule {
+                    from: s!(from),
+                    from_type: FromType::Named(s!(name)),
+                }),
+            };
+        let mut insert = |module: &str,
+                          variables: Vec<(&str, ModuleScopedVariable)>,
+                          name: &str| {
+            graph.table.insert(
+                s!(module),
+                SymbolDependency {
+                    canonical_path: s!(module),
+                    local_variable_table: variables
+                        .into_iter()
+                        .map(|(name, variable)| (s!(name), variable))
+                        .collect(),
+                    named_export_table: HashMap::from([(s!(name), ModuleExport::Local(s!(name)))]),
+                    default_export: None,
+                    re_export_star_from: None,
+                },
+            );
+        };
+        insert("header", vec![("Header", variable(&[], None))], "Header");
+        // import { Header } from 'header';
+        // export const FriendList = () => <Header />;
+        insert(
+            "friends",
+            vec![
+                ("Header", variable(&[], Some(("header", "Header")))),
+                ("FriendList", variable(&["Header"], None)),
+            ],
+            "FriendList",
+        );
+        // import { Header } from 'header';
+        // import { FriendList } from 'friends';
+        // export const UserProfileHeader = () => <><Header /><FriendList /></>;
+        insert(
+            "profile",
+            vec![
+                ("Header", variable(&[], Some(("header", "Header")))),
+                ("FriendList", variable(&[], Some(("friends", "FriendList")))),
+                (
+                    "UserProfileHeader",
+                    variable(&["Header", "FriendList"], None),
+                ),
+            ],
+            "UserProfileHeader",
+        );
+
+        let friend_list = (s!("friends"), TraceTarget::NamedExport(s!("FriendList")));
+        let header = (s!("header"), TraceTarget::NamedExport(s!("Header")));
+        let profile_header = (s!("profile"), TraceTarget::LocalVar(s!("Header")));
+        let user_profile_header = (
+            s!("profile"),
+            TraceTarget::NamedExport(s!("UserProfileHeader")),
+        );
+        let dt = DependOnTracker::new(&graph, true);
+        let res = dt
+            .trace_sources(vec![
+                friend_list.clone(),
+                user_profile_header.clone(),
+                friend_list.clone(),
+            ])
+            .unwrap();
+        assert_eq!(
+            res.sources,
+            vec![friend_list.clone(), user_profile_header.clone()]
+        );
+        assert_eq!(res.graph.nodes[..2], res.sources);
+        // the symbols of the friends and header modules are shared, they're traced once
+        assert_eq!(res.graph.nodes.len(), 9);
+        let reached_by = |module_symbol: &ModuleSymbol| -> Vec<usize> {
+            let i = res
+                .graph
+                .nodes
+                .iter()
+                .position(|s| s == module_symbol)
+                .unwrap();
+            res.reached_by[i].clone()
+        };
+        assert_eq!(reached_by(&friend_list), vec![0, 1]);
+        assert_eq!(reached_by(&header), vec![0, 1]);
+        assert_eq!(reached_by(&profile_header), vec![1]);
+
+        // the graph of each source is the same as tracing it alone
+        assert_eq!(res.graph_of(0), dt.trace_graph(friend_list).unwrap());
+        let graph_of_profile = res.graph_of(1);
+        let trace_graph = dt.trace_graph(user_profile_header.clone()).unwrap();
+        assert_eq!(graph_of_profile.nodes[0], user_profile_header);
+        assert_eq!(graph_of_profile.nodes.len(), trace_graph.nodes.len());
+        assert_eq!(
+            graph_of_profile.paths().count(),
+            trace_graph.paths().count()
+        );
+    }
+
+    #[test]
+    fn shared_tracker() {
+        // chain.js
+        // const a0 = 0, a1 = a0, a2 = a1, ..., a9 = a8;
+        let mut local_variable_table = HashMap::new();
+        for i in 0..10 {
+            let used_by = match i {
+                9 => None,
+                _ => Some(vec![UsedBy::Itself(UsedByType::LocalVar(format!(
+                    "a{}",
+                    i + 1
+                )))]),
+            };
+            local_variable_table.insert(format!("a{}", i), used_by);
+        }
+        let graph = UsedByGraph {
+            modules: HashMap::from([(
+                s!("chain.js"),
+                Module {
+                    local_variable_table,
+                    named_export_table: HashMap::new(),
+                    default_export: None,
+                },
+            )]),
+        };
+        let a = |i: usize| (s!("chain.js"), TraceTarget::LocalVar(format!("a{}", i)));
+        let dt = DependencyTracker::new(&graph, true);
+
+        let tracker = shared::SharedTracker::new(shared::DEFAULT_CACHE_CAPACITY);
+        std::thread::scope(|scope| {
+            for _ in 0..4 {
+                scope.spawn(|| {
+                    let graphs = tracker.trace_graphs(&graph, vec![a(0), a(5)]).unwrap();
+                    assert_eq!(*graphs[0], dt.trace_graph(a(0)).unwrap());
+                    assert_eq!(*graphs[1], dt.trace_graph(a(5)).unwrap());
+                });
+            }
+        });
+        let cache_size = tracker.cache_size();
+        assert!(cache_size > 0);
+        let graph_of_a5 = tracker.trace_graph(&graph, a(5)).unwrap();
+        assert_eq!(graph_of_a5.nodes.len(), 5);
+        assert_eq!(tracker.cache_size(), cache_size);
+        tracker.evict_modules(&HashSet::from([s!("other.js")]));
+        assert_eq!(tracker.cache_size(), cache_size);
+        tracker.evict_modules(&HashSet::from([s!("chain.js")]));
+        assert_eq!(tracker.cache_size(), 0);
+        tracker.trace_graph(&graph, a(5)).unwrap();
+        tracker.clear();
+        assert_eq!(tracker.cache_size(), 0);
+
+        // only the recently used graph fits
+        let tracker = shared::SharedTracker::new(cache_size / 2);
+        tracker.trace_graph(&graph, a(0)).unwrap();
+        assert_eq!(tracker.cache_size(), 0);
+        tracker.trace_graph(&graph, a(5)).unwrap();
+        let size_of_a5 = tracker.cache_size();
+        assert!(size_of_a5 > 0);
+        tracker.trace_graph(&graph, a(6)).unwrap();
+        assert!(tracker.cache_size() < size_of_a5);
+    }
+
+    #[test]
+    fn trace_deep_chain() {
+        // chain.js
+        // const a0 = 0, a1 = a0, a2 = a1, ..., a1999 = a1998;
+        let n = 2000;
+        let mut local_variable_table = HashMap::new();
+        for i in 0..n {
+            let used_by = match i + 1 == n {
+                true => None,
+                false => Some(vec![UsedBy::Itself(UsedByType::LocalVar(format!(
+                    "a{}",
+                    i + 1
+                )))]),
+            };
+            local_variable_table.insert(format!("a{}", i), used_by);
+        }
+        let graph = UsedByGraph {
+            modules: HashMap::from([(
+                s!("chain.js"),
+                Module {
+                    local_variable_table,
+                    named_export_table: HashMap::new(),
+                    default_export: None,
+                },
+            )]),
+        };
+        let a = |i: usize| (s!("chain.js"), TraceTarget::LocalVar(format!("a{}", i)));
+
+        // a hop took a few frames of the stack when tracing recursively
+        std::thread::Builder::new()
+            .stack_size(128 * 1024)
+            .spawn(move || {
+                let mut dt = DependencyTracker::new(&graph, true);
+                let res = dt.trace(a(0)).unwrap();
+                assert_eq!(res.paths, vec![(0..n).rev().map(a).collect::<Vec<_>>()]);
+                let res = dt.trace(a(1)).unwrap();
+                assert_eq!(res.paths[0].len(), n - 1);
+
+                dt.set_max_depth(Some(n));
+                let res = dt.trace(a(0)).unwrap();
+                assert_eq!(res.paths[0].len(), n);
+                assert!(!res.truncation.is_truncated());
+
+                let trace_graph = dt.trace_graph(a(0)).unwrap();
+                assert_eq!(trace_graph.nodes.len(), n);
+                assert_eq!(trace_graph.paths().next().unwrap().len(), n);
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+    }
+
+    #[test]
+    fn trace_order() {
+        // m.js
+        // const A = 0;
+        // const B = () => A + D, C = () => A;
+        // const D = () => B + C, E = () => C;
+        // const F = () => E;
+        let used_by = |names: &[&str]| {
+            Some(
+                names
+                    .iter()
+                    .map(|name| UsedBy::Itself(UsedByType::LocalVar(name.to_string())))
+                    .collect(),
+            )
+        };
+        let graph = UsedByGraph {
+            modules: HashMap::from([(
+                s!("m.js"),
+                Module {
+                    local_variable_table: HashMap::from([
+                        (s!("A"), used_by(&["B", "C"])),
+                        (s!("B"), used_by(&["D"])),
+                        (s!("C"), used_by(&["D", "E"])),
+                        (s!("D"), used_by(&["B"])),
+                        (s!("E"), used_by(&["F"])),
+                        (s!("F"), None),
+                    ]),
+                    named_export_table: HashMap::new(),
+                    default_export: None,
+                },
+            )]),
+        };
+        let m = |name: &str| (s!("m.js"), TraceTarget::LocalVar(name.to_string()));
+        let ms = |names: &str| names.chars().map(|c| m(&c.to_string())).collect::<Vec<_>>();
+
+        // depth-first, following the used-by symbols in the order they are listed
+        let mut dt = DependencyTracker::new(&graph, false);
+        let res = dt.trace(m("A")).unwrap();
+        assert_eq!(
+            res.paths,
+            vec![
+                ms("DBA"),
+                ms("BA"),
+                ms("BDCA"),
+                ms("DCA"),
+                ms("FECA"),
+                ms("ECA"),
+                ms("CA"),
+                ms("A")
+            ]
+        );
+        assert_eq!(res.cycles, vec![ms("BDB")]);
+        let trace_graph = dt.trace_graph(m("A")).unwrap();
+        assert_eq!(trace_graph.nodes, ms("ABDCEF"));
+        assert_eq!(
+            trace_graph.edges,
+            vec![(0, 1), (1, 2), (0, 3), (3, 2), (3, 4), (4, 5)]
+        );
+        assert_eq!(trace_graph.cycles, vec![ms("BDB")]);
+        assert_eq!(
+            trace_graph.paths().collect::<Vec<_>>(),
+            vec![ms("DBA"), ms("DCA"), ms("FECA")]
+        );
+
+        let mut dt = DependencyTracker::new(&graph, true);
+        assert_eq!(
+            dt.trace(m("A")).unwrap().paths,
+            vec![ms("DBA"), ms("BDCA"), ms("FECA")]
+        );
+    }
 }
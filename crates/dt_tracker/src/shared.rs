@@ -0,0 +1,189 @@
+use super::{
+    store::GraphStore, tracer::Tracer, ModuleSymbol, StoreLookup, TraceGraph, TraceLimits,
+};
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    mem,
+    sync::{Arc, Mutex},
+};
+
+// 256 MiB, the graphs of a large project take a few KiB each
+pub const DEFAULT_CACHE_CAPACITY: usize = 256 << 20;
+
+// The least recently used graphs are evicted first when the cache is over its capacity.
+#[derive(Debug, Default)]
+struct GraphCache {
+    // the graph, its estimated size, and when it's used last
+    graphs: HashMap<ModuleSymbol, (Arc<TraceGraph>, usize, u64)>,
+    // the keys of `graphs` by when they're used last
+    recently_used: BTreeMap<u64, ModuleSymbol>,
+    size: usize,
+    clock: u64,
+}
+
+impl GraphCache {
+    fn get(&mut self, module_symbol: &ModuleSymbol) -> Option<Arc<TraceGraph>> {
+        self.clock += 1;
+        let (graph, _, used_at) = self.graphs.get_mut(module_symbol)?;
+        self.recently_used.remove(used_at);
+        *used_at = self.clock;
+        self.recently_used.insert(self.clock, module_symbol.clone());
+        Some(graph.clone())
+    }
+
+    fn insert(&mut self, module_symbol: ModuleSymbol, graph: Arc<TraceGraph>, capacity: usize) {
+        self.clock += 1;
+        let size = estimate_size(&module_symbol, &graph);
+        if let Some((_, old_size, used_at)) = self.graphs.remove(&module_symbol) {
+            self.recently_used.remove(&used_at);
+            self.size -= old_size;
+        }
+        self.recently_used.insert(self.clock, module_symbol.clone());
+        self.graphs.insert(module_symbol, (graph, size, self.clock));
+        self.size += size;
+        while self.size > capacity {
+            let Some((_, evicted)) = self.recently_used.pop_first() else {
+                break;
+            };
+            if let Some((_, evicted_size, _)) = self.graphs.remove(&evicted) {
+                self.size -= evicted_size;
+            }
+        }
+    }
+
+    fn retain(&mut self, mut keep: impl FnMut(&ModuleSymbol, &TraceGraph) -> bool) {
+        let Self {
+            graphs,
+            recently_used,
+            size,
+            ..
+        } = self;
+        graphs.retain(|module_symbol, (graph, graph_size, used_at)| {
+            if keep(module_symbol, graph) {
+                return true;
+            }
+            recently_used.remove(used_at);
+            *size -= *graph_size;
+            false
+        });
+    }
+}
+
+// Roughly the bytes taken by the graph and its key in the cache.
+fn estimate_size(module_symbol: &ModuleSymbol, graph: &TraceGraph) -> usize {
+    let symbol_size = |(module_path, trace_target): &ModuleSymbol| {
+        mem::size_of::<ModuleSymbol>() + module_path.len() + trace_target.to_string().len()
+    };
+    symbol_size(module_symbol)
+        + graph.nodes.iter().map(symbol_size).sum::<usize>()
+        + graph.edges.len() * mem::size_of::<(usize, usize)>()
+        + graph
+            .cycles
+            .iter()
+            .flatten()
+            .map(symbol_size)
+            .sum::<usize>()
+        + graph
+            .truncation
+            .max_depth_reached
+            .iter()
+            .chain(graph.truncation.stopped_at.iter())
+            .map(symbol_size)
+            .sum::<usize>()
+}
+
+// A tracker to share between threads, e.g. by the workers of the API servers. It traces
+// the graphs like `DependencyTracker::trace_graph()`, they're cached across the traces up
+// to `capacity` bytes. The cache belongs to one version of the store, create another
+// tracker or `clear()` it when the store is reloaded, or `evict_modules()` when only some
+// modules of it change.
+#[derive(Debug)]
+pub struct SharedTracker {
+    tracer: Tracer,
+    capacity: usize,
+    cache: Mutex<GraphCache>,
+}
+
+impl SharedTracker {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            tracer: Tracer::new(true),
+            capacity,
+            cache: Mutex::new(GraphCache::default()),
+        }
+    }
+
+    // see `DependencyTracker::set_limits()`
+    pub fn set_limits(&mut self, limits: TraceLimits) -> anyhow::Result<()> {
+        self.tracer.set_limits(limits)?;
+        self.clear();
+        Ok(())
+    }
+
+    pub fn clear(&self) {
+        *self.lock() = GraphCache::default();
+    }
+
+    // Drop the graphs having a symbol of the modules, e.g. the modules changed in the
+    // store and the ones whose used-by edges changed with them. The others are kept.
+    pub fn evict_modules(&self, module_paths: &HashSet<String>) {
+        self.lock().retain(|(module_path, _), graph| {
+            !module_paths.contains(module_path)
+                && !graph
+                    .nodes
+                    .iter()
+                    .any(|(module_path, _)| module_paths.contains(module_path))
+        });
+    }
+
+    // the estimated bytes taken by the cached graphs
+    pub fn cache_size(&self) -> usize {
+        self.lock().size
+    }
+
+    // The cache is still consistent if a thread panicked while holding it, it's only
+    // changed in between the lookups.
+    fn lock(&self) -> std::sync::MutexGuard<'_, GraphCache> {
+        self.cache
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    pub fn trace_graph(
+        &self,
+        store: &(impl GraphStore + ?Sized),
+        module_symbol: ModuleSymbol,
+    ) -> anyhow::Result<Arc<TraceGraph>> {
+        Ok(self.trace_graphs(store, vec![module_symbol])?.remove(0))
+    }
+
+    // One graph for each of the symbols. The symbols are traced alone, a graph traced
+    // together with others as in `DependencyTracker::trace_sources()` depends on them, e.g.
+    // its depth is from the nearest source, so it can't be cached for the symbol. The lock
+    // is not held while tracing, the threads missing the same symbol at the same time may
+    // both trace it.
+    pub fn trace_graphs(
+        &self,
+        store: &(impl GraphStore + ?Sized),
+        module_symbols: Vec<ModuleSymbol>,
+    ) -> anyhow::Result<Vec<Arc<TraceGraph>>> {
+        let mut graphs = vec![];
+        for module_symbol in module_symbols {
+            if let Some(graph) = self.lock().get(&module_symbol) {
+                graphs.push(graph);
+                continue;
+            }
+            let graph = Arc::new(
+                self.tracer
+                    .trace_graph(&StoreLookup(store), module_symbol.clone())?,
+            );
+            // it depends on how long it takes
+            if !graph.truncation.time_budget_exceeded {
+                self.lock()
+                    .insert(module_symbol, graph.clone(), self.capacity);
+            }
+            graphs.push(graph);
+        }
+        Ok(graphs)
+    }
+}
@@ -0,0 +1,176 @@
+use super::{ModuleSymbol, TraceTarget};
+use anyhow::Context;
+use dt_graph::used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType};
+use dt_portable::Portable;
+
+// Where the symbols, their used-by edges, the routes and the translations are stored.
+// `DependencyTracker` and the search of the API servers are written against it, so they
+// work the same over the in-memory graph or portable and over the database, see
+// `db_version::DbStore`.
+pub trait GraphStore {
+    // the module paths, sorted
+    fn modules(&self) -> anyhow::Result<Vec<String>>;
+
+    fn symbols(&self, module_path: &str) -> anyhow::Result<Vec<TraceTarget>>;
+
+    fn used_by(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>>;
+
+    // The same, when only the symbols up to `depth` away from this one will be looked up.
+    // The stores loading the edges ahead, e.g. the database, can stop there.
+    fn used_by_within(
+        &self,
+        module_symbol: &ModuleSymbol,
+        _depth: usize,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.used_by(module_symbol)
+    }
+
+    // the routes the symbol is attached to
+    fn routes(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<String>>;
+
+    // the i18n keys whose translation equals the search, or contains it, sorted
+    fn search_translations(&self, search: &str, exact_match: bool) -> anyhow::Result<Vec<String>>;
+
+    // the symbols using the i18n key
+    fn translation_usages(&self, i18n_key: &str) -> anyhow::Result<Vec<ModuleSymbol>>;
+}
+
+fn get_module<'a>(graph: &'a UsedByGraph, module_path: &str) -> anyhow::Result<&'a Module> {
+    graph
+        .modules
+        .get(module_path)
+        .context(format!("module {} not found", module_path))
+}
+
+// The graph alone has no routes and translations.
+impl GraphStore for UsedByGraph {
+    fn modules(&self) -> anyhow::Result<Vec<String>> {
+        let mut module_paths: Vec<String> = self.modules.keys().cloned().collect();
+        module_paths.sort();
+        Ok(module_paths)
+    }
+
+    fn symbols(&self, module_path: &str) -> anyhow::Result<Vec<TraceTarget>> {
+        let module = get_module(self, module_path)?;
+        let mut symbols: Vec<TraceTarget> = module
+            .local_variable_table
+            .keys()
+            .map(|name| TraceTarget::LocalVar(name.to_owned()))
+            .chain(
+                module
+                    .named_export_table
+                    .keys()
+                    .map(|name| TraceTarget::NamedExport(name.to_owned())),
+            )
+            .collect();
+        if module.default_export.is_some() {
+            symbols.push(TraceTarget::DefaultExport);
+        }
+        Ok(symbols)
+    }
+
+    fn used_by(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        let module = get_module(self, &module_symbol.0)?;
+
+        let used_by = match module_symbol.1 {
+            TraceTarget::NamedExport(ref name) => module
+                .named_export_table
+                .get(name)
+                .context(format!("exported name {} not found", name))?,
+            TraceTarget::DefaultExport => &module.default_export,
+            TraceTarget::LocalVar(ref name) => module
+                .local_variable_table
+                .get(name)
+                .context(format!("local symbol {} not found", name))?,
+        };
+
+        let mut res = vec![];
+        if let Some(used_by) = used_by {
+            for next_target in used_by.iter() {
+                let (by, by_type) = match next_target {
+                    UsedBy::Itself(used_by_type) => (&module_symbol.0, used_by_type),
+                    UsedBy::Other(UsedByOther { by, by_type }) => (by, by_type),
+                };
+                let trace_target = match by_type {
+                    UsedByType::NamedExport(name) => TraceTarget::NamedExport(name.to_string()),
+                    UsedByType::DefaultExport => TraceTarget::DefaultExport,
+                    UsedByType::LocalVar(name) => TraceTarget::LocalVar(name.to_string()),
+                };
+                res.push((by.clone(), trace_target));
+            }
+        }
+        Ok(res)
+    }
+
+    fn routes(&self, _module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    fn search_translations(
+        &self,
+        _search: &str,
+        _exact_match: bool,
+    ) -> anyhow::Result<Vec<String>> {
+        Ok(vec![])
+    }
+
+    fn translation_usages(&self, _i18n_key: &str) -> anyhow::Result<Vec<ModuleSymbol>> {
+        Ok(vec![])
+    }
+}
+
+impl GraphStore for Portable {
+    fn modules(&self) -> anyhow::Result<Vec<String>> {
+        self.used_by_graph.modules()
+    }
+
+    fn symbols(&self, module_path: &str) -> anyhow::Result<Vec<TraceTarget>> {
+        self.used_by_graph.symbols(module_path)
+    }
+
+    fn used_by(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.used_by_graph.used_by(module_symbol)
+    }
+
+    // only the local variables are attached to routes
+    fn routes(&self, (module_path, trace_target): &ModuleSymbol) -> anyhow::Result<Vec<String>> {
+        let routes = match trace_target {
+            TraceTarget::LocalVar(name) => self
+                .symbol_to_route
+                .get(module_path)
+                .and_then(|symbol_to_routes| symbol_to_routes.get(name)),
+            _ => None,
+        };
+        Ok(routes.cloned().unwrap_or_default())
+    }
+
+    fn search_translations(&self, search: &str, exact_match: bool) -> anyhow::Result<Vec<String>> {
+        let mut i18n_keys: Vec<String> = self
+            .translation_json
+            .iter()
+            .filter(|(_, translation)| match exact_match {
+                true => *translation == search,
+                false => translation.contains(search),
+            })
+            .map(|(i18n_key, _)| i18n_key.to_owned())
+            .collect();
+        i18n_keys.sort();
+        Ok(i18n_keys)
+    }
+
+    fn translation_usages(&self, i18n_key: &str) -> anyhow::Result<Vec<ModuleSymbol>> {
+        let mut usages: Vec<ModuleSymbol> = vec![];
+        if let Some(i18n_key_usage) = self.i18n_to_symbol.get(i18n_key) {
+            for (module_path, symbols) in i18n_key_usage.iter() {
+                for symbol in symbols.iter() {
+                    usages.push((
+                        module_path.to_owned(),
+                        TraceTarget::LocalVar(symbol.to_owned()),
+                    ));
+                }
+            }
+        }
+        usages.sort_by(|a, b| (&a.0, a.1.to_string()).cmp(&(&b.0, b.1.to_string())));
+        Ok(usages)
+    }
+}
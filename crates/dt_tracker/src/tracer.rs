@@ -0,0 +1,712 @@
+use super::{ModuleSymbol, MultiTraceGraph, TraceGraph, TraceLimits, TraceResult};
+use anyhow::Context;
+use globset::{Glob, GlobSet, GlobSetBuilder};
+use std::{
+    cell::RefCell,
+    collections::{HashMap, HashSet, VecDeque},
+    time::Instant,
+};
+
+// Where the trackers differ, the direction and the in-memory or database version of the
+// graph, everything else of tracing is shared by `Tracer`.
+pub(crate) trait EdgeLookup {
+    // the used-by symbols, or the depend-on symbols for forward tracing
+    fn get_next(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>>;
+
+    // The same, when only the symbols up to `depth` away from this one will be looked up,
+    // so the lookups loading ahead can stop there.
+    fn get_next_within(
+        &self,
+        module_symbol: &ModuleSymbol,
+        _depth: usize,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.get_next(module_symbol)
+    }
+}
+
+// The stop symbols and modules of `TraceLimits`.
+#[derive(Debug)]
+struct Stops {
+    symbols: HashSet<ModuleSymbol>,
+    modules: GlobSet,
+}
+
+impl Stops {
+    // Whether the symbol is stopped when it's not traced.
+    fn contains(&self, module_symbol: &ModuleSymbol) -> bool {
+        self.symbols.contains(module_symbol) || self.modules.is_match(&module_symbol.0)
+    }
+}
+
+// The stop symbols have no edges, except for the traced symbols, and so do the symbols of
+// the stop modules, except for the modules of the traced symbols. The ones having edges
+// are recorded to report the result as truncated.
+struct StopLookup<'a, L: EdgeLookup> {
+    lookup: &'a L,
+    stops: &'a Stops,
+    traced: &'a [ModuleSymbol],
+    stopped: RefCell<HashSet<ModuleSymbol>>,
+}
+
+impl<'a, L: EdgeLookup> StopLookup<'a, L> {
+    fn new(lookup: &'a L, stops: &'a Stops, traced: &'a [ModuleSymbol]) -> Self {
+        Self {
+            lookup,
+            stops,
+            traced,
+            stopped: RefCell::new(HashSet::new()),
+        }
+    }
+
+    fn is_stop(&self, module_symbol: &ModuleSymbol) -> bool {
+        if self.traced.contains(module_symbol) {
+            return false;
+        }
+        self.stops.symbols.contains(module_symbol)
+            || (self.traced.iter().all(|traced| traced.0 != module_symbol.0)
+                && self.stops.modules.is_match(&module_symbol.0))
+    }
+}
+
+impl<'a, L: EdgeLookup> StopLookup<'a, L> {
+    fn stop(
+        &self,
+        module_symbol: &ModuleSymbol,
+        next: Vec<ModuleSymbol>,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        if !next.is_empty() && self.is_stop(module_symbol) {
+            self.stopped.borrow_mut().insert(module_symbol.clone());
+            return Ok(vec![]);
+        }
+        Ok(next)
+    }
+}
+
+impl<'a, L: EdgeLookup> EdgeLookup for StopLookup<'a, L> {
+    fn get_next(&self, module_symbol: &ModuleSymbol) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.stop(module_symbol, self.lookup.get_next(module_symbol)?)
+    }
+
+    fn get_next_within(
+        &self,
+        module_symbol: &ModuleSymbol,
+        depth: usize,
+    ) -> anyhow::Result<Vec<ModuleSymbol>> {
+        self.stop(
+            module_symbol,
+            self.lookup.get_next_within(module_symbol, depth)?,
+        )
+    }
+}
+
+fn is_over(deadline: Option<Instant>) -> bool {
+    deadline.is_some_and(|deadline| Instant::now() >= deadline)
+}
+
+// The edges reachable from the traced symbol, and the symbols in cycles.
+#[derive(Default)]
+struct Subgraph {
+    next: HashMap<ModuleSymbol, Vec<ModuleSymbol>>,
+    cyclic: HashSet<ModuleSymbol>,
+    // the symbols at `max_depth` having edges, they're not followed
+    max_depth_reached: HashSet<ModuleSymbol>,
+    // the symbols left in the queue are not followed either
+    time_budget_exceeded: bool,
+}
+
+// The edges within `max_depth` from the traced symbols, the cycles are not searched since
+// nothing is cached with the limits or when tracing the graph.
+fn collect_within_depth(
+    lookup: &impl EdgeLookup,
+    module_symbols: &[ModuleSymbol],
+    max_depth: usize,
+    deadline: Option<Instant>,
+) -> anyhow::Result<Subgraph> {
+    let mut subgraph = Subgraph::default();
+    let mut queue: VecDeque<(ModuleSymbol, usize)> =
+        module_symbols.iter().map(|s| (s.clone(), 0)).collect();
+    let mut visited: HashSet<ModuleSymbol> = module_symbols.iter().cloned().collect();
+    while let Some((module_symbol, depth)) = queue.pop_front() {
+        if is_over(deadline) {
+            subgraph.time_budget_exceeded = true;
+            break;
+        }
+        let next = match max_depth {
+            usize::MAX => lookup.get_next(&module_symbol)?,
+            _ => lookup.get_next_within(&module_symbol, max_depth - depth)?,
+        };
+        if depth == max_depth {
+            if !next.is_empty() {
+                subgraph.max_depth_reached.insert(module_symbol);
+            }
+            continue;
+        }
+        for next_target in next.iter() {
+            if visited.insert(next_target.clone()) {
+                queue.push_back((next_target.clone(), depth + 1));
+            }
+        }
+        subgraph.next.insert(module_symbol, next);
+    }
+    Ok(subgraph)
+}
+
+// Tarjan's strongly connected components algorithm, the symbols of the components with
+// more than one symbol (or used by themselves) are in cycles. The symbols being searched
+// are kept in an explicit stack instead of recursing, a long chain of symbols would
+// overflow the call stack.
+struct CycleFinder<'a, L: EdgeLookup> {
+    lookup: &'a L,
+    // the cached symbols are never in cycles, so they are leaves here
+    cache: &'a HashMap<ModuleSymbol, TraceResult>,
+    subgraph: Subgraph,
+    index: HashMap<ModuleSymbol, usize>,
+    lowlink: HashMap<ModuleSymbol, usize>,
+    stack: Vec<ModuleSymbol>,
+    on_stack: HashSet<ModuleSymbol>,
+}
+
+// A symbol being searched, and the number of its edges walked.
+struct Connecting {
+    module_symbol: ModuleSymbol,
+    index: usize,
+    next_symbols: Vec<ModuleSymbol>,
+    walked: usize,
+}
+
+impl<'a, L: EdgeLookup> CycleFinder<'a, L> {
+    fn find(
+        lookup: &'a L,
+        cache: &'a HashMap<ModuleSymbol, TraceResult>,
+        module_symbol: &ModuleSymbol,
+    ) -> anyhow::Result<Subgraph> {
+        let mut finder = Self {
+            lookup,
+            cache,
+            subgraph: Subgraph::default(),
+            index: HashMap::new(),
+            lowlink: HashMap::new(),
+            stack: vec![],
+            on_stack: HashSet::new(),
+        };
+        let mut connecting = vec![finder.enter(module_symbol.clone())?];
+        while let Some(current) = connecting.last_mut() {
+            let next = match current.next_symbols.get(current.walked) {
+                Some(next) => next.clone(),
+                None => {
+                    let current = connecting.pop().unwrap();
+                    let lowlink = finder.lowlink[&current.module_symbol];
+                    finder.leave(current);
+                    if let Some(parent) = connecting.last() {
+                        finder.lower_lowlink(&parent.module_symbol, lowlink);
+                    }
+                    continue;
+                }
+            };
+            current.walked += 1;
+            if finder.cache.contains_key(&next) {
+                continue;
+            }
+            match finder.index.get(&next) {
+                None => connecting.push(finder.enter(next)?),
+                Some(next_index) if finder.on_stack.contains(&next) => {
+                    let next_index = *next_index;
+                    let module_symbol = current.module_symbol.clone();
+                    finder.lower_lowlink(&module_symbol, next_index);
+                }
+                Some(_) => {}
+            }
+        }
+        Ok(finder.subgraph)
+    }
+
+    fn enter(&mut self, module_symbol: ModuleSymbol) -> anyhow::Result<Connecting> {
+        let index = self.index.len();
+        self.index.insert(module_symbol.clone(), index);
+        self.lowlink.insert(module_symbol.clone(), index);
+        self.stack.push(module_symbol.clone());
+        self.on_stack.insert(module_symbol.clone());
+        let next_symbols = self.lookup.get_next(&module_symbol)?;
+        Ok(Connecting {
+            module_symbol,
+            index,
+            next_symbols,
+            walked: 0,
+        })
+    }
+
+    fn lower_lowlink(&mut self, module_symbol: &ModuleSymbol, lowlink: usize) {
+        if lowlink < self.lowlink[module_symbol] {
+            self.lowlink.insert(module_symbol.clone(), lowlink);
+        }
+    }
+
+    // All the edges of the symbol are walked.
+    fn leave(&mut self, connecting: Connecting) {
+        let Connecting {
+            module_symbol,
+            index,
+            next_symbols,
+            ..
+        } = connecting;
+        if self.lowlink[&module_symbol] == index {
+            let mut component = vec![];
+            loop {
+                let symbol = self.stack.pop().unwrap();
+                self.on_stack.remove(&symbol);
+                let is_root = symbol == module_symbol;
+                component.push(symbol);
+                if is_root {
+                    break;
+                }
+            }
+            if component.len() > 1 || next_symbols.contains(&module_symbol) {
+                self.subgraph.cyclic.extend(component);
+            }
+        }
+        self.subgraph.next.insert(module_symbol, next_symbols);
+    }
+}
+
+// [A, B, C, A] and [B, C, A, B] are the same cycle.
+fn is_same_cycle(a: &[ModuleSymbol], b: &[ModuleSymbol]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let (a, b) = (&a[1..], &b[1..]);
+    (0..a.len()).any(|offset| (0..a.len()).all(|i| a[(i + offset) % a.len()] == b[i]))
+}
+
+fn add_cycle(cycles: &mut Vec<Vec<ModuleSymbol>>, cycle: Vec<ModuleSymbol>) {
+    if !cycles.iter().any(|c| is_same_cycle(c, &cycle)) {
+        cycles.push(cycle);
+    }
+}
+
+// A symbol of the current path of `Tracer::visit()`, the result of the edges walked so far.
+struct Visiting<'s> {
+    module_symbol: ModuleSymbol,
+    res: TraceResult,
+    next_targets: &'s [ModuleSymbol],
+    walked: usize,
+    is_interrupted: bool,
+    // a stop symbol, or a symbol of a stop module, is reached
+    reaches_stop: bool,
+}
+
+fn merge_result(res: &mut TraceResult, next_res: TraceResult) {
+    res.paths.extend(next_res.paths);
+    for cycle in next_res.cycles {
+        add_cycle(&mut res.cycles, cycle);
+    }
+    res.truncation.merge(next_res.truncation);
+}
+
+#[derive(Debug)]
+pub(crate) struct Tracer {
+    cache: HashMap<ModuleSymbol, TraceResult>,
+    trace_full_path_only: bool,
+    limits: TraceLimits,
+    stops: Stops,
+    // the state of the current trace for `max_paths` and `time_budget`
+    path_count: usize,
+    deadline: Option<Instant>,
+    max_paths_reached: bool,
+    time_budget_exceeded: bool,
+}
+
+impl Tracer {
+    pub fn new(trace_full_path_only: bool) -> Self {
+        Self {
+            cache: HashMap::new(),
+            trace_full_path_only,
+            limits: TraceLimits::default(),
+            stops: Stops {
+                symbols: HashSet::new(),
+                modules: GlobSet::empty(),
+            },
+            path_count: 0,
+            deadline: None,
+            max_paths_reached: false,
+            time_budget_exceeded: false,
+        }
+    }
+
+    pub fn set_max_depth(&mut self, max_depth: Option<usize>) {
+        self.limits.max_depth = max_depth;
+        self.cache.clear();
+    }
+
+    pub fn set_limits(&mut self, limits: TraceLimits) -> anyhow::Result<()> {
+        let mut builder = GlobSetBuilder::new();
+        for pattern in limits.stop_modules.iter() {
+            builder.add(Glob::new(pattern).context(format!("invalid glob {}", pattern))?);
+        }
+        self.stops = Stops {
+            symbols: limits.stop_symbols.iter().cloned().collect(),
+            modules: builder.build()?,
+        };
+        self.limits = limits;
+        self.cache.clear();
+        Ok(())
+    }
+
+    // The result depends on how deep a symbol is from the traced one, the paths found
+    // before it and the time taken, so the cache is only used without those limits.
+    fn is_cacheable(&self) -> bool {
+        self.limits.max_depth.is_none()
+            && self.limits.max_paths.is_none()
+            && self.limits.time_budget.is_none()
+    }
+
+    pub fn trace(
+        &mut self,
+        lookup: &impl EdgeLookup,
+        module_symbol: ModuleSymbol,
+    ) -> anyhow::Result<TraceResult> {
+        // early return if cached
+        if let Some(cached) = self.cache.get(&module_symbol) {
+            return Ok(cached.clone());
+        }
+
+        self.path_count = 0;
+        self.deadline = self
+            .limits
+            .time_budget
+            .map(|time_budget| Instant::now() + time_budget);
+        self.max_paths_reached = false;
+        let traced = [module_symbol];
+        let lookup = StopLookup::new(lookup, &self.stops, &traced);
+        let subgraph = match self.is_cacheable() {
+            true => CycleFinder::find(&lookup, &self.cache, &traced[0])?,
+            false => collect_within_depth(
+                &lookup,
+                &traced,
+                self.limits.max_depth.unwrap_or(usize::MAX),
+                self.deadline,
+            )?,
+        };
+        let stopped = lookup.stopped.into_inner();
+        self.time_budget_exceeded = subgraph.time_budget_exceeded;
+        let [module_symbol] = traced;
+        let mut res = self.visit(&subgraph, &stopped, module_symbol);
+        res.truncation.max_paths_reached = self.max_paths_reached;
+        res.truncation.time_budget_exceeded = self.time_budget_exceeded;
+        Ok(res)
+    }
+
+    // Walk the edges depth-first and number the symbols in the order they're found, an
+    // edge back to a symbol of the current path closes a cycle. Every symbol and edge is
+    // visited once, so the size of the result is linear to the size of the graph, and
+    // `max_paths` doesn't apply.
+    pub fn trace_graph(
+        &self,
+        lookup: &impl EdgeLookup,
+        module_symbol: ModuleSymbol,
+    ) -> anyhow::Result<TraceGraph> {
+        Ok(self.trace_sources(lookup, vec![module_symbol])?.graph)
+    }
+
+    // Trace the symbols together as a super node using all of them. The subgraph is
+    // collected once and each source is walked from in turn, a symbol already walked from
+    // by an earlier source only gets the edge to it, so the shared parts are traced once.
+    pub fn trace_sources(
+        &self,
+        lookup: &impl EdgeLookup,
+        module_symbols: Vec<ModuleSymbol>,
+    ) -> anyhow::Result<MultiTraceGraph> {
+        let mut sources: Vec<ModuleSymbol> = vec![];
+        for module_symbol in module_symbols {
+            if !sources.contains(&module_symbol) {
+                sources.push(module_symbol);
+            }
+        }
+        let max_depth = self.limits.max_depth.unwrap_or(usize::MAX);
+        let deadline = self
+            .limits
+            .time_budget
+            .map(|time_budget| Instant::now() + time_budget);
+        let lookup = StopLookup::new(lookup, &self.stops, &sources);
+        let subgraph = collect_within_depth(&lookup, &sources, max_depth, deadline)?;
+        let stopped = lookup.stopped.into_inner();
+
+        let mut graph = TraceGraph {
+            nodes: sources.clone(),
+            ..TraceGraph::default()
+        };
+        let mut index: HashMap<ModuleSymbol, usize> = sources
+            .iter()
+            .enumerate()
+            .map(|(i, module_symbol)| (module_symbol.clone(), i))
+            .collect();
+        let mut edges = HashSet::new();
+        // the edges closing the cycles are left out of the graph, but a source reaching
+        // one symbol of a cycle reaches all of them
+        let mut back_edges = vec![];
+        // whether the edges of each node are walked, the nodes in the order they're done
+        // with, the reverse of it is a topological order of the graph
+        let mut walked_from = vec![false; sources.len()];
+        let mut done = vec![];
+        for source in 0..sources.len() {
+            if walked_from[source] {
+                continue;
+            }
+            walked_from[source] = true;
+            // the current path and the number of edges walked from each symbol of it
+            let mut stack = vec![(source, 0)];
+            let mut on_stack = HashSet::from([source]);
+            while let Some((from, walked)) = stack.last_mut() {
+                let from = *from;
+                // the symbols at `max_depth` are not followed
+                let next_targets = match subgraph.next.get(&graph.nodes[from]) {
+                    Some(next_targets) => &next_targets[..],
+                    None => &[][..],
+                };
+                let next_target = match next_targets.get(*walked) {
+                    Some(next_target) => next_target,
+                    None => {
+                        done.push(from);
+                        on_stack.remove(&from);
+                        stack.pop();
+                        continue;
+                    }
+                };
+                *walked += 1;
+                let to = match index.get(next_target) {
+                    Some(to) => *to,
+                    None => {
+                        let to = graph.nodes.len();
+                        index.insert(next_target.clone(), to);
+                        graph.nodes.push(next_target.clone());
+                        walked_from.push(false);
+                        to
+                    }
+                };
+                if on_stack.contains(&to) {
+                    let i = stack.iter().position(|(node, _)| *node == to).unwrap();
+                    let mut cycle: Vec<ModuleSymbol> = stack[i..]
+                        .iter()
+                        .map(|(node, _)| graph.nodes[*node].clone())
+                        .collect();
+                    cycle.push(next_target.clone());
+                    add_cycle(&mut graph.cycles, cycle);
+                    back_edges.push((from, to));
+                    continue;
+                }
+                if edges.insert((from, to)) {
+                    graph.edges.push((from, to));
+                }
+                if !walked_from[to] {
+                    walked_from[to] = true;
+                    stack.push((to, 0));
+                    on_stack.insert(to);
+                }
+            }
+        }
+        for module_symbol in graph.nodes.iter() {
+            if subgraph.max_depth_reached.contains(module_symbol) {
+                graph
+                    .truncation
+                    .max_depth_reached
+                    .push(module_symbol.clone());
+            }
+            if stopped.contains(module_symbol) {
+                graph.truncation.stopped_at.push(module_symbol.clone());
+            }
+        }
+        graph.truncation.time_budget_exceeded = subgraph.time_budget_exceeded;
+
+        // Spread the sources along the edges in the topological order, as bit sets. The
+        // back edges may carry sources to the symbols done with earlier, so it's repeated
+        // until nothing changes, which takes one more round for each nesting of cycles.
+        let words = sources.len().div_ceil(64);
+        let mut reached = vec![vec![0u64; words]; graph.nodes.len()];
+        for (source, bits) in reached.iter_mut().take(sources.len()).enumerate() {
+            bits[source / 64] |= 1 << (source % 64);
+        }
+        let mut next = vec![vec![]; graph.nodes.len()];
+        for (from, to) in graph.edges.iter().chain(back_edges.iter()) {
+            next[*from].push(*to);
+        }
+        let mut changed = true;
+        while changed {
+            changed = false;
+            for from in done.iter().rev() {
+                let from_bits = reached[*from].clone();
+                for to in next[*from].iter() {
+                    for (to_word, from_word) in reached[*to].iter_mut().zip(from_bits.iter()) {
+                        if *to_word | from_word != *to_word {
+                            *to_word |= from_word;
+                            changed = true;
+                        }
+                    }
+                }
+            }
+        }
+        let reached_by = reached
+            .iter()
+            .map(|bits| {
+                (0..sources.len())
+                    .filter(|source| bits[source / 64] & (1 << (source % 64)) != 0)
+                    .collect()
+            })
+            .collect();
+        Ok(MultiTraceGraph {
+            sources,
+            graph,
+            reached_by,
+        })
+    }
+
+    // Walk the edges, an edge back to a symbol of the current path closes a cycle, it's
+    // reported and not followed. The symbols at `max_depth` and the stop symbols are not
+    // followed either. Nothing is followed once `max_paths` paths are found or the time
+    // budget is exceeded. The current path is an explicit stack instead of recursing, so
+    // a long chain of symbols doesn't overflow the call stack, and the results are built
+    // in the same order as walking recursively.
+    fn visit(
+        &mut self,
+        subgraph: &Subgraph,
+        stopped: &HashSet<ModuleSymbol>,
+        module_symbol: ModuleSymbol,
+    ) -> TraceResult {
+        if let Some(cached) = self.cache.get(&module_symbol) {
+            return cached.clone();
+        }
+
+        let mut current_path = vec![self.enter(subgraph, stopped, 1, module_symbol)];
+        loop {
+            let visiting = current_path.last_mut().unwrap();
+            let next_targets = visiting.next_targets;
+            let next_target = next_targets.get(visiting.walked);
+            if next_target.is_some() {
+                if self.is_path_count_reached() {
+                    self.max_paths_reached = true;
+                    visiting.is_interrupted = true;
+                } else if is_over(self.deadline) {
+                    self.time_budget_exceeded = true;
+                    visiting.is_interrupted = true;
+                }
+            }
+            let next_target = match next_target {
+                Some(next_target) if !visiting.is_interrupted => next_target,
+                _ => {
+                    let visiting = current_path.pop().unwrap();
+                    let reaches_stop = visiting.reaches_stop;
+                    let res = self.leave(subgraph, visiting);
+                    match current_path.last_mut() {
+                        Some(prev) => {
+                            prev.reaches_stop |= reaches_stop;
+                            merge_result(&mut prev.res, res);
+                        }
+                        None => return res,
+                    }
+                    continue;
+                }
+            };
+            visiting.walked += 1;
+
+            if let Some(i) = current_path
+                .iter()
+                .position(|visiting| visiting.module_symbol == *next_target)
+            {
+                let mut cycle: Vec<ModuleSymbol> = current_path[i..]
+                    .iter()
+                    .map(|visiting| visiting.module_symbol.clone())
+                    .collect();
+                cycle.push(next_target.clone());
+                add_cycle(&mut current_path.last_mut().unwrap().res.cycles, cycle);
+                continue;
+            }
+            if let Some(cached) = self.cache.get(next_target) {
+                merge_result(&mut current_path.last_mut().unwrap().res, cached.clone());
+                continue;
+            }
+            let depth = current_path.len() + 1;
+            current_path.push(self.enter(subgraph, stopped, depth, next_target.clone()));
+        }
+    }
+
+    // Start visiting the symbol `depth` symbols away from the traced one, counting both.
+    fn enter<'s>(
+        &self,
+        subgraph: &'s Subgraph,
+        stopped: &HashSet<ModuleSymbol>,
+        depth: usize,
+        module_symbol: ModuleSymbol,
+    ) -> Visiting<'s> {
+        let mut res = TraceResult::default();
+        if stopped.contains(&module_symbol) {
+            res.truncation.stopped_at.push(module_symbol.clone());
+        }
+        let next_targets = match subgraph.next.get(&module_symbol) {
+            Some(next_targets) => &next_targets[..],
+            None => &[][..],
+        };
+        let next_targets = match self.limits.max_depth {
+            Some(max_depth) if depth > max_depth => {
+                if !next_targets.is_empty() || subgraph.max_depth_reached.contains(&module_symbol) {
+                    res.truncation.max_depth_reached.push(module_symbol.clone());
+                }
+                &[][..]
+            }
+            _ => next_targets,
+        };
+        Visiting {
+            reaches_stop: self.stops.contains(&module_symbol),
+            module_symbol,
+            res,
+            next_targets,
+            walked: 0,
+            is_interrupted: false,
+        }
+    }
+
+    // All the edges of the symbol are walked, or it's interrupted.
+    fn leave(&mut self, subgraph: &Subgraph, visiting: Visiting) -> TraceResult {
+        let Visiting {
+            module_symbol,
+            mut res,
+            is_interrupted,
+            reaches_stop,
+            ..
+        } = visiting;
+        // append current ModuleSymbol to each path
+        for path in res.paths.iter_mut() {
+            path.push(module_symbol.clone());
+        }
+        // Because we only want to trace the full path, we only need to add a new path
+        // when this ModuleSymbol is not used by anyone. Otherwise always append the current
+        // ModuleSymbol since we want to list every single path that is reachable from the
+        // target. An interrupted symbol is not the end of a full path.
+        let is_path = match self.trace_full_path_only {
+            true => res.paths.is_empty() && !is_interrupted,
+            false => true,
+        };
+        if is_path {
+            match self.is_path_count_reached() {
+                true => self.max_paths_reached = true,
+                false => {
+                    res.paths.push(vec![module_symbol.clone()]);
+                    self.path_count += 1;
+                }
+            }
+        }
+
+        // The result of a symbol in a cycle depends on where the cycle is entered, so
+        // it can't be cached. Other symbols can't reach the current path, their result
+        // is always the same. The stop symbols, and the symbols of the stop modules, are
+        // only followed when they're traced or in the traced module, so the results
+        // reaching them are not cached either.
+        if self.is_cacheable() && !subgraph.cyclic.contains(&module_symbol) && !reaches_stop {
+            self.cache.insert(module_symbol, res.clone());
+        }
+        res
+    }
+
+    fn is_path_count_reached(&self) -> bool {
+        self.limits
+            .max_paths
+            .is_some_and(|max_paths| self.path_count >= max_paths)
+    }
+}
@@ -0,0 +1,52 @@
+use super::{Granularity, Graph};
+use serde_json::{json, Value};
+
+// The elements JSON of Cytoscape.js, the modules are compound nodes containing their
+// symbols for the symbol granularity.
+pub(crate) fn render(graph: &Graph) -> String {
+    let mut nodes: Vec<Value> = vec![];
+    for (i, (module_path, indexes)) in graph.group_by_module().into_iter().enumerate() {
+        nodes.push(json!({ "data": { "id": format!("m{}", i), "label": module_path } }));
+        for index in indexes {
+            let node = &graph.nodes[index];
+            nodes.push(json!({
+                "data": {
+                    "id": format!("n{}", index),
+                    "label": node.label(),
+                    "module": node.module_path,
+                    "parent": format!("m{}", i),
+                }
+            }));
+        }
+    }
+    if graph.granularity == Granularity::Module {
+        for (index, node) in graph.nodes.iter().enumerate() {
+            nodes.push(json!({
+                "data": {
+                    "id": format!("n{}", index),
+                    "label": node.label(),
+                    "module": node.module_path,
+                }
+            }));
+        }
+    }
+    let edges: Vec<Value> = graph
+        .edges
+        .iter()
+        .enumerate()
+        .map(|(i, edge)| {
+            json!({
+                "data": {
+                    "id": format!("e{}", i),
+                    "source": format!("n{}", edge.from),
+                    "target": format!("n{}", edge.to),
+                    "label": edge.label(),
+                    "kind": edge.kind.as_str(),
+                    "weight": edge.weight,
+                }
+            })
+        })
+        .collect();
+    serde_json::to_string_pretty(&json!({ "elements": { "nodes": nodes, "edges": edges } }))
+        .unwrap()
+}
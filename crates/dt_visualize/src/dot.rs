@@ -0,0 +1,40 @@
+use super::{Granularity, Graph};
+
+pub(crate) fn render(graph: &Graph) -> String {
+    let mut dot = String::from("digraph {\n");
+    for (i, (module_path, indexes)) in graph.group_by_module().into_iter().enumerate() {
+        dot.push_str(&format!("    subgraph cluster_{} {{\n", i));
+        dot.push_str(&format!("        label={};\n", quote(module_path)));
+        for index in indexes {
+            dot.push_str(&format!(
+                "        n{} [label={}];\n",
+                index,
+                quote(&graph.nodes[index].label())
+            ));
+        }
+        dot.push_str("    }\n");
+    }
+    if graph.granularity == Granularity::Module {
+        for (index, node) in graph.nodes.iter().enumerate() {
+            dot.push_str(&format!(
+                "    n{} [label={}];\n",
+                index,
+                quote(&node.label())
+            ));
+        }
+    }
+    for edge in graph.edges.iter() {
+        dot.push_str(&format!(
+            "    n{} -> n{} [label={}];\n",
+            edge.from,
+            edge.to,
+            quote(&edge.label())
+        ));
+    }
+    dot.push_str("}\n");
+    dot
+}
+
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('\\', "\\\\").replace('"', "\\\""))
+}
@@ -0,0 +1,40 @@
+use super::Graph;
+
+pub(crate) fn render(graph: &Graph) -> String {
+    let mut graphml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8"?>
+<graphml xmlns="http://graphml.graphdrawing.org/xmlns">
+  <key id="label" for="node" attr.name="label" attr.type="string"/>
+  <key id="module" for="node" attr.name="module" attr.type="string"/>
+  <key id="kind" for="edge" attr.name="kind" attr.type="string"/>
+  <key id="weight" for="edge" attr.name="weight" attr.type="int"/>
+  <graph id="G" edgedefault="directed">
+"#,
+    );
+    for (index, node) in graph.nodes.iter().enumerate() {
+        graphml.push_str(&format!(
+            "    <node id=\"n{}\"><data key=\"label\">{}</data><data key=\"module\">{}</data></node>\n",
+            index,
+            escape(&node.label()),
+            escape(&node.module_path)
+        ));
+    }
+    for edge in graph.edges.iter() {
+        graphml.push_str(&format!(
+            "    <edge source=\"n{}\" target=\"n{}\"><data key=\"kind\">{}</data><data key=\"weight\">{}</data></edge>\n",
+            edge.from,
+            edge.to,
+            edge.kind.as_str(),
+            edge.weight
+        ));
+    }
+    graphml.push_str("  </graph>\n</graphml>\n");
+    graphml
+}
+
+fn escape(s: &str) -> String {
+    s.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
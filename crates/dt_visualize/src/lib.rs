@@ -0,0 +1,381 @@
+mod cytoscape;
+mod dot;
+mod graphml;
+mod mermaid;
+
+use dt_graph::{
+    projection::Projection,
+    used_by_graph::{UsedBy, UsedByGraph, UsedByOther, UsedByType},
+};
+use dt_tracker::{ModuleSymbol, TraceGraph, TraceResult, TraceTarget};
+use std::collections::{HashMap, HashSet};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Dot,
+    GraphMl,
+    Mermaid,
+    Cytoscape,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Symbol,
+    Module,
+}
+
+// The paths of `DependencyTracker` go from the users to the traced symbol, the ones of
+// `DependOnTracker` go from the dependencies to the traced symbol.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceDirection {
+    UsedBy,
+    DependOn,
+}
+
+// The edge rules of the README, an edge from `A` to `B` means `A` depends on `B`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum EdgeKind {
+    // a local variable is lexically contained within another one of the same module
+    Lexical,
+    // a local variable is imported from another module
+    Import,
+    // a module exports its local variable
+    Export,
+    // a module re-exports the export of another module
+    ReExport,
+}
+
+impl EdgeKind {
+    fn new(from: &ModuleSymbol, to: &ModuleSymbol) -> Self {
+        match (from.0 == to.0, &from.1) {
+            (true, TraceTarget::LocalVar(_)) => EdgeKind::Lexical,
+            (true, _) => EdgeKind::Export,
+            (false, TraceTarget::LocalVar(_)) => EdgeKind::Import,
+            (false, _) => EdgeKind::ReExport,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EdgeKind::Lexical => "lexical",
+            EdgeKind::Import => "import",
+            EdgeKind::Export => "export",
+            EdgeKind::ReExport => "re-export",
+        }
+    }
+}
+
+// A module or a node of a projection for the module granularity, or a symbol of the
+// module.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Node {
+    pub module_path: String,
+    pub symbol: Option<TraceTarget>,
+}
+
+impl Node {
+    pub fn label(&self) -> String {
+        match &self.symbol {
+            None => self.module_path.to_owned(),
+            Some(TraceTarget::LocalVar(name)) => name.to_owned(),
+            Some(TraceTarget::NamedExport(name)) => format!("export {}", name),
+            Some(TraceTarget::DefaultExport) => String::from("export default"),
+        }
+    }
+
+    fn sort_key(&self) -> (&str, u8, &str) {
+        match &self.symbol {
+            None => (&self.module_path, 0, ""),
+            Some(TraceTarget::LocalVar(name)) => (&self.module_path, 1, name),
+            Some(TraceTarget::NamedExport(name)) => (&self.module_path, 2, name),
+            Some(TraceTarget::DefaultExport) => (&self.module_path, 3, ""),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    // indexes of `Graph::nodes`
+    pub from: usize,
+    pub to: usize,
+    pub kind: EdgeKind,
+    // the number of symbol edges collapsed into this one, it's always 1 for the symbol
+    // granularity
+    pub weight: usize,
+}
+
+impl Edge {
+    pub fn label(&self) -> String {
+        match self.weight {
+            1 => self.kind.as_str().to_owned(),
+            weight => format!("{} ({})", self.kind.as_str(), weight),
+        }
+    }
+}
+
+// The graph to render, the nodes are sorted by module path so the output is stable.
+//
+// ```ignore
+// let graph = Graph::from_used_by_graph(&used_by_graph, Granularity::Module);
+// println!("{}", graph.render(Format::Mermaid));
+// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct Graph {
+    pub granularity: Granularity,
+    pub nodes: Vec<Node>,
+    pub edges: Vec<Edge>,
+}
+
+impl Graph {
+    pub fn from_used_by_graph(used_by_graph: &UsedByGraph, granularity: Granularity) -> Self {
+        let mut builder = GraphBuilder::new(granularity);
+        for (module_path, module) in used_by_graph.modules.iter() {
+            let local_variables = module
+                .local_variable_table
+                .iter()
+                .map(|(name, used_by)| (TraceTarget::LocalVar(name.to_owned()), used_by));
+            let named_exports = module
+                .named_export_table
+                .iter()
+                .map(|(name, used_by)| (TraceTarget::NamedExport(name.to_owned()), used_by));
+            // unused default exports are None as well, the used ones are found from
+            // their used-by lists and the local variables exported as default
+            let default_export = module
+                .default_export
+                .as_ref()
+                .map(|_| (TraceTarget::DefaultExport, &module.default_export));
+            builder.add_module(module_path);
+            for (symbol, used_by_list) in local_variables.chain(named_exports).chain(default_export)
+            {
+                let symbol = (module_path.to_owned(), symbol);
+                builder.add_node(&symbol);
+                for used_by in used_by_list.iter().flatten() {
+                    let (by, by_type) = match used_by {
+                        UsedBy::Itself(used_by_type) => (module_path, used_by_type),
+                        UsedBy::Other(UsedByOther { by, by_type }) => (by, by_type),
+                    };
+                    let user = (
+                        by.to_owned(),
+                        match by_type {
+                            UsedByType::NamedExport(name) => {
+                                TraceTarget::NamedExport(name.to_owned())
+                            }
+                            UsedByType::DefaultExport => TraceTarget::DefaultExport,
+                            UsedByType::LocalVar(name) => TraceTarget::LocalVar(name.to_owned()),
+                        },
+                    );
+                    builder.add_edge(&user, &symbol);
+                }
+            }
+        }
+        builder.build()
+    }
+
+    // The nodes of the projection are drawn like modules, the import and re-export
+    // edges between two nodes are drawn separately.
+    pub fn from_projection(projection: &Projection) -> Self {
+        let nodes: Vec<Node> = projection
+            .nodes
+            .iter()
+            .map(|node| Node {
+                module_path: node.to_owned(),
+                symbol: None,
+            })
+            .collect();
+        let ids: HashMap<&str, usize> = projection
+            .nodes
+            .iter()
+            .enumerate()
+            .map(|(id, node)| (node.as_str(), id))
+            .collect();
+        let mut edges = vec![];
+        for (from, dependencies) in projection.depend_on.iter() {
+            for (to, weight) in dependencies.iter() {
+                for (kind, weight) in [
+                    (EdgeKind::Import, weight.imports),
+                    (EdgeKind::ReExport, weight.re_exports),
+                ] {
+                    if weight > 0 {
+                        edges.push(Edge {
+                            from: ids[from.as_str()],
+                            to: ids[to.as_str()],
+                            kind,
+                            weight,
+                        });
+                    }
+                }
+            }
+        }
+        edges.sort();
+        Graph {
+            granularity: Granularity::Module,
+            nodes,
+            edges,
+        }
+    }
+
+    // The subgraph of the traced paths and cycles.
+    pub fn from_trace_result(
+        trace_result: &TraceResult,
+        direction: TraceDirection,
+        granularity: Granularity,
+    ) -> Self {
+        let mut builder = GraphBuilder::new(granularity);
+        for path in trace_result.paths.iter() {
+            if let [module_symbol] = path.as_slice() {
+                builder.add_node(module_symbol);
+            }
+            for step in path.windows(2) {
+                match direction {
+                    TraceDirection::UsedBy => builder.add_edge(&step[0], &step[1]),
+                    TraceDirection::DependOn => builder.add_edge(&step[1], &step[0]),
+                }
+            }
+        }
+        builder.add_cycles(&trace_result.cycles, direction);
+        builder.build()
+    }
+
+    // The subgraph of the traced graph, see `from_trace_result()`.
+    pub fn from_trace_graph(
+        trace_graph: &TraceGraph,
+        direction: TraceDirection,
+        granularity: Granularity,
+    ) -> Self {
+        let mut builder = GraphBuilder::new(granularity);
+        for module_symbol in trace_graph.nodes.iter() {
+            builder.add_node(module_symbol);
+        }
+        // the edges follow the traced direction like the cycles
+        for (from, to) in trace_graph.edges.iter() {
+            let (from, to) = (&trace_graph.nodes[*from], &trace_graph.nodes[*to]);
+            match direction {
+                TraceDirection::UsedBy => builder.add_edge(to, from),
+                TraceDirection::DependOn => builder.add_edge(from, to),
+            }
+        }
+        builder.add_cycles(&trace_graph.cycles, direction);
+        builder.build()
+    }
+
+    pub fn render(&self, format: Format) -> String {
+        match format {
+            Format::Dot => dot::render(self),
+            Format::GraphMl => graphml::render(self),
+            Format::Mermaid => mermaid::render(self),
+            Format::Cytoscape => cytoscape::render(self),
+        }
+    }
+
+    // Group the node indexes by module path for the renderers drawing the modules as
+    // clusters, it's empty for the module granularity.
+    fn group_by_module(&self) -> Vec<(&str, Vec<usize>)> {
+        let mut groups: Vec<(&str, Vec<usize>)> = vec![];
+        if self.granularity == Granularity::Module {
+            return groups;
+        }
+        for (i, node) in self.nodes.iter().enumerate() {
+            match groups.last_mut() {
+                Some((module_path, indexes)) if *module_path == node.module_path => indexes.push(i),
+                _ => groups.push((&node.module_path, vec![i])),
+            }
+        }
+        groups
+    }
+}
+
+struct GraphBuilder {
+    granularity: Granularity,
+    nodes: HashMap<Node, usize>,
+    // (from, to, kind) => weight
+    edges: HashMap<(usize, usize, EdgeKind), usize>,
+    // the traced paths share the edges, they are only counted once
+    symbol_edges: HashSet<(ModuleSymbol, ModuleSymbol)>,
+}
+
+impl GraphBuilder {
+    fn new(granularity: Granularity) -> Self {
+        Self {
+            granularity,
+            nodes: HashMap::new(),
+            edges: HashMap::new(),
+            symbol_edges: HashSet::new(),
+        }
+    }
+
+    fn add_module(&mut self, module_path: &str) {
+        if self.granularity == Granularity::Module {
+            self.insert_node(Node {
+                module_path: module_path.to_owned(),
+                symbol: None,
+            });
+        }
+    }
+
+    fn add_node(&mut self, module_symbol: &ModuleSymbol) -> usize {
+        let symbol = match self.granularity {
+            Granularity::Symbol => Some(module_symbol.1.clone()),
+            Granularity::Module => None,
+        };
+        self.insert_node(Node {
+            module_path: module_symbol.0.to_owned(),
+            symbol,
+        })
+    }
+
+    fn insert_node(&mut self, node: Node) -> usize {
+        let id = self.nodes.len();
+        *self.nodes.entry(node).or_insert(id)
+    }
+
+    // `from` depends on `to`, the edges within a module are dropped for the module
+    // granularity.
+    fn add_edge(&mut self, from: &ModuleSymbol, to: &ModuleSymbol) {
+        if !self.symbol_edges.insert((from.clone(), to.clone())) {
+            return;
+        }
+        let kind = EdgeKind::new(from, to);
+        let from = self.add_node(from);
+        let to = self.add_node(to);
+        if from != to || self.granularity == Granularity::Symbol {
+            *self.edges.entry((from, to, kind)).or_default() += 1;
+        }
+    }
+
+    // the cycles follow the traced edges, unlike the paths they go the other way around
+    fn add_cycles(&mut self, cycles: &[Vec<ModuleSymbol>], direction: TraceDirection) {
+        for cycle in cycles.iter() {
+            for step in cycle.windows(2) {
+                match direction {
+                    TraceDirection::UsedBy => self.add_edge(&step[1], &step[0]),
+                    TraceDirection::DependOn => self.add_edge(&step[0], &step[1]),
+                }
+            }
+        }
+    }
+
+    fn build(self) -> Graph {
+        let mut nodes: Vec<(Node, usize)> = self.nodes.into_iter().collect();
+        nodes.sort_by(|(a, _), (b, _)| a.sort_key().cmp(&b.sort_key()));
+        let mut new_ids = vec![0; nodes.len()];
+        for (new_id, (_, id)) in nodes.iter().enumerate() {
+            new_ids[*id] = new_id;
+        }
+        let mut edges: Vec<Edge> = self
+            .edges
+            .into_iter()
+            .map(|((from, to, kind), weight)| Edge {
+                from: new_ids[from],
+                to: new_ids[to],
+                kind,
+                weight,
+            })
+            .collect();
+        edges.sort();
+        Graph {
+            granularity: self.granularity,
+            nodes: nodes.into_iter().map(|(node, _)| node).collect(),
+            edges,
+        }
+    }
+}
@@ -0,0 +1,35 @@
+use super::{Granularity, Graph};
+
+pub(crate) fn render(graph: &Graph) -> String {
+    let mut mermaid = String::from("flowchart LR\n");
+    for (i, (module_path, indexes)) in graph.group_by_module().into_iter().enumerate() {
+        mermaid.push_str(&format!("    subgraph m{} [{}]\n", i, quote(module_path)));
+        for index in indexes {
+            mermaid.push_str(&format!(
+                "        n{}[{}]\n",
+                index,
+                quote(&graph.nodes[index].label())
+            ));
+        }
+        mermaid.push_str("    end\n");
+    }
+    if graph.granularity == Granularity::Module {
+        for (index, node) in graph.nodes.iter().enumerate() {
+            mermaid.push_str(&format!("    n{}[{}]\n", index, quote(&node.label())));
+        }
+    }
+    for edge in graph.edges.iter() {
+        mermaid.push_str(&format!(
+            "    n{} -->|{}| n{}\n",
+            edge.from,
+            quote(&edge.label()),
+            edge.to
+        ));
+    }
+    mermaid
+}
+
+// quotes can't be escaped with backslashes in mermaid, use the entity code instead
+fn quote(s: &str) -> String {
+    format!("\"{}\"", s.replace('"', "#quot;"))
+}
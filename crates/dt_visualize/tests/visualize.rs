@@ -0,0 +1,272 @@
+use dt_graph::{
+    projection::Projection,
+    used_by_graph::{Module, UsedBy, UsedByGraph, UsedByOther, UsedByType},
+};
+use dt_tracker::{DependencyTracker, TraceResult, TraceTarget};
+use dt_visualize::{EdgeKind, Format, Granularity, Graph, TraceDirection};
+use std::collections::HashMap;
+
+macro_rules! s {
+    ($s:expr) => {{
+        $s.to_string()
+    }};
+}
+
+// a.js
+//     import { b } from './b';
+//     const a = () => b;
+//     export default a;
+// b.js
+//     const b = 1;
+//     export { b };
+// index.js
+//     export { b } from './b';
+fn used_by_graph() -> UsedByGraph {
+    UsedByGraph {
+        modules: HashMap::from([
+            (
+                s!("a.js"),
+                Module {
+                    local_variable_table: HashMap::from([
+                        (
+                            s!("a"),
+                            Some(vec![UsedBy::Itself(UsedByType::DefaultExport)]),
+                        ),
+                        (
+                            s!("b"),
+                            Some(vec![UsedBy::Itself(UsedByType::LocalVar(s!("a")))]),
+                        ),
+                    ]),
+                    named_export_table: HashMap::new(),
+                    default_export: None,
+                },
+            ),
+            (
+                s!("b.js"),
+                Module {
+                    local_variable_table: HashMap::from([(
+                        s!("b"),
+                        Some(vec![UsedBy::Itself(UsedByType::NamedExport(s!("b")))]),
+                    )]),
+                    named_export_table: HashMap::from([(
+                        s!("b"),
+                        Some(vec![
+                            UsedBy::Other(UsedByOther {
+                                by: s!("a.js"),
+                                by_type: UsedByType::LocalVar(s!("b")),
+                            }),
+                            UsedBy::Other(UsedByOther {
+                                by: s!("index.js"),
+                                by_type: UsedByType::NamedExport(s!("b")),
+                            }),
+                        ]),
+                    )]),
+                    default_export: None,
+                },
+            ),
+            (
+                s!("index.js"),
+                Module {
+                    local_variable_table: HashMap::new(),
+                    named_export_table: HashMap::from([(s!("b"), None)]),
+                    default_export: None,
+                },
+            ),
+        ]),
+    }
+}
+
+fn edges(graph: &Graph) -> Vec<(String, String, EdgeKind)> {
+    graph
+        .edges
+        .iter()
+        .map(|edge| {
+            let label =
+                |i: usize| format!("{} {}", graph.nodes[i].module_path, graph.nodes[i].label());
+            (label(edge.from), label(edge.to), edge.kind)
+        })
+        .collect()
+}
+
+#[test]
+fn symbol_granularity() {
+    let graph = Graph::from_used_by_graph(&used_by_graph(), Granularity::Symbol);
+    assert_eq!(graph.nodes.len(), 6);
+    assert_eq!(
+        edges(&graph),
+        vec![
+            (s!("a.js a"), s!("a.js b"), EdgeKind::Lexical),
+            (s!("a.js b"), s!("b.js export b"), EdgeKind::Import),
+            (s!("a.js export default"), s!("a.js a"), EdgeKind::Export),
+            (s!("b.js export b"), s!("b.js b"), EdgeKind::Export),
+            (
+                s!("index.js export b"),
+                s!("b.js export b"),
+                EdgeKind::ReExport
+            ),
+        ]
+    );
+}
+
+#[test]
+fn module_granularity() {
+    let graph = Graph::from_used_by_graph(&used_by_graph(), Granularity::Module);
+    assert_eq!(
+        graph.render(Format::Mermaid),
+        r#"flowchart LR
+    n0["a.js"]
+    n1["b.js"]
+    n2["index.js"]
+    n0 -->|"import"| n1
+    n2 -->|"re-export"| n1
+"#
+    );
+    assert_eq!(
+        graph.render(Format::Dot),
+        r#"digraph {
+    n0 [label="a.js"];
+    n1 [label="b.js"];
+    n2 [label="index.js"];
+    n0 -> n1 [label="import"];
+    n2 -> n1 [label="re-export"];
+}
+"#
+    );
+}
+
+#[test]
+fn traced_subgraph() {
+    let b = (s!("b.js"), TraceTarget::LocalVar(s!("b")));
+    let export_b = (s!("b.js"), TraceTarget::NamedExport(s!("b")));
+    let used_by_paths = vec![
+        vec![
+            (s!("a.js"), TraceTarget::DefaultExport),
+            (s!("a.js"), TraceTarget::LocalVar(s!("a"))),
+            (s!("a.js"), TraceTarget::LocalVar(s!("b"))),
+            export_b.clone(),
+            b.clone(),
+        ],
+        vec![
+            (s!("index.js"), TraceTarget::NamedExport(s!("b"))),
+            export_b.clone(),
+            b.clone(),
+        ],
+    ];
+    let full_graph = Graph::from_used_by_graph(&used_by_graph(), Granularity::Symbol);
+
+    let trace_result = TraceResult {
+        paths: used_by_paths.clone(),
+        cycles: vec![],
+        ..TraceResult::default()
+    };
+    let graph =
+        Graph::from_trace_result(&trace_result, TraceDirection::UsedBy, Granularity::Symbol);
+    assert_eq!(graph, full_graph);
+
+    // the forward tracing paths start from the dependencies
+    let trace_result = TraceResult {
+        paths: used_by_paths
+            .into_iter()
+            .map(|mut path| {
+                path.reverse();
+                path
+            })
+            .collect(),
+        cycles: vec![],
+        ..TraceResult::default()
+    };
+    let graph =
+        Graph::from_trace_result(&trace_result, TraceDirection::DependOn, Granularity::Symbol);
+    assert_eq!(graph, full_graph);
+
+    // the traced graph has the same edges
+    let trace_graph = DependencyTracker::new(&used_by_graph(), true)
+        .trace_graph(b)
+        .unwrap();
+    let graph = Graph::from_trace_graph(&trace_graph, TraceDirection::UsedBy, Granularity::Symbol);
+    assert_eq!(graph, full_graph);
+}
+
+#[test]
+fn render_symbol_granularity() {
+    let trace_result = TraceResult {
+        paths: vec![vec![
+            (s!("b.js"), TraceTarget::NamedExport(s!("b"))),
+            (s!("b.js"), TraceTarget::LocalVar(s!("b"))),
+        ]],
+        cycles: vec![],
+        ..TraceResult::default()
+    };
+    let graph =
+        Graph::from_trace_result(&trace_result, TraceDirection::UsedBy, Granularity::Symbol);
+    assert_eq!(
+        graph.render(Format::Mermaid),
+        r#"flowchart LR
+    subgraph m0 ["b.js"]
+        n0["b"]
+        n1["export b"]
+    end
+    n1 -->|"export"| n0
+"#
+    );
+    assert_eq!(
+        graph.render(Format::Dot),
+        r#"digraph {
+    subgraph cluster_0 {
+        label="b.js";
+        n0 [label="b"];
+        n1 [label="export b"];
+    }
+    n1 -> n0 [label="export"];
+}
+"#
+    );
+    let graphml = graph.render(Format::GraphMl);
+    assert!(graphml.contains(
+        r#"<node id="n1"><data key="label">export b</data><data key="module">b.js</data></node>"#
+    ));
+    assert!(
+        graphml.contains(r#"<edge source="n1" target="n0"><data key="kind">export</data><data key="weight">1</data></edge>"#)
+    );
+
+    let cytoscape: serde_json::Value =
+        serde_json::from_str(&graph.render(Format::Cytoscape)).unwrap();
+    let nodes = cytoscape["elements"]["nodes"].as_array().unwrap();
+    assert_eq!(nodes.len(), 3);
+    assert_eq!(nodes[0]["data"]["label"], "b.js");
+    assert_eq!(nodes[2]["data"]["parent"], "m0");
+    assert_eq!(cytoscape["elements"]["edges"][0]["data"]["label"], "export");
+}
+
+#[test]
+fn projection() {
+    let projection = Projection::new(&used_by_graph(), |module_path| match module_path {
+        "index.js" => s!("barrel"),
+        _ => s!("components"),
+    });
+    let graph = Graph::from_projection(&projection);
+    assert_eq!(
+        graph.render(Format::Mermaid),
+        r#"flowchart LR
+    n0["barrel"]
+    n1["components"]
+    n0 -->|"re-export"| n1
+"#
+    );
+
+    let projection = Projection::new(&used_by_graph(), |module_path| match module_path {
+        "b.js" => s!("b"),
+        _ => s!("others"),
+    });
+    let graph = Graph::from_projection(&projection);
+    assert_eq!(
+        graph.render(Format::Dot),
+        r#"digraph {
+    n0 [label="b"];
+    n1 [label="others"];
+    n1 -> n0 [label="import"];
+    n1 -> n0 [label="re-export"];
+}
+"#
+    );
+}
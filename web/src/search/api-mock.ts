@@ -1,108 +1,72 @@
 import { SearchResult } from "./api";
 
+const modulePath = (name: string) =>
+  `module/path/can/be/super/long/too/bad/${name}.tsx`;
+
+const step = (name: string) => ({
+  module_path: modulePath(name),
+  symbol_name: name,
+});
+
 const mockedTraceResult = {
   "i18n.key.pikachu": {
     "/account": {
-      // paths from Avatar to /account
+      // graph from Avatar to /account
       Avatar: [
-        // path 1
-        [
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Avatar.tsx",
-            symbol_name: "Avatar",
-          },
-          {
-            module_path:
-              "module/path/can/be/super/long/too/bad/SuperBigAvatar.tsx",
-            symbol_name: "SuperBigAvatar",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Header.tsx",
-            symbol_name: "Header",
-          },
-          {
-            module_path:
-              "module/path/can/be/super/long/too/bad/UserProfileHeader.tsx",
-            symbol_name: "UserProfileHeader",
-          },
-          {
-            module_path:
-              "module/path/can/be/super/long/too/bad/UserProfile.tsx",
-            symbol_name: "UserProfile",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Account.tsx",
-            symbol_name: "Account",
-          },
-        ],
-        // path 2
-        [
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Avatar.tsx",
-            symbol_name: "Avatar",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/FriendList.tsx",
-            symbol_name: "FriendList",
-          },
-          {
-            module_path:
-              "module/path/can/be/super/long/too/bad/UserProfile.tsx",
-            symbol_name: "UserProfile",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Account.tsx",
-            symbol_name: "Account",
-          },
-        ],
+        {
+          nodes: [
+            step("Avatar"),
+            step("SuperBigAvatar"),
+            step("Header"),
+            step("UserProfileHeader"),
+            step("UserProfile"),
+            step("Account"),
+            step("FriendList"),
+          ],
+          // Avatar is used by SuperBigAvatar and FriendList, both paths
+          // lead to UserProfile
+          edges: [
+            [0, 1],
+            [1, 2],
+            [2, 3],
+            [3, 4],
+            [4, 5],
+            [0, 6],
+            [6, 4],
+          ] as [number, number][],
+        },
       ],
     },
   },
   "i18n.key.pikapi": {
     "/home": {
-      // paths from Header to /home
+      // graph from Header to /home
       Header: [
-        // path 1
-        [
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Header.tsx",
-            symbol_name: "Header",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Layout.tsx",
-            symbol_name: "Layout",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Home.tsx",
-            symbol_name: "Home",
-          },
-        ],
+        {
+          nodes: [step("Header"), step("Layout"), step("Home")],
+          edges: [
+            [0, 1],
+            [1, 2],
+          ] as [number, number][],
+        },
       ],
     },
     "/account": {
-      // paths from Header to /account
+      // graph from Header to /account
       Header: [
-        // path 1
-        [
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Header.tsx",
-            symbol_name: "Header",
-          },
-          {
-            module_path:
-              "module/path/can/be/super/long/too/bad/UserProfileHeader.tsx",
-            symbol_name: "UserProfileHeader",
-          },
-          {
-            module_path:
-              "module/path/can/be/super/long/too/bad/UserProfile.tsx",
-            symbol_name: "UserProfile",
-          },
-          {
-            module_path: "module/path/can/be/super/long/too/bad/Account.tsx",
-            symbol_name: "Account",
-          },
-        ],
+        {
+          nodes: [
+            step("Header"),
+            step("UserProfileHeader"),
+            step("UserProfile"),
+            step("Account"),
+          ],
+          edges: [
+            [0, 1],
+            [1, 2],
+            [2, 3],
+          ] as [number, number][],
+        },
       ],
     },
   },
@@ -1,10 +1,9 @@
 import * as React from "react";
-import { v4 as uuidv4 } from "uuid";
 import { styled } from "@mui/material";
 import Box from "@mui/material/Box";
 import { SimpleTreeView } from "@mui/x-tree-view/SimpleTreeView";
 import { TreeItem } from "@mui/x-tree-view/TreeItem";
-import type { ModuleSymbol } from "../shared/type";
+import type { ModuleSymbol, TraceGraph } from "../shared/type";
 import { SearchResult } from "../api";
 
 const StyledTreeView = styled(SimpleTreeView)({
@@ -22,62 +21,63 @@ const StyledTreeView = styled(SimpleTreeView)({
   },
 });
 
-const mapAllModuleSymbolToString = (
-  tracePaths: ModuleSymbol[][]
-): string[][] => {
-  return tracePaths.map((tracePath) =>
-    tracePath.map(({ module_path, symbol_name }) => {
-      if (module_path.startsWith("/")) {
-        return `${symbol_name}@${module_path.slice(1)}`;
-      } else {
-        return `${symbol_name}@${module_path}`;
-      }
-    })
-  );
-};
-
-// Use the same tracePaths references with incremental depth to avoid memory waste.
-function TracePathToTreeView({
-  tracePaths,
-  depth = 0,
-}: {
-  tracePaths: string[][];
-  depth?: number;
-}) {
-  const groups = new Map<string, string[][]>();
-  const group2MaxLength = new Map<string, number>();
-  for (let tracePath of tracePaths) {
-    if (depth < tracePath.length) {
-      const key = tracePath[depth];
-      if (groups.has(key)) {
-        groups.get(key)!.push(tracePath);
-        group2MaxLength.set(
-          key,
-          Math.max(group2MaxLength.get(key)!, tracePath.length)
-        );
-      } else {
-        groups.set(key, [tracePath]);
-        group2MaxLength.set(key, tracePath.length);
-      }
-    }
+const mapModuleSymbolToString = ({
+  module_path,
+  symbol_name,
+}: ModuleSymbol): string => {
+  if (module_path.startsWith("/")) {
+    return `${symbol_name}@${module_path.slice(1)}`;
+  } else {
+    return `${symbol_name}@${module_path}`;
   }
+};
 
-  if (groups.size === 0) {
-    return;
+const getNextNodes = (traceGraph: TraceGraph): number[][] => {
+  const nextNodes: number[][] = traceGraph.nodes.map(() => []);
+  for (const [from, to] of traceGraph.edges) {
+    nextNodes[from].push(to);
   }
+  return nextNodes;
+};
 
-  const treeItems = [];
-  for (let [key, value] of groups) {
-    const uniuqueKey = uuidv4();
-    treeItems.push(
-      <TreeItem key={uniuqueKey} itemId={uniuqueKey} label={key}>
-        {depth + 1 < group2MaxLength.get(key)! ? (
-          <TracePathToTreeView tracePaths={value} depth={depth + 1} />
-        ) : null}
-      </TreeItem>
-    );
-  }
-  return treeItems;
+// Expand the graph into a tree from its first node. A graph can have too
+// many paths to render at once, so the children are only rendered when the
+// item is expanded.
+function TraceGraphToTreeView({
+  traceGraph,
+  nextNodes,
+  node = 0,
+  itemId,
+  expandedItems,
+}: {
+  traceGraph: TraceGraph;
+  nextNodes: number[][];
+  node?: number;
+  itemId: string;
+  expandedItems: Set<string>;
+}) {
+  const children = nextNodes[node];
+  return (
+    <TreeItem
+      itemId={itemId}
+      label={mapModuleSymbolToString(traceGraph.nodes[node])}
+    >
+      {children.length === 0 ? null : expandedItems.has(itemId) ? (
+        children.map((child) => (
+          <TraceGraphToTreeView
+            key={child}
+            traceGraph={traceGraph}
+            nextNodes={nextNodes}
+            node={child}
+            itemId={`${itemId}/${child}`}
+            expandedItems={expandedItems}
+          />
+        ))
+      ) : (
+        <TreeItem itemId={`${itemId}/...`} label="..." />
+      )}
+    </TreeItem>
+  );
 }
 
 export const TreeView = React.memo(function TreeView({
@@ -85,6 +85,11 @@ export const TreeView = React.memo(function TreeView({
 }: {
   data: SearchResult;
 }) {
+  const [expandedItems, setExpandedItems] = React.useState<string[]>([]);
+  const expandedItemSet = React.useMemo(
+    () => new Set(expandedItems),
+    [expandedItems]
+  );
   return (
     <Box
       sx={{
@@ -92,7 +97,10 @@ export const TreeView = React.memo(function TreeView({
         pb: 2,
       }}
     >
-      <StyledTreeView>
+      <StyledTreeView
+        expandedItems={expandedItems}
+        onExpandedItemsChange={(_, itemIds) => setExpandedItems(itemIds)}
+      >
         {Object.entries(data.trace_result).map(
           ([i18nKey, urlToTraceTargets]) => (
             <TreeItem key={i18nKey} itemId={i18nKey} label={i18nKey}>
@@ -102,12 +110,17 @@ export const TreeView = React.memo(function TreeView({
                   itemId={`${i18nKey} - ${url}`}
                   label={url}
                 >
-                  {Object.entries(traceTargets).map(([traceTarget, paths]) => (
-                    <TracePathToTreeView
-                      key={`${i18nKey} - ${url} - ${traceTarget}`}
-                      tracePaths={mapAllModuleSymbolToString(paths)}
-                    />
-                  ))}
+                  {Object.entries(traceTargets).map(([traceTarget, graphs]) =>
+                    graphs.map((traceGraph, i) => (
+                      <TraceGraphToTreeView
+                        key={`${i18nKey} - ${url} - ${traceTarget} - ${i}`}
+                        itemId={`${i18nKey} - ${url} - ${traceTarget} - ${i}`}
+                        traceGraph={traceGraph}
+                        nextNodes={getNextNodes(traceGraph)}
+                        expandedItems={expandedItemSet}
+                      />
+                    ))
+                  )}
                 </TreeItem>
               ))}
             </TreeItem>
@@ -5,12 +5,17 @@ export type ModuleSymbol = {
   module_path: string;
   symbol_name: string;
 };
-export type TracePath = ModuleSymbol[];
+// The symbols from the one using the i18n key (nodes[0]) to the ones
+// used in the url, [from, to] of the edges means `from` is used by `to`.
+export type TraceGraph = {
+  nodes: ModuleSymbol[];
+  edges: [number, number][];
+};
 
 // Trace result might have multiple i18n keys, each i18n key might be
 // used in multiple module symbols, and each module symbol might be
 // used in multiple urls through different paths.
 export type TraceResult = Record<
   I18nKey,
-  Record<Url, Record<string, TracePath[]>>
+  Record<Url, Record<string, TraceGraph[]>>
 >;